@@ -79,6 +79,7 @@ async fn add_new_source() -> color_eyre::eyre::Result<()> {
     let manga_id_selector = "#add-dialog-manga-id > input";
     let manga_source_selector = "#add-dialog-source > input";
     let add_btn_id = "trigger-add-dialog-btn";
+    let preview_btn_id = "add-dialog-preview-btn";
     let submit_btn_id = "add-dialog-add-btn";
 
     // navigate to dashboard
@@ -97,7 +98,11 @@ async fn add_new_source() -> color_eyre::eyre::Result<()> {
     manga_source.send_keys("Shounen\n").await?;
     dbg!("fill source form");
 
-    // submit
+    // preview then confirm
+    c.find(Locator::Id(preview_btn_id)).await?.click().await?;
+    c.wait()
+        .for_element(Locator::Id("add-dialog-preview"))
+        .await?;
     c.find(Locator::Id(submit_btn_id)).await?.click().await?;
     dbg!("submit");
 
@@ -121,6 +126,7 @@ async fn add_duplicate_source_toast_error() -> color_eyre::eyre::Result<()> {
     let manga_id_selector = "#add-dialog-manga-id > input";
     let manga_source_selector = "#add-dialog-source > input";
     let add_btn_id = "trigger-add-dialog-btn";
+    let preview_btn_id = "add-dialog-preview-btn";
     let submit_btn_id = "add-dialog-add-btn";
 
     // navigate to dashboard
@@ -141,7 +147,11 @@ async fn add_duplicate_source_toast_error() -> color_eyre::eyre::Result<()> {
     manga_source.send_keys("YanMaga\n").await?;
     dbg!("fill source form");
 
-    // submit
+    // preview then confirm
+    c.find(Locator::Id(preview_btn_id)).await?.click().await?;
+    c.wait()
+        .for_element(Locator::Id("add-dialog-preview"))
+        .await?;
     c.find(Locator::Id(submit_btn_id)).await?.click().await?;
     dbg!("submit");
 
@@ -0,0 +1,17 @@
+-- Add migration script here
+create table chapters (
+    source MangaSource not null,
+    manga_id text not null,
+    chapter_id text not null,
+    title text not null,
+    url text not null,
+    release_date timestamp not null,
+    first_seen timestamp not null,
+    PRIMARY KEY(source, manga_id, chapter_id),
+    FOREIGN KEY(source, manga_id) REFERENCES series(source, manga_id) ON DELETE CASCADE
+);
+
+-- keep the chapter each tracked series currently points to
+insert into chapters (source, manga_id, chapter_id, title, url, release_date, first_seen)
+select source, manga_id, latest_chapter_title, latest_chapter_title, latest_chapter_url, latest_chapter_release_date, last_update
+from series;
@@ -0,0 +1,25 @@
+-- Add migration script here
+CREATE TYPE FetchErrorKind AS ENUM (
+    'Transient',
+    'LayoutChanged',
+    'NotFound',
+    'Blocked',
+    'WebDriverUnavailable'
+);
+
+create table series_health (
+    source MangaSource not null,
+    manga_id text not null,
+    last_success timestamp,
+    last_attempt timestamp not null,
+    consecutive_failures integer not null default 0,
+    last_error text,
+    last_error_kind FetchErrorKind,
+    PRIMARY KEY(source, manga_id),
+    FOREIGN KEY(source, manga_id) REFERENCES series(source, manga_id) ON DELETE CASCADE
+);
+
+-- every tracked series was fetched fine at least when it was last updated
+insert into series_health (source, manga_id, last_success, last_attempt)
+select source, manga_id, last_update, last_update
+from series;
@@ -0,0 +1,4 @@
+-- Add migration script here
+alter table series_health
+    add column suspended boolean not null default false,
+    add column next_probe_at timestamp;
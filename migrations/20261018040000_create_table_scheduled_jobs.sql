@@ -0,0 +1,6 @@
+-- Add migration script here
+create table scheduled_jobs (
+    name text not null,
+    last_tick timestamp not null,
+    PRIMARY KEY(name)
+);
@@ -0,0 +1,27 @@
+-- Add migration script here
+create table job_runs (
+    id bigserial not null,
+    started_at timestamp not null,
+    finished_at timestamp,
+    checked integer not null default 0,
+    skipped integer not null default 0,
+    released integer not null default 0,
+    upcoming integer not null default 0,
+    no_change integer not null default 0,
+    failed integer not null default 0,
+    notification_failures integer not null default 0,
+    -- set when the run was aborted
+    error text,
+    PRIMARY KEY(id)
+);
+
+create table job_run_errors (
+    run_id bigint not null,
+    source MangaSource not null,
+    manga_id text not null,
+    error_kind FetchErrorKind,
+    error text not null,
+    FOREIGN KEY(run_id) REFERENCES job_runs(id) ON DELETE CASCADE
+);
+
+create index job_run_errors_run_id on job_run_errors(run_id);
@@ -0,0 +1,3 @@
+-- Add migration script here
+alter table series
+    add column tags text[] not null default '{}';
@@ -0,0 +1,13 @@
+-- Add migration script here
+create table notification_outbox (
+    id bigserial primary key,
+    sink text not null,
+    event jsonb not null,
+    created_at timestamp not null default now(),
+    attempts integer not null default 0,
+    last_error text,
+    delivered_at timestamp
+);
+
+create index notification_outbox_pending_idx on notification_outbox (sink, id)
+    where delivered_at is null;
@@ -0,0 +1,23 @@
+-- Add migration script here
+create table users (
+    id bigserial primary key,
+    username text not null unique,
+    password_hash text not null,
+    created_at timestamp not null default now()
+);
+
+create table sessions (
+    token_hash text primary key,
+    user_id bigint not null references users (id) on delete cascade,
+    created_at timestamp not null default now(),
+    expires_at timestamp not null
+);
+
+create table subscriptions (
+    user_id bigint not null references users (id) on delete cascade,
+    source MangaSource not null,
+    manga_id text not null,
+    created_at timestamp not null default now(),
+    primary key (user_id, source, manga_id),
+    foreign key (source, manga_id) references series (source, manga_id) on delete cascade
+);
@@ -0,0 +1,13 @@
+-- Add migration script here
+-- user_id is null for the read state shared by anonymous visitors
+create table read_states (
+    user_id bigint references users (id) on delete cascade,
+    source MangaSource not null,
+    manga_id text not null,
+    chapter_title text not null,
+    chapter_url text not null,
+    read_at timestamp not null,
+    foreign key (source, manga_id) references series (source, manga_id) on delete cascade
+);
+
+create unique index read_states_reader_series on read_states ((coalesce(user_id, 0)), source, manga_id);
@@ -0,0 +1,10 @@
+-- Add migration script here
+create table api_tokens (
+    id bigserial primary key,
+    user_id bigint not null references users (id) on delete cascade,
+    name text not null,
+    token_hash text not null unique,
+    created_at timestamp not null default now(),
+    last_used_at timestamp,
+    unique (user_id, name)
+);
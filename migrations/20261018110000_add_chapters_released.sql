@@ -0,0 +1,10 @@
+-- Add migration script here
+alter table chapters add column released boolean not null default true;
+
+-- release dates are stored as japan local time
+update chapters set released = false
+where release_date > (now() at time zone 'Asia/Tokyo');
+
+update chapters as c set released = s.latest_chapter_released
+from series as s
+where c.source = s.source and c.manga_id = s.manga_id and c.chapter_id = s.latest_chapter_title;
@@ -0,0 +1,6 @@
+-- Add migration script here
+alter table chapters alter column release_date drop not null;
+
+-- these sources don't publish release dates, the stored ones are fetch times
+update chapters set release_date = null
+where source in ('GanganOnline', 'MangaUp', 'MechaComic', 'GANMA');
@@ -0,0 +1,3 @@
+-- Add migration script here
+-- releases found by a refresh without broadcast, recorded as handled without being sent
+alter table notification_outbox add column suppressed boolean not null default false;
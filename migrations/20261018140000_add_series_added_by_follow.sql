@@ -0,0 +1,4 @@
+-- Add migration script here
+-- series created by a follow are removed again once their last follower leaves,
+-- the ones added otherwise are shared and stay tracked
+alter table series add column added_by_follow boolean not null default false;
@@ -0,0 +1,3 @@
+-- Add migration script here
+-- sources the deployment can't fetch at all, a configuration problem rather than a site one
+alter type FetchErrorKind add value 'Unsupported';
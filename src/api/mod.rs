@@ -0,0 +1,409 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::{header::CONTENT_TYPE, StatusCode},
+    response::{IntoResponse, Response},
+    routing::{delete, get},
+    Extension, Json, Router,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    core::types::{ChapterRecord, Manga, MangaQuery, MangaSource, Paginated, TrackedManga, User},
+    server::{
+        auth::Caller,
+        service::{
+            add_manga_service, delete_manga_service, follow_manga_service,
+            retrieve_chapters_service, retrieve_manga_service, unfollow_manga_service, SeriesError,
+        },
+    },
+    state::AppState,
+};
+
+pub const PREFIX: &str = "/api/v1";
+pub const MAX_PAGE_SIZE: i64 = 100;
+
+const OPENAPI: &str = include_str!("openapi.json");
+
+/// stable json routes for scripts, the server functions wire format isn't meant to be relied on
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route("/api/v1/openapi.json", get(openapi))
+        .route("/api/v1/series", get(list_series).post(add_series))
+        .route("/api/v1/series/{source}/{id}", delete(delete_series))
+        .route("/api/v1/series/{source}/{id}/chapters", get(list_chapters))
+}
+
+#[derive(Debug, Serialize)]
+pub struct ApiError {
+    #[serde(skip)]
+    status: StatusCode,
+    error: String,
+}
+
+impl ApiError {
+    fn new(status: StatusCode, error: impl Into<String>) -> Self {
+        Self {
+            status,
+            error: error.into(),
+        }
+    }
+}
+
+impl From<SeriesError> for ApiError {
+    fn from(error: SeriesError) -> Self {
+        let status = match error {
+            SeriesError::Invalid(_) => StatusCode::BAD_REQUEST,
+            SeriesError::AlreadyTracked(_) => StatusCode::CONFLICT,
+            SeriesError::NotTracked(_) => StatusCode::NOT_FOUND,
+            SeriesError::Upstream(_) => StatusCode::BAD_GATEWAY,
+            SeriesError::Internal(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        };
+        Self::new(status, error.to_string())
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        (self.status, Json(self)).into_response()
+    }
+}
+
+/// the user calling the api, anonymous calls are refused when login is required
+fn authorize(
+    state: &AppState,
+    caller: Option<Extension<Caller>>,
+) -> Result<Option<User>, ApiError> {
+    match caller.map(|Extension(caller)| caller) {
+        Some(Caller::User(user)) => Ok(Some(user)),
+        Some(Caller::Admin) => Ok(None),
+        None if state.auth.require_login => {
+            Err(ApiError::new(StatusCode::UNAUTHORIZED, "login required"))
+        }
+        None => Ok(None),
+    }
+}
+
+async fn openapi() -> impl IntoResponse {
+    ([(CONTENT_TYPE, "application/json")], OPENAPI)
+}
+
+#[derive(Debug, Deserialize, Default)]
+#[serde(default)]
+pub struct SeriesParams {
+    pub page: Option<i64>,
+    pub size: Option<i64>,
+    pub source: Option<MangaSource>,
+    pub title: Option<String>,
+    pub author: Option<String>,
+    pub chapter_title: Option<String>,
+    pub unread_only: bool,
+}
+
+async fn list_series(
+    State(state): State<AppState>,
+    caller: Option<Extension<Caller>>,
+    Query(params): Query<SeriesParams>,
+) -> Result<Json<Paginated<Vec<TrackedManga>>>, ApiError> {
+    let user = authorize(&state, caller)?;
+    let page = params.page.unwrap_or(1).max(1);
+    let size = params.size.unwrap_or(20).clamp(1, MAX_PAGE_SIZE);
+
+    let query = MangaQuery {
+        source: params.source,
+        title: params.title,
+        author: params.author,
+        chapter_title: params.chapter_title,
+        day: None,
+        unread_only: params.unread_only,
+        subscriber: user.map(|u| u.id),
+    };
+
+    retrieve_manga_service(page, size, query, state.pool)
+        .await
+        .map(Json)
+        .map_err(|e| ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, e))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct NewSeries {
+    pub source: MangaSource,
+    pub manga_id: String,
+}
+
+async fn add_series(
+    State(state): State<AppState>,
+    caller: Option<Extension<Caller>>,
+    Json(body): Json<NewSeries>,
+) -> Result<(StatusCode, Json<Manga>), ApiError> {
+    let user = authorize(&state, caller)?;
+    let source = Some(body.source);
+
+    match user {
+        Some(user) => {
+            follow_manga_service(
+                user.id,
+                body.manga_id,
+                source,
+                state.webdriver_url,
+                state.pool,
+            )
+            .await
+        }
+        None => add_manga_service(body.manga_id, source, state.webdriver_url, state.pool).await,
+    }
+    .map(|manga| (StatusCode::CREATED, Json(manga)))
+    .map_err(ApiError::from)
+}
+
+async fn delete_series(
+    State(state): State<AppState>,
+    caller: Option<Extension<Caller>>,
+    Path((source, id)): Path<(MangaSource, String)>,
+) -> Result<StatusCode, ApiError> {
+    let caller = caller.map(|Extension(caller)| caller);
+    let manga_list = vec![(source, id)];
+
+    // users only unfollow, deleting a series for everyone is up to the admin
+    match caller {
+        Some(Caller::User(user)) => unfollow_manga_service(user.id, manga_list, state.pool).await,
+        Some(Caller::Admin) => delete_manga_service(manga_list, state.pool).await,
+        None if state.auth.anonymous_is_admin() => {
+            delete_manga_service(manga_list, state.pool).await
+        }
+        None if state.auth.require_login => {
+            return Err(ApiError::new(StatusCode::UNAUTHORIZED, "login required"))
+        }
+        None => return Err(ApiError::new(StatusCode::FORBIDDEN, "admin only")),
+    }
+    .map(|_| StatusCode::NO_CONTENT)
+    .map_err(ApiError::from)
+}
+
+async fn list_chapters(
+    State(state): State<AppState>,
+    caller: Option<Extension<Caller>>,
+    Path((source, id)): Path<(MangaSource, String)>,
+) -> Result<Json<Vec<ChapterRecord>>, ApiError> {
+    authorize(&state, caller)?;
+
+    retrieve_chapters_service(source, id, state.pool)
+        .await
+        .map(Json)
+        .map_err(|e| ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, e))
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::body::{to_bytes, Body};
+    use http::Request;
+    use leptos::config::LeptosOptions;
+    use strum::IntoEnumIterator;
+    use tower::ServiceExt;
+
+    use super::*;
+    use crate::{
+        core::types::Chapter, db::insert::insert_manga, server::auth::AuthConfig,
+        testcontainer::postgres_container::get_test_db,
+    };
+
+    fn app(pool: sqlx::PgPool) -> Router {
+        app_with_auth(pool, AuthConfig::default())
+    }
+
+    fn app_with_auth(pool: sqlx::PgPool, auth: AuthConfig) -> Router {
+        router().with_state(AppState {
+            leptos_options: LeptosOptions::builder()
+                .output_name("manga-tracker")
+                .build(),
+            pool,
+            webdriver_url: "".into(),
+            sinks: Vec::new(),
+            auth,
+        })
+    }
+
+    async fn json(response: Response) -> serde_json::Value {
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        serde_json::from_slice(&body).unwrap()
+    }
+
+    #[test]
+    fn openapi_lists_every_source() {
+        let doc: serde_json::Value = serde_json::from_str(OPENAPI).unwrap();
+        let sources: Vec<_> = doc["components"]["schemas"]["MangaSource"]["enum"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|s| s.as_str().unwrap().to_string())
+            .collect();
+        let expected: Vec<_> = MangaSource::iter()
+            .map(|s| {
+                serde_json::to_value(s)
+                    .unwrap()
+                    .as_str()
+                    .unwrap()
+                    .to_string()
+            })
+            .collect();
+
+        assert_eq!(sources, expected);
+        for path in [
+            "/api/v1/series",
+            "/api/v1/series/{source}/{id}",
+            "/api/v1/series/{source}/{id}/chapters",
+        ] {
+            assert!(doc["paths"].get(path).is_some(), "{path} is not documented");
+        }
+    }
+
+    #[tokio::test]
+    async fn list_then_delete_series() {
+        let db = get_test_db("rest_series").await.unwrap();
+        let manga = Manga::from_chapters(
+            "title".into(),
+            Some("cover".into()),
+            "author".into(),
+            vec![Chapter::new(
+                "chapter 1".into(),
+                "https://example.com/1".into(),
+                "".into(),
+                chrono::Utc::now().fixed_offset(),
+            )],
+        )
+        .unwrap();
+        insert_manga(MangaSource::GammaPlus, "1".into(), manga, &db.0)
+            .await
+            .unwrap();
+
+        let response = app(db.0.clone())
+            .oneshot(
+                Request::get("/api/v1/series?source=GammaPlus&size=500")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = json(response).await;
+        assert_eq!(body["data"][0]["manga_id"], "1");
+        assert_eq!(body["total_page"], 1);
+
+        let response = app(db.0.clone())
+            .oneshot(
+                Request::get("/api/v1/series/GammaPlus/1/chapters")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(json(response).await[0]["title"], "chapter 1");
+
+        let delete = || {
+            Request::delete("/api/v1/series/GammaPlus/1")
+                .body(Body::empty())
+                .unwrap()
+        };
+        let response = app(db.0.clone()).oneshot(delete()).await.unwrap();
+        assert_eq!(response.status(), StatusCode::NO_CONTENT);
+
+        let response = app(db.0).oneshot(delete()).await.unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        assert_eq!(json(response).await["error"], "no manga deleted");
+    }
+
+    #[tokio::test]
+    async fn delete_series_admin_only() {
+        let db = get_test_db("rest_delete_admin").await.unwrap();
+        let auth = AuthConfig {
+            admin_token: Some("secret".into()),
+            ..Default::default()
+        };
+
+        let response = app_with_auth(db.0, auth)
+            .oneshot(
+                Request::delete("/api/v1/series/GammaPlus/1")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+        assert_eq!(json(response).await["error"], "admin only");
+    }
+
+    #[tokio::test]
+    async fn add_tracked_series_conflicts() {
+        let db = get_test_db("rest_add_conflict").await.unwrap();
+        let manga = Manga::from_chapters(
+            "title".into(),
+            Some("cover".into()),
+            "author".into(),
+            vec![Chapter::new(
+                "chapter 1".into(),
+                "https://example.com/1".into(),
+                "".into(),
+                chrono::Utc::now().fixed_offset(),
+            )],
+        )
+        .unwrap();
+        insert_manga(MangaSource::GammaPlus, "1".into(), manga, &db.0)
+            .await
+            .unwrap();
+
+        let response = app(db.0)
+            .oneshot(
+                Request::post("/api/v1/series")
+                    .header(CONTENT_TYPE, "application/json")
+                    .body(Body::from(r#"{"source":"GammaPlus","manga_id":"1"}"#))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::CONFLICT);
+        assert_eq!(json(response).await["error"], "manga already exist in db");
+    }
+
+    #[tokio::test]
+    async fn source_failures_are_bad_gateway() {
+        let db = get_test_db("rest_add_upstream").await.unwrap();
+
+        // the test app has no webdriver, so a source read through one can't be fetched
+        let response = app(db.0)
+            .oneshot(
+                Request::post("/api/v1/series")
+                    .header(CONTENT_TYPE, "application/json")
+                    .body(Body::from(r#"{"source":"Urasunday","manga_id":"1"}"#))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_GATEWAY);
+    }
+
+    #[tokio::test]
+    async fn db_errors_are_server_errors() {
+        let db = get_test_db("rest_db_errors").await.unwrap();
+        db.0.close().await;
+
+        let response = app(db.0.clone())
+            .oneshot(
+                Request::post("/api/v1/series")
+                    .header(CONTENT_TYPE, "application/json")
+                    .body(Body::from(r#"{"source":"GammaPlus","manga_id":"1"}"#))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+
+        let response = app(db.0)
+            .oneshot(
+                Request::delete("/api/v1/series/GammaPlus/1")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+    }
+}
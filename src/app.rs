@@ -2,15 +2,18 @@ use leptos::prelude::*;
 use leptos_meta::{provide_meta_context, MetaTags, Stylesheet, Title};
 use leptos_router::{
     components::{Route, Router, Routes},
-    StaticSegment,
+    ParamSegment, StaticSegment,
 };
 use thaw::{
-    ConfigProvider, Divider, Flex, FlexGap, FlexJustify, Layout, LayoutHeader, Link,
-    ToasterProvider,
+    Button, ButtonAppearance, ConfigProvider, Divider, Flex, FlexAlign, FlexGap, FlexJustify,
+    Layout, LayoutHeader, Link, ToasterProvider,
 };
 
 use crate::pages::dashboard::Dashboard;
 use crate::pages::home::HomePage;
+use crate::pages::jobs::{JobRunPage, JobRuns};
+use crate::pages::login::Login;
+use crate::pages::tokens::ApiTokens;
 
 pub fn shell(options: LeptosOptions) -> impl IntoView {
     view! {
@@ -50,6 +53,13 @@ pub fn App() -> impl IntoView {
                             <Routes fallback=|| "Page not found.".into_view()>
                                 <Route path=StaticSegment("") view=HomePage />
                                 <Route path=StaticSegment("dashboard") view=Dashboard />
+                                <Route path=StaticSegment("login") view=Login />
+                                <Route path=StaticSegment("tokens") view=ApiTokens />
+                                <Route path=StaticSegment("jobs") view=JobRuns />
+                                <Route
+                                    path=(StaticSegment("jobs"), ParamSegment("id"))
+                                    view=JobRunPage
+                                />
                             </Routes>
                         </PageLayout>
                     </main>
@@ -65,7 +75,10 @@ fn PageLayout(children: Children) -> impl IntoView {
         <Layout>
             <Flex vertical=true>
                 <LayoutHeader>
-                    <p>"Manga Tracker"</p>
+                    <Flex justify=FlexJustify::SpaceBetween align=FlexAlign::Center>
+                        <p>"Manga Tracker"</p>
+                        <UserMenu />
+                    </Flex>
                     <Divider />
                 </LayoutHeader>
 
@@ -74,6 +87,8 @@ fn PageLayout(children: Children) -> impl IntoView {
                         <Flex gap=FlexGap::Large vertical=true style="padding-top:12px">
                             <Link href="/">"Home"</Link>
                             <Link href="/dashboard">"Dashboard"</Link>
+                            <Link href="/jobs">"Jobs"</Link>
+                            <Link href="/tokens">"Tokens"</Link>
                         </Flex>
 
                         <div style="position: absolute; left: 115px; height: 100%;">
@@ -88,3 +103,43 @@ fn PageLayout(children: Children) -> impl IntoView {
         </Layout>
     }
 }
+
+#[component]
+fn UserMenu() -> impl IntoView {
+    use crate::server::{current_user, logout};
+
+    let user = Resource::new(|| (), |_| current_user());
+    let handle_logout = move |_| {
+        leptos::task::spawn_local(async move {
+            if logout().await.is_ok() {
+                let _ = window().location().set_href("/login");
+            }
+        })
+    };
+
+    view! {
+        <Transition>
+            {move || {
+                user.get()
+                    .map(|user| match user.ok().flatten() {
+                        Some(user) => {
+                            view! {
+                                <Flex align=FlexAlign::Center>
+                                    <span id="current-user">{user.username}</span>
+                                    <Button
+                                        attr:id="logout-btn"
+                                        appearance=ButtonAppearance::Subtle
+                                        on_click=handle_logout
+                                    >
+                                        "Logout"
+                                    </Button>
+                                </Flex>
+                            }
+                                .into_any()
+                        }
+                        None => view! { <Link href="/login">"Login"</Link> }.into_any(),
+                    })
+            }}
+        </Transition>
+    }
+}
@@ -1,162 +1,137 @@
-use crate::core::parser::{
-    cdata_rss::fetch_cdata_rss, comic_fuz::fetch_comic_fuz, comic_pixiv::fetch_pixiv_data,
-    comic_walker::fetch_comic_walker_data, gangan_online::fetch_gangan_online, ganma::fetch_ganma,
-    manga_up::fetch_mangaup, mecha_comic::fetch_mecha_comic, rss_manga::fetch_generic_rss,
-    urasunday::fetch_urasunday, yanmaga::fetch_yanmaga,
-};
 use fantoccini::error::{CmdError, NewSessionError};
-use http::header;
+use http::StatusCode;
+use thiserror::Error;
 
-use super::types::{Manga, MangaSource};
+use super::{
+    source::{registry, FetchContext, SourceFetcher},
+    types::{FetchErrorKind, Manga, MangaSource},
+};
 
-#[derive(Debug)]
-pub enum FetchError {
+/// error raised by the parser modules, without knowledge of the series being fetched
+#[derive(Debug, Error)]
+pub enum SourceError {
+    #[error("{0}")]
     ReqwestError(reqwest::Error),
+
+    #[error("{0}")]
     JsonDeserializeError(serde_json::Error),
+
+    #[error("{}", .0.as_deref().unwrap_or("Error on deserializing xml"))]
     XmlDeserializeError(Option<String>),
+
+    #[error("{}", .0.as_deref().unwrap_or("Chapter Not Found"))]
     ChapterNotFound(Option<String>),
+
+    #[error("{}", .0.as_deref().unwrap_or("Page Not Found"))]
     PageNotFound(Option<String>),
+
+    /// the site answered but has no such series, as opposed to a page we can't read
+    #[error("{}", .0.as_deref().unwrap_or("Series Not Found"))]
+    SeriesNotFound(Option<String>),
+
+    #[error("{0}")]
     WebDriverSessionError(NewSessionError),
+
+    #[error("{0}")]
     WebDriverCmdError(CmdError),
+
+    /// nothing can fetch the source, the deployment rather than the site needs fixing
+    #[error("{0}")]
+    Unsupported(String),
+}
+
+impl SourceError {
+    pub fn kind(&self) -> FetchErrorKind {
+        match self {
+            SourceError::ReqwestError(e) => match e.status() {
+                Some(status) => status_kind(status),
+                None if e.is_decode() => FetchErrorKind::LayoutChanged,
+                None => FetchErrorKind::Transient,
+            },
+            SourceError::JsonDeserializeError(_)
+            | SourceError::XmlDeserializeError(_)
+            | SourceError::ChapterNotFound(_)
+            | SourceError::PageNotFound(_) => FetchErrorKind::LayoutChanged,
+            SourceError::SeriesNotFound(_) => FetchErrorKind::NotFound,
+            SourceError::WebDriverSessionError(_) => FetchErrorKind::WebDriverUnavailable,
+            SourceError::WebDriverCmdError(_) => FetchErrorKind::Transient,
+            SourceError::Unsupported(_) => FetchErrorKind::Unsupported,
+        }
+    }
+
+    fn url(&self) -> Option<String> {
+        match self {
+            SourceError::ReqwestError(e) => e.url().map(|u| u.to_string()),
+            _ => None,
+        }
+    }
 }
 
-const USER_AGENT: &str = "Mozilla/5.0 (X11; Linux x86_64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/135.0.0.0 Safari/537.36";
+/// only a missing page is reported as not found, other client errors mean our requests no
+/// longer match what the site expects
+fn status_kind(status: StatusCode) -> FetchErrorKind {
+    match status {
+        StatusCode::FORBIDDEN | StatusCode::TOO_MANY_REQUESTS => FetchErrorKind::Blocked,
+        StatusCode::NOT_FOUND | StatusCode::GONE => FetchErrorKind::NotFound,
+        StatusCode::REQUEST_TIMEOUT => FetchErrorKind::Transient,
+        status if status.is_client_error() => FetchErrorKind::LayoutChanged,
+        _ => FetchErrorKind::Transient,
+    }
+}
+
+#[derive(Debug, Error)]
+#[error("{kind} on {manga_source} ({manga_id}) at {url}: {cause}")]
+pub struct FetchError {
+    pub manga_source: MangaSource,
+    pub manga_id: String,
+    pub url: String,
+    pub kind: FetchErrorKind,
+    #[source]
+    pub cause: SourceError,
+}
+
+impl FetchError {
+    pub fn new(manga_source: MangaSource, manga_id: &str, cause: SourceError) -> Self {
+        let url = cause.url().unwrap_or_else(|| match manga_source.fetcher() {
+            Some(fetcher) => fetcher.series_url(manga_id),
+            None => "".into(),
+        });
+
+        Self {
+            manga_source,
+            manga_id: manga_id.to_owned(),
+            url,
+            kind: cause.kind(),
+            cause,
+        }
+    }
+}
 
 impl MangaSource {
+    pub fn fetcher(&self) -> Option<&'static dyn SourceFetcher> {
+        registry().get(self)
+    }
+
+    /// the chapter dates of the source can be recorded as release dates
+    pub fn has_release_dates(&self) -> bool {
+        self.fetcher()
+            .is_none_or(|fetcher| !fetcher.capabilities().undated_chapters)
+    }
+
     pub async fn fetch(&self, webdriver_url: &str, manga_id: &str) -> Result<Manga, FetchError> {
-        let client = {
-            let mut headers = header::HeaderMap::new();
-            headers.insert("User-Agent", header::HeaderValue::from_static(USER_AGENT));
-            reqwest::Client::builder()
-                .default_headers(headers)
-                .build()
-                .unwrap()
-        };
-
-        let manga = match self {
-            MangaSource::Yanmaga => fetch_yanmaga(client, manga_id).await,
-            MangaSource::ShounenJumpPlus => {
-                fetch_generic_rss(
-                    client,
-                    format!("https://shonenjumpplus.com/rss/series/{manga_id}"),
-                )
-                .await
-            }
-            MangaSource::ComicEarthStar => {
-                fetch_generic_rss(
-                    client,
-                    format!("https://comic-earthstar.com/rss/series/{manga_id}"),
-                )
-                .await
-            }
-            MangaSource::KurageBunch => {
-                fetch_generic_rss(
-                    client,
-                    format!("https://kuragebunch.com/rss/series/{manga_id}"),
-                )
-                .await
-            }
-            MangaSource::ComicGrowl => {
-                fetch_cdata_rss(
-                    client,
-                    format!("https://comic-growl.com/series/{manga_id}/rss"),
-                )
-                .await
-            }
-            MangaSource::ComicMedu => {
-                fetch_cdata_rss(
-                    client,
-                    format!("https://comic-medu.com/series/{manga_id}/rss"),
-                )
-                .await
-            }
-            MangaSource::ComicDays => {
-                fetch_generic_rss(
-                    client,
-                    format!("https://comic-days.com/rss/series/{manga_id}"),
-                )
-                .await
-            }
-            MangaSource::MagazinePocket => {
-                fetch_generic_rss(
-                    client,
-                    format!("https://mgpk-cdn.magazinepocket.com/static/rss/{manga_id}/feed.xml"),
-                )
-                .await
-            }
-            MangaSource::TonariYoungJump => {
-                fetch_generic_rss(
-                    client,
-                    format!("https://tonarinoyj.jp/rss/series/{manga_id}"),
-                )
-                .await
-            }
-            MangaSource::SundayWebry => {
-                fetch_generic_rss(
-                    client,
-                    format!("https://www.sunday-webry.com/rss/series/{manga_id}"),
-                )
-                .await
-            }
-            MangaSource::ComicAction => {
-                fetch_generic_rss(
-                    client,
-                    format!("https://comic-action.com/rss/series/{manga_id}"),
-                )
-                .await
-            }
-            MangaSource::ComicGardo => {
-                fetch_generic_rss(
-                    client,
-                    format!("https://comic-gardo.com/rss/series/{manga_id}"),
-                )
-                .await
-            }
-
-            MangaSource::ComicPixiv => fetch_pixiv_data(client, manga_id).await,
-            MangaSource::Urasunday => fetch_urasunday(webdriver_url, manga_id).await,
-            MangaSource::ComicWalker => fetch_comic_walker_data(client, manga_id).await,
-            MangaSource::MangaUp => fetch_mangaup(client, manga_id).await,
-            MangaSource::ComicFuz => fetch_comic_fuz(client, manga_id).await,
-            MangaSource::GanganOnline => fetch_gangan_online(client, manga_id).await,
-            MangaSource::GammaPlus => {
-                fetch_cdata_rss(
-                    client,
-                    format!("https://takecomic.jp/series/{manga_id}/rss"),
-                )
-                .await
-            }
-            MangaSource::ChampionCross => {
-                fetch_cdata_rss(
-                    client,
-                    format!("https://championcross.jp/series/{manga_id}/rss"),
-                )
-                .await
-            }
-            MangaSource::GANMA => fetch_ganma(client, manga_id).await,
-            MangaSource::YoungAnimal => {
-                fetch_cdata_rss(
-                    client,
-                    format!("https://younganimal.com/series/{manga_id}/rss"),
-                )
-                .await
-            }
-            MangaSource::MechaComic => fetch_mecha_comic(client, manga_id).await,
-            MangaSource::YoungChampion => {
-                fetch_cdata_rss(
-                    client,
-                    format!("https://youngchampion.jp/series/{manga_id}/rss"),
-                )
-                .await
-            }
-            MangaSource::IchijinPlus => {
-                fetch_generic_rss(
-                    client,
-                    format!("https://ichicomi.com/rss/series/{manga_id}"),
-                )
-                .await
-            }
-        }?;
+        let fetcher = self.fetcher().ok_or_else(|| {
+            FetchError::new(
+                self.clone(),
+                manga_id,
+                SourceError::Unsupported(format!("no fetcher registered for {self}")),
+            )
+        })?;
+
+        let ctx = FetchContext::new(webdriver_url);
+        let manga = fetcher
+            .fetch(&ctx, manga_id)
+            .await
+            .map_err(|e| FetchError::new(self.clone(), manga_id, e))?;
 
         Ok(self.postprocess(manga))
     }
@@ -164,52 +139,23 @@ impl MangaSource {
     fn postprocess(&self, mut manga: Manga) -> Manga {
         manga.title = self.cleanup_title(&manga.title);
         manga.latest_chapter_url = self.replace_episode_url(&manga.latest_chapter_url);
+        for chapter in manga.chapters.iter_mut() {
+            chapter.url = self.replace_episode_url(&chapter.url);
+        }
         manga
     }
 
     pub fn cleanup_title(&self, title: &str) -> String {
-        let mut removed_suffix = match self {
-            MangaSource::ShounenJumpPlus => title.replace("少年ジャンプ＋", "").trim().to_owned(),
-            MangaSource::ComicEarthStar => title.replace("コミック アース・スター｜毎週木曜・最新話更新！無料で漫画が読めるWEBコミック誌", "").trim().to_owned(),
-            MangaSource::KurageBunch => title.replace("くらげバンチ", "").trim().to_owned(),
-            MangaSource::ComicDays => title.replace("コミックDAYS", "").trim().to_owned(),
-            MangaSource::MagazinePocket => title.replace("マガポケ", "").trim().to_owned(),
-            MangaSource::TonariYoungJump => title.replace("となりのヤングジャンプ", "").trim().to_owned(),
-            MangaSource::SundayWebry => title.replace("サンデーうぇぶり", "").trim().to_owned(),
-            MangaSource::ComicAction => title.replace("webアクション｜双葉社発のマンガサイト", "").trim().to_owned(),
-            MangaSource::IchijinPlus => title.replace("一迅プラス", "").trim().to_owned(),
-            MangaSource::ComicGardo => title.replace("コミックガルド", "").trim().to_owned(),
-            _ => title.to_owned()
-,
-        };
-
-        match self {
-            MangaSource::ShounenJumpPlus
-            | MangaSource::ComicEarthStar
-            | MangaSource::KurageBunch
-            | MangaSource::ComicDays
-            | MangaSource::MagazinePocket
-            | MangaSource::TonariYoungJump
-            | MangaSource::SundayWebry
-            | MangaSource::IchijinPlus
-            | MangaSource::ComicAction
-            | MangaSource::ComicGardo => {
-                removed_suffix.pop();
-                removed_suffix.remove(0);
-            }
-            _ => {}
-        };
-
-        removed_suffix
+        match self.fetcher() {
+            Some(fetcher) => fetcher.cleanup_title(title),
+            None => title.to_owned(),
+        }
     }
 
     pub fn replace_episode_url(&self, url: &str) -> String {
-        // for now this is for megazine pocket since the new url is not working yet
-        match self {
-            MangaSource::MagazinePocket => url
-                .replace("mgpk-web.magazinepocket.com", "pocket.shonenmagazine.com")
-                .to_owned(),
-            _ => url.to_owned(),
+        match self.fetcher() {
+            Some(fetcher) => fetcher.replace_episode_url(url),
+            None => url.to_owned(),
         }
     }
 }
@@ -219,6 +165,38 @@ mod tests {
 
     use super::*;
 
+    #[test]
+    fn classify_status() {
+        assert_eq!(status_kind(StatusCode::FORBIDDEN), FetchErrorKind::Blocked);
+        assert_eq!(
+            status_kind(StatusCode::TOO_MANY_REQUESTS),
+            FetchErrorKind::Blocked
+        );
+        assert_eq!(status_kind(StatusCode::NOT_FOUND), FetchErrorKind::NotFound);
+        assert_eq!(status_kind(StatusCode::GONE), FetchErrorKind::NotFound);
+        assert_eq!(
+            status_kind(StatusCode::REQUEST_TIMEOUT),
+            FetchErrorKind::Transient
+        );
+        assert_eq!(
+            status_kind(StatusCode::BAD_REQUEST),
+            FetchErrorKind::LayoutChanged
+        );
+        assert_eq!(
+            status_kind(StatusCode::BAD_GATEWAY),
+            FetchErrorKind::Transient
+        );
+    }
+
+    #[test]
+    fn classify_configuration_errors() {
+        let no_fetcher = SourceError::Unsupported("no fetcher registered".into());
+        assert_eq!(no_fetcher.kind(), FetchErrorKind::Unsupported);
+
+        let missing = SourceError::SeriesNotFound(Some("chapters are empty".into()));
+        assert_eq!(missing.kind(), FetchErrorKind::NotFound);
+    }
+
     #[test]
     fn test_cleanup_shounen_jump_plus() {
         let title = "少年ジャンプ＋（魔都精兵のスレイブ）";
@@ -229,6 +207,14 @@ mod tests {
         assert_eq!(got, expected);
     }
 
+    #[test]
+    fn test_cleanup_empty_title() {
+        let source = MangaSource::ShounenJumpPlus;
+
+        assert_eq!(source.cleanup_title(""), "");
+        assert_eq!(source.cleanup_title("少年ジャンプ＋"), "");
+    }
+
     #[test]
     fn test_cleanup_comic_earth_star() {
         let title = "コミック アース・スター｜毎週木曜・最新話更新！無料で漫画が読めるWEBコミック誌（願いを叶えてもらおうと悪魔を召喚したけど、可愛かったので結婚しました　～悪魔の新妻～）";
@@ -330,6 +316,22 @@ mod tests {
         assert_eq!(got, expected);
     }
 
+    #[test]
+    fn test_fetch_error_context() {
+        let err = FetchError::new(
+            MangaSource::ComicFuz,
+            "123",
+            SourceError::PageNotFound(Some("title not found".into())),
+        );
+
+        assert_eq!(err.kind, FetchErrorKind::LayoutChanged);
+        assert_eq!(err.url, "https://comic-fuz.com/manga/123");
+        assert_eq!(
+            err.to_string(),
+            "Site layout changed on Comic Fuz (123) at https://comic-fuz.com/manga/123: title not found"
+        );
+    }
+
     #[test]
     fn test_cleanup_do_nothing() {
         let title = "恋する(おとめ)の作り方";
@@ -3,4 +3,8 @@ pub mod fetch;
 
 #[cfg(feature = "ssr")]
 pub mod parser;
+#[cfg(feature = "ssr")]
+pub mod resolve;
+#[cfg(feature = "ssr")]
+pub mod source;
 pub mod types;
@@ -1,10 +1,71 @@
-use chrono::{DateTime, Datelike, FixedOffset};
-use chrono_tz::Japan;
+use chrono::{DateTime, FixedOffset};
 use reqwest::Client;
 use xmlserde::{xml_deserialize_from_str, XmlValue};
 use xmlserde_derives::XmlDeserialize;
 
-use crate::core::{fetch::FetchError, types::Manga};
+use crate::core::{
+    fetch::SourceError,
+    source::{FetchContext, FetchFuture, SourceFetcher, SourceRegistry},
+    types::{Chapter, Manga, MangaSource},
+};
+
+/// Sites served by comici, their rss wraps every value inside CDATA
+pub struct CdataRssSource {
+    source: MangaSource,
+    feed_url: &'static str,
+}
+
+impl SourceFetcher for CdataRssSource {
+    fn source(&self) -> MangaSource {
+        self.source.clone()
+    }
+
+    fn fetch<'a>(&'a self, ctx: &'a FetchContext, manga_id: &'a str) -> FetchFuture<'a> {
+        Box::pin(fetch_cdata_rss(
+            ctx.client.clone(),
+            self.feed_url.replace("{manga_id}", manga_id),
+        ))
+    }
+
+    fn series_url(&self, manga_id: &str) -> String {
+        self.feed_url
+            .trim_end_matches("/rss")
+            .replace("{manga_id}", manga_id)
+    }
+}
+
+pub fn register(registry: &mut SourceRegistry) {
+    let sources = [
+        (
+            MangaSource::ComicGrowl,
+            "https://comic-growl.com/series/{manga_id}/rss",
+        ),
+        (
+            MangaSource::ComicMedu,
+            "https://comic-medu.com/series/{manga_id}/rss",
+        ),
+        (
+            MangaSource::GammaPlus,
+            "https://takecomic.jp/series/{manga_id}/rss",
+        ),
+        (
+            MangaSource::ChampionCross,
+            "https://championcross.jp/series/{manga_id}/rss",
+        ),
+        (
+            MangaSource::YoungAnimal,
+            "https://younganimal.com/series/{manga_id}/rss",
+        ),
+        (
+            MangaSource::YoungChampion,
+            "https://youngchampion.jp/series/{manga_id}/rss",
+        ),
+    ];
+
+    for (source, feed_url) in sources {
+        registry.register(CdataRssSource { source, feed_url });
+    }
+}
 
 #[derive(Debug, XmlDeserialize)]
 #[xmlserde(root = b"rss")]
@@ -23,44 +84,48 @@ pub struct Channel {
 }
 
 impl TryFrom<Channel> for Manga {
-    type Error = FetchError;
+    type Error = SourceError;
 
     fn try_from(value: Channel) -> Result<Self, Self::Error> {
-        let latest_chapter = value
+        let author = value
             .item
             .first()
-            .ok_or(FetchError::ChapterNotFound(None))?;
-        let release_date = &latest_chapter.pub_date.date.0.with_timezone(&Japan);
-
-        let thumbnail = {
-            let t = latest_chapter.thumbnail.inner.clone();
-            let u = latest_chapter.thumbnail.url.clone();
-
-            let mut res = "".into();
-
-            if let Some(val) = t {
-                res = val;
-            } else if let Some(url) = u {
-                res = url;
-            }
-
-            res
-        };
-
-        Ok(Self {
-            title: value.title.inner.trim().to_owned(),
-            cover_url: thumbnail,
-            author: latest_chapter
-                .creator
-                .inner
-                .clone()
-                .unwrap_or("".to_owned())
-                .clone(),
-            latest_chapter_title: latest_chapter.title.inner.trim().to_owned(),
-            latest_chapter_url: latest_chapter.link.inner.clone(),
-            latest_chapter_release_date: latest_chapter.pub_date.date.0,
-            latest_chapter_publish_day: release_date.weekday(),
-        })
+            .ok_or(SourceError::ChapterNotFound(None))?
+            .creator
+            .inner
+            .clone()
+            .unwrap_or("".to_owned());
+
+        let chapters = value
+            .item
+            .into_iter()
+            .map(|item| {
+                let thumbnail = {
+                    let t = item.thumbnail.inner;
+                    let u = item.thumbnail.url;
+
+                    let mut res = "".into();
+
+                    if let Some(val) = t {
+                        res = val;
+                    } else if let Some(url) = u {
+                        res = url;
+                    }
+
+                    res
+                };
+
+                Chapter::new(
+                    item.title.inner.trim().to_owned(),
+                    item.link.inner,
+                    thumbnail,
+                    item.pub_date.date.0,
+                )
+            })
+            .collect();
+
+        Manga::from_chapters(value.title.inner.trim().to_owned(), None, author, chapters)
+            .ok_or(SourceError::ChapterNotFound(None))
     }
 }
 
@@ -125,24 +190,24 @@ impl XmlValue for Rfc2822Date {
     }
 }
 
-pub fn parse_cdata_xml(xml: String) -> Result<Manga, FetchError> {
+pub fn parse_cdata_xml(xml: String) -> Result<Manga, SourceError> {
     let result: Rss = xml_deserialize_from_str(&xml.replace("<![CDATA[", "").replace("]]>", ""))
-        .map_err(|e| FetchError::XmlDeserializeError(Some(e)))?;
+        .map_err(|e| SourceError::XmlDeserializeError(Some(e)))?;
 
     Manga::try_from(result.channel)
 }
 
-pub async fn fetch_cdata_rss(client: Client, url: String) -> Result<Manga, FetchError> {
+pub async fn fetch_cdata_rss(client: Client, url: String) -> Result<Manga, SourceError> {
     let rss = client
         .get(url)
         .send()
         .await
-        .map_err(FetchError::ReqwestError)?
+        .map_err(SourceError::ReqwestError)?
         .error_for_status()
-        .map_err(FetchError::ReqwestError)?
+        .map_err(SourceError::ReqwestError)?
         .text()
         .await
-        .map_err(FetchError::ReqwestError)?;
+        .map_err(SourceError::ReqwestError)?;
 
     parse_cdata_xml(rss)
 }
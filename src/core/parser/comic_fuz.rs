@@ -1,10 +1,14 @@
-use chrono::{Datelike, Local, NaiveDate, NaiveTime, TimeZone};
-use chrono_tz::Japan;
-use reqwest::Client;
+use chrono::{Local, NaiveDate, NaiveTime, TimeZone};
+use reqwest::{Client, Url};
 use scraper::{Html, Selector};
 use serde::{Deserialize, Serialize};
 
-use crate::core::{fetch::FetchError, types::Manga};
+use crate::core::{
+    fetch::SourceError,
+    parser::parsed_chapters,
+    source::{match_url_template, FetchContext, FetchFuture, SourceFetcher, SourceRegistry},
+    types::{Chapter as MangaChapter, Manga, MangaSource},
+};
 
 #[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -61,14 +65,14 @@ pub struct MangaInfo {
     pub manga_name: String,
 }
 
-pub fn parse_comic_fuz_from_html(html: String) -> Result<Manga, FetchError> {
+pub fn parse_comic_fuz_from_html(html: String) -> Result<Manga, SourceError> {
     let next_data_selector = Selector::parse(r#"script[id="__NEXT_DATA__"]"#).unwrap();
     let document = Html::parse_document(&html);
 
     let next_data = document
         .select(&next_data_selector)
         .next()
-        .ok_or(FetchError::PageNotFound(Some(
+        .ok_or(SourceError::PageNotFound(Some(
             "__NEXT_DATA__ not found".into(),
         )))?
         .inner_html();
@@ -76,7 +80,7 @@ pub fn parse_comic_fuz_from_html(html: String) -> Result<Manga, FetchError> {
     let data = {
         let obj: ComicFuz = serde_json::from_str(&next_data).map_err(|e| {
             dbg!(&e);
-            FetchError::PageNotFound(Some(e.to_string()))
+            SourceError::PageNotFound(Some(e.to_string()))
         })?;
         obj.props.page_props
     };
@@ -88,62 +92,86 @@ pub fn parse_comic_fuz_from_html(html: String) -> Result<Manga, FetchError> {
         .collect::<Vec<_>>()
         .join(",");
 
-    let latest_chapter = data
-        .chapters
-        .first()
-        .ok_or(FetchError::ChapterNotFound(Some(
-            "chapters is empty".into(),
-        )))?
-        .chapters
-        .first()
-        .ok_or(FetchError::ChapterNotFound(Some(
-            "nested chapters is empty".into(),
-        )))?;
-
-    let release_date = match &latest_chapter.updated_date {
-        Some(raw) => {
-            let naive_date = NaiveDate::parse_from_str(raw, "%Y/%m/%d").map_err(|e| {
-                FetchError::ChapterNotFound(Some(format!("error on date parse {} : {}", &raw, e)))
-            })?;
-
-            Local
-                .from_local_datetime(&naive_date.and_time(NaiveTime::default()))
-                .unwrap()
-        }
-        None => Local::now(),
-    };
-
-    Ok(Manga {
-        title: data.manga.manga_name.to_owned(),
-        cover_url: format!("https://img.comic-fuz.com{}", latest_chapter.thumbnail_url),
-        author,
-        latest_chapter_title: latest_chapter.chapter_main_name.to_owned(),
-        latest_chapter_url: format!(
-            "https://comic-fuz.com/manga/viewer/{}",
-            latest_chapter.chapter_id
-        ),
-        latest_chapter_release_date: release_date.fixed_offset(),
-        latest_chapter_publish_day: release_date.with_timezone(&Japan).weekday(),
-    })
+    let chapters = parsed_chapters(
+        MangaSource::ComicFuz,
+        data.chapters
+            .iter()
+            .flat_map(|group| group.chapters.iter())
+            .map(|chapter| -> Result<MangaChapter, SourceError> {
+                let release_date = match &chapter.updated_date {
+                    Some(raw) => {
+                        let naive_date =
+                            NaiveDate::parse_from_str(raw, "%Y/%m/%d").map_err(|e| {
+                                SourceError::ChapterNotFound(Some(format!(
+                                    "error on date parse {} : {}",
+                                    &raw, e
+                                )))
+                            })?;
+
+                        Local
+                            .from_local_datetime(&naive_date.and_time(NaiveTime::default()))
+                            .unwrap()
+                    }
+                    None => Local::now(),
+                };
+
+                Ok(MangaChapter::new(
+                    chapter.chapter_main_name.to_owned(),
+                    format!("https://comic-fuz.com/manga/viewer/{}", chapter.chapter_id),
+                    format!("https://img.comic-fuz.com{}", chapter.thumbnail_url),
+                    release_date.fixed_offset(),
+                ))
+            }),
+    )?;
+
+    Manga::from_chapters(data.manga.manga_name.to_owned(), None, author, chapters).ok_or(
+        SourceError::ChapterNotFound(Some("chapters is empty".into())),
+    )
 }
 
-pub async fn fetch_comic_fuz(client: Client, manga_id: &str) -> Result<Manga, FetchError> {
+pub async fn fetch_comic_fuz(client: Client, manga_id: &str) -> Result<Manga, SourceError> {
     let url = format!("https://comic-fuz.com/manga/{manga_id}");
 
     let html = client
         .get(url)
         .send()
         .await
-        .map_err(FetchError::ReqwestError)?
+        .map_err(SourceError::ReqwestError)?
         .error_for_status()
-        .map_err(FetchError::ReqwestError)?
+        .map_err(SourceError::ReqwestError)?
         .text()
         .await
-        .map_err(FetchError::ReqwestError)?;
+        .map_err(SourceError::ReqwestError)?;
 
     parse_comic_fuz_from_html(html)
 }
 
+pub struct ComicFuzSource;
+
+impl SourceFetcher for ComicFuzSource {
+    fn source(&self) -> MangaSource {
+        MangaSource::ComicFuz
+    }
+
+    fn fetch<'a>(&'a self, ctx: &'a FetchContext, manga_id: &'a str) -> FetchFuture<'a> {
+        Box::pin(fetch_comic_fuz(ctx.client.clone(), manga_id))
+    }
+
+    fn series_url(&self, manga_id: &str) -> String {
+        format!("https://comic-fuz.com/manga/{manga_id}")
+    }
+
+    // the viewer lives under /manga too, series ids are numeric
+    fn manga_id_from_url(&self, url: &Url) -> Option<String> {
+        match_url_template(&self.series_url("{manga_id}"), url)
+            .filter(|id| id.chars().all(|c| c.is_ascii_digit()))
+    }
+}
+
+pub fn register(registry: &mut SourceRegistry) {
+    registry.register(ComicFuzSource);
+}
+
 #[cfg(test)]
 mod tests {
     use std::fs;
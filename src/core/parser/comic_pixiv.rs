@@ -1,12 +1,12 @@
 use chrono::DateTime;
-use chrono::Datelike;
 use chrono_tz::Japan;
 use reqwest::Client;
 use serde::Deserialize;
 use serde::Serialize;
 
-use crate::core::fetch::FetchError;
-use crate::core::types::Manga;
+use crate::core::fetch::SourceError;
+use crate::core::source::{FetchContext, FetchFuture, SourceFetcher, SourceRegistry};
+use crate::core::types::{Chapter, Manga, MangaSource};
 
 // metadata struct
 #[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -75,16 +75,18 @@ pub struct EpisodeDetail {
     pub state: String,
 }
 
-pub async fn fetch_pixiv_data(client: Client, id: &str) -> Result<Manga, FetchError> {
+pub async fn fetch_pixiv_data(client: Client, id: &str) -> Result<Manga, SourceError> {
     let metadata = client
         .get(format!("https://comic.pixiv.net/api/app/works/v5/{id}"))
         .header("x-requested-with", "pixivcomic")
         .send()
         .await
-        .map_err(FetchError::ReqwestError)?
+        .map_err(SourceError::ReqwestError)?
+        .error_for_status()
+        .map_err(SourceError::ReqwestError)?
         .json::<Metadata>()
         .await
-        .map_err(FetchError::ReqwestError)?;
+        .map_err(SourceError::ReqwestError)?;
 
     let details = client
         .get(format!(
@@ -93,39 +95,66 @@ pub async fn fetch_pixiv_data(client: Client, id: &str) -> Result<Manga, FetchEr
         .header("x-requested-with", "pixivcomic")
         .send()
         .await
-        .map_err(FetchError::ReqwestError)?
+        .map_err(SourceError::ReqwestError)?
+        .error_for_status()
+        .map_err(SourceError::ReqwestError)?
         .json::<Detail>()
         .await
-        .map_err(FetchError::ReqwestError)?;
+        .map_err(SourceError::ReqwestError)?;
 
     if details.data.episodes.is_empty() {
-        return Err(FetchError::ChapterNotFound(Some(
+        return Err(SourceError::ChapterNotFound(Some(
             "episodes is empty".into(),
         )));
     }
 
-    let latest_episode = details
+    let chapters = details
         .data
         .episodes
         .into_iter()
-        .find(|d| d.state.ne("not_publishing"))
-        .ok_or(FetchError::ChapterNotFound(Some(
-            "latest episode not found".into(),
-        )))?;
-
-    let episode_detail = latest_episode.episode.unwrap();
-
-    let release_date = DateTime::from_timestamp_millis(episode_detail.read_start_at)
-        .unwrap()
-        .with_timezone(&Japan);
-
-    Ok(Manga {
-        title: metadata.data.official_work.name,
-        cover_url: episode_detail.thumbnail_image_url.clone(),
-        author: metadata.data.official_work.author,
-        latest_chapter_title: episode_detail.numbering_title.clone(),
-        latest_chapter_url: format!("https://comic.pixiv.net{}", episode_detail.viewer_path),
-        latest_chapter_release_date: release_date.fixed_offset(),
-        latest_chapter_publish_day: release_date.weekday(),
-    })
+        .filter(|d| d.state.ne("not_publishing"))
+        .filter_map(|d| d.episode)
+        .map(|episode| {
+            let release_date = DateTime::from_timestamp_millis(episode.read_start_at)
+                .unwrap()
+                .with_timezone(&Japan);
+
+            Chapter::new(
+                episode.numbering_title,
+                format!("https://comic.pixiv.net{}", episode.viewer_path),
+                episode.thumbnail_image_url,
+                release_date.fixed_offset(),
+            )
+        })
+        .collect();
+
+    Manga::from_chapters(
+        metadata.data.official_work.name,
+        None,
+        metadata.data.official_work.author,
+        chapters,
+    )
+    .ok_or(SourceError::ChapterNotFound(Some(
+        "latest episode not found".into(),
+    )))
+}
+
+pub struct ComicPixivSource;
+
+impl SourceFetcher for ComicPixivSource {
+    fn source(&self) -> MangaSource {
+        MangaSource::ComicPixiv
+    }
+
+    fn fetch<'a>(&'a self, ctx: &'a FetchContext, manga_id: &'a str) -> FetchFuture<'a> {
+        Box::pin(fetch_pixiv_data(ctx.client.clone(), manga_id))
+    }
+
+    fn series_url(&self, manga_id: &str) -> String {
+        format!("https://comic.pixiv.net/works/{manga_id}")
+    }
+}
+
+pub fn register(registry: &mut SourceRegistry) {
+    registry.register(ComicPixivSource);
 }
@@ -1,13 +1,13 @@
 use chrono::DateTime;
-use chrono::Datelike;
 use chrono::Local;
 use chrono_tz::Japan;
 use reqwest::Client;
 use serde::Deserialize;
 use serde::Serialize;
 
-use crate::core::fetch::FetchError;
-use crate::core::types::Manga;
+use crate::core::fetch::SourceError;
+use crate::core::source::{FetchContext, FetchFuture, SourceFetcher, SourceRegistry};
+use crate::core::types::{Chapter, Manga, MangaSource};
 
 #[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -75,7 +75,7 @@ pub struct Internal {
     pub episodetype: String,
 }
 
-pub async fn fetch_comic_walker_data(client: Client, id: &str) -> Result<Manga, FetchError> {
+pub async fn fetch_comic_walker_data(client: Client, id: &str) -> Result<Manga, SourceError> {
     let data = client
         .get(format!(
             "https://comic-walker.com/api/contents/details/work?workCode={id}"
@@ -83,18 +83,12 @@ pub async fn fetch_comic_walker_data(client: Client, id: &str) -> Result<Manga,
 /*         .header("User-Agent", "Mozilla/5.0 (Macintosh; Intel Mac OS X 10_15_7) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/127.0.0.0 Safari/537.36") */
         .send()
         .await
-        .map_err(FetchError::ReqwestError)?
+        .map_err(SourceError::ReqwestError)?
+        .error_for_status()
+        .map_err(SourceError::ReqwestError)?
         .json::<ComicWalkerData>()
         .await
-        .map_err(FetchError::ReqwestError)?;
-
-    let latest_chapter = data
-        .latest_episodes
-        .result
-        .first()
-        .ok_or(FetchError::ChapterNotFound(Some(
-            "episodes is empty".into(),
-        )))?;
+        .map_err(SourceError::ReqwestError)?;
 
     let author = data
         .work
@@ -104,23 +98,49 @@ pub async fn fetch_comic_walker_data(client: Client, id: &str) -> Result<Manga,
         .collect::<Vec<_>>()
         .join(",");
 
-    let release_date = latest_chapter.update_date.with_timezone(&Japan);
-
-    Ok(Manga {
-        title: data.work.title,
-        cover_url: latest_chapter
-            .original_thumbnail
-            .to_owned()
-            .unwrap_or(data.work.original_thumbnail.clone()),
-        author,
-        latest_chapter_title: latest_chapter.title.to_owned(),
-        latest_chapter_url: format!(
-            "https://comic-walker.com/detail/{}/episodes/{}",
-            id, &latest_chapter.code
-        ),
-        latest_chapter_release_date: release_date.fixed_offset(),
-        latest_chapter_publish_day: release_date.weekday(),
-    })
+    let chapters = data
+        .latest_episodes
+        .result
+        .iter()
+        .map(|episode| {
+            Chapter::new(
+                episode.title.to_owned(),
+                format!(
+                    "https://comic-walker.com/detail/{}/episodes/{}",
+                    id, &episode.code
+                ),
+                episode
+                    .original_thumbnail
+                    .to_owned()
+                    .unwrap_or(data.work.original_thumbnail.clone()),
+                episode.update_date.with_timezone(&Japan).fixed_offset(),
+            )
+        })
+        .collect();
+
+    Manga::from_chapters(data.work.title, None, author, chapters).ok_or(
+        SourceError::ChapterNotFound(Some("episodes is empty".into())),
+    )
+}
+
+pub struct ComicWalkerSource;
+
+impl SourceFetcher for ComicWalkerSource {
+    fn source(&self) -> MangaSource {
+        MangaSource::ComicWalker
+    }
+
+    fn fetch<'a>(&'a self, ctx: &'a FetchContext, manga_id: &'a str) -> FetchFuture<'a> {
+        Box::pin(fetch_comic_walker_data(ctx.client.clone(), manga_id))
+    }
+
+    fn series_url(&self, manga_id: &str) -> String {
+        format!("https://comic-walker.com/detail/{manga_id}")
+    }
+}
+
+pub fn register(registry: &mut SourceRegistry) {
+    registry.register(ComicWalkerSource);
 }
 
 #[cfg(test)]
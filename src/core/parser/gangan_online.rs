@@ -1,10 +1,13 @@
-use chrono::{Datelike, Local};
-use chrono_tz::Japan;
+use chrono::Local;
 use reqwest::Client;
 use scraper::{Html, Selector};
 use serde::{Deserialize, Serialize};
 
-use crate::core::{fetch::FetchError, types::Manga};
+use crate::core::{
+    fetch::SourceError,
+    source::{Capabilities, FetchContext, FetchFuture, SourceFetcher, SourceRegistry},
+    types::{Chapter as MangaChapter, Manga, MangaSource},
+};
 
 #[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -54,14 +57,14 @@ pub struct Chapter {
     pub publishing_period: Option<String>,
 }
 
-pub fn parse_gangan_online_from_html(html: String) -> Result<Manga, FetchError> {
+pub fn parse_gangan_online_from_html(html: String) -> Result<Manga, SourceError> {
     let next_data_selector = Selector::parse(r#"script[id="__NEXT_DATA__"]"#).unwrap();
     let document = Html::parse_document(&html);
 
     let next_data = document
         .select(&next_data_selector)
         .next()
-        .ok_or(FetchError::PageNotFound(Some(
+        .ok_or(SourceError::PageNotFound(Some(
             "__NEXT_DATA__ not found".into(),
         )))?
         .inner_html();
@@ -69,57 +72,90 @@ pub fn parse_gangan_online_from_html(html: String) -> Result<Manga, FetchError>
     let data = {
         let obj: GanganOnline = serde_json::from_str(&next_data).map_err(|e| {
             dbg!(&e);
-            FetchError::JsonDeserializeError(e)
+            SourceError::JsonDeserializeError(e)
         })?;
 
         obj.props.page_props.data.default
     };
 
-    let latest_chapter = data
+    let chapters = data
         .chapters
-        .first()
-        .ok_or(FetchError::PageNotFound(Some("chapters are empty".into())))?;
-
-    let latest_chapter_title = if latest_chapter.sub_text.is_some() {
-        latest_chapter.sub_text.clone().unwrap()
-    } else {
-        latest_chapter.main_text.to_owned()
-    };
-
-    Ok(Manga {
-        title: data.title_name.to_owned(),
-        cover_url: format!(
-            "https://www.ganganonline.com{}",
-            latest_chapter.thumbnail_url
-        ),
-        author: data.author.to_owned(),
-        latest_chapter_title,
-        latest_chapter_url: format!(
-            "https://www.ganganonline.com/title/{}/chapter/{}",
-            data.title_id, latest_chapter.id
-        ),
-        latest_chapter_release_date: Local::now().fixed_offset(),
-        latest_chapter_publish_day: Local::now().with_timezone(&Japan).weekday(),
-    })
+        .iter()
+        .map(|chapter| {
+            let chapter_title = if chapter.sub_text.is_some() {
+                chapter.sub_text.clone().unwrap()
+            } else {
+                chapter.main_text.to_owned()
+            };
+
+            MangaChapter::new(
+                chapter_title,
+                format!(
+                    "https://www.ganganonline.com/title/{}/chapter/{}",
+                    data.title_id, chapter.id
+                ),
+                format!("https://www.ganganonline.com{}", chapter.thumbnail_url),
+                Local::now().fixed_offset(),
+            )
+        })
+        .collect();
+
+    Manga::from_chapters(
+        data.title_name.to_owned(),
+        None,
+        data.author.to_owned(),
+        chapters,
+    )
+    // unknown titles still get a page, only without any chapter
+    .ok_or(SourceError::SeriesNotFound(Some(
+        "chapters are empty".into(),
+    )))
 }
 
-pub async fn fetch_gangan_online(client: Client, manga_id: &str) -> Result<Manga, FetchError> {
+pub async fn fetch_gangan_online(client: Client, manga_id: &str) -> Result<Manga, SourceError> {
     let url = format!("https://www.ganganonline.com/title/{manga_id}");
 
     let html = client
         .get(url)
         .send()
         .await
-        .map_err(FetchError::ReqwestError)?
+        .map_err(SourceError::ReqwestError)?
         .error_for_status()
-        .map_err(FetchError::ReqwestError)?
+        .map_err(SourceError::ReqwestError)?
         .text()
         .await
-        .map_err(FetchError::ReqwestError)?;
+        .map_err(SourceError::ReqwestError)?;
 
     parse_gangan_online_from_html(html)
 }
 
+pub struct GanganOnlineSource;
+
+impl SourceFetcher for GanganOnlineSource {
+    fn source(&self) -> MangaSource {
+        MangaSource::GanganOnline
+    }
+
+    fn fetch<'a>(&'a self, ctx: &'a FetchContext, manga_id: &'a str) -> FetchFuture<'a> {
+        Box::pin(fetch_gangan_online(ctx.client.clone(), manga_id))
+    }
+
+    fn series_url(&self, manga_id: &str) -> String {
+        format!("https://www.ganganonline.com/title/{manga_id}")
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            undated_chapters: true,
+            ..Default::default()
+        }
+    }
+}
+
+pub fn register(registry: &mut SourceRegistry) {
+    registry.register(GanganOnlineSource);
+}
+
 #[cfg(test)]
 mod tests {
     use std::fs;
@@ -1,10 +1,14 @@
-use chrono::{Datelike, Local};
+use chrono::Local;
 use reqwest::Client;
 use scraper::{Html, Selector};
 
-use crate::core::{fetch::FetchError, types::Manga};
+use crate::core::{
+    fetch::SourceError,
+    source::{Capabilities, FetchContext, FetchFuture, SourceFetcher, SourceRegistry},
+    types::{Chapter, Manga, MangaSource},
+};
 
-pub fn parse_ganma_from_html(html: String) -> Result<Manga, FetchError> {
+pub fn parse_ganma_from_html(html: String) -> Result<Manga, SourceError> {
     let title_selector =
         Selector::parse(r#"h1[class="text-lg font-semibold leading-tight"]"#).unwrap();
     let author_selector = Selector::parse(r#"div[class="font-semibold"]"#).unwrap();
@@ -19,67 +23,95 @@ pub fn parse_ganma_from_html(html: String) -> Result<Manga, FetchError> {
     let title = document
         .select(&title_selector)
         .next()
-        .ok_or(FetchError::PageNotFound(Some("title not found".into())))?
+        .ok_or(SourceError::PageNotFound(Some("title not found".into())))?
         .inner_html();
     let author = document
         .select(&author_selector)
         .next()
-        .ok_or(FetchError::PageNotFound(Some("author not found".into())))?
+        .ok_or(SourceError::PageNotFound(Some("author not found".into())))?
         .inner_html();
 
     // latest chapter only include total chapter number
     let cover_url = document
         .select(&cover_selector)
         .next()
-        .ok_or(FetchError::ChapterNotFound(Some("cover not found".into())))?
+        .ok_or(SourceError::ChapterNotFound(Some("cover not found".into())))?
         .attr("src")
-        .ok_or(FetchError::ChapterNotFound(Some(
+        .ok_or(SourceError::ChapterNotFound(Some(
             "cover src attribute not found".into(),
         )))?; // use series thumbnail
     let total_chapter_count = document
         .select(&total_chapter_selector)
         .next()
-        .ok_or(FetchError::ChapterNotFound(Some(
+        .ok_or(SourceError::ChapterNotFound(Some(
             "chapter count not found".into(),
         )))?
         .inner_html();
     let chapter_url = document
         .select(&chapter_url_selector)
         .next()
-        .ok_or(FetchError::ChapterNotFound(Some("url not found".into())))?
+        .ok_or(SourceError::ChapterNotFound(Some("url not found".into())))?
         .attr("href")
-        .ok_or(FetchError::ChapterNotFound(Some(
+        .ok_or(SourceError::ChapterNotFound(Some(
             "url href attribute not found".into(),
         )))?; // the url redirect to app store / play store
 
-    Ok(Manga {
-        title,
-        cover_url: cover_url.to_owned(),
-        author,
-        latest_chapter_title: total_chapter_count.replace("<!-- -->", ""),
-        latest_chapter_url: chapter_url.to_owned(),
-        latest_chapter_release_date: Local::now().fixed_offset(),
-        latest_chapter_publish_day: Local::now().weekday(),
-    })
+    // only the latest chapter can be built from the page
+    let chapters = vec![Chapter::new(
+        total_chapter_count.replace("<!-- -->", ""),
+        chapter_url.to_owned(),
+        cover_url.to_owned(),
+        Local::now().fixed_offset(),
+    )];
+
+    Manga::from_chapters(title, Some(cover_url.to_owned()), author, chapters)
+        .ok_or(SourceError::ChapterNotFound(None))
 }
 
-pub async fn fetch_ganma(client: Client, manga_id: &str) -> Result<Manga, FetchError> {
+pub async fn fetch_ganma(client: Client, manga_id: &str) -> Result<Manga, SourceError> {
     let url = format!("https://ganma.jp/web/magazine/{manga_id}");
 
     let html = client
         .get(url)
         .send()
         .await
-        .map_err(FetchError::ReqwestError)?
+        .map_err(SourceError::ReqwestError)?
         .error_for_status()
-        .map_err(FetchError::ReqwestError)?
+        .map_err(SourceError::ReqwestError)?
         .text()
         .await
-        .map_err(FetchError::ReqwestError)?;
+        .map_err(SourceError::ReqwestError)?;
 
     parse_ganma_from_html(html)
 }
 
+pub struct GanmaSource;
+
+impl SourceFetcher for GanmaSource {
+    fn source(&self) -> MangaSource {
+        MangaSource::GANMA
+    }
+
+    fn fetch<'a>(&'a self, ctx: &'a FetchContext, manga_id: &'a str) -> FetchFuture<'a> {
+        Box::pin(fetch_ganma(ctx.client.clone(), manga_id))
+    }
+
+    fn series_url(&self, manga_id: &str) -> String {
+        format!("https://ganma.jp/web/magazine/{manga_id}")
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            undated_chapters: true,
+            ..Default::default()
+        }
+    }
+}
+
+pub fn register(registry: &mut SourceRegistry) {
+    registry.register(GanmaSource);
+}
+
 #[cfg(test)]
 mod tests {
     use std::fs;
@@ -1,5 +1,9 @@
-use crate::core::{fetch::FetchError, types::Manga};
-use chrono::{Datelike, Local};
+use crate::core::{
+    fetch::SourceError,
+    source::{Capabilities, FetchContext, FetchFuture, SourceFetcher, SourceRegistry},
+    types::{Chapter as MangaChapter, Manga, MangaSource},
+};
+use chrono::Local;
 use regex::{Regex, RegexBuilder};
 use reqwest::Client;
 use scraper::{Html, Selector};
@@ -34,7 +38,7 @@ pub struct Chapter {
     pub days_to_change_status: String,
 }
 
-pub fn parse_manga_up_from_html(html: String) -> Result<Manga, FetchError> {
+pub fn parse_manga_up_from_html(html: String) -> Result<Manga, SourceError> {
     let title_selector = Selector::parse(r#"h2[class*="pc:text-title-lg-pc"]"#).unwrap();
     let author_selector = Selector::parse(
         r#"div[class="text-on_background_medium sp:text-body-md-sp pc:text-body-md-pc"]"#,
@@ -45,24 +49,24 @@ pub fn parse_manga_up_from_html(html: String) -> Result<Manga, FetchError> {
     let title = document
         .select(&title_selector)
         .next()
-        .ok_or(FetchError::PageNotFound(Some("title not found".into())))?
+        .ok_or(SourceError::PageNotFound(Some("title not found".into())))?
         .inner_html();
     let author = document
         .select(&author_selector)
         .next()
-        .ok_or(FetchError::PageNotFound(Some("author not found".into())))?
+        .ok_or(SourceError::PageNotFound(Some("author not found".into())))?
         .inner_html();
 
     // parse chapter related data
     let captures = CHAPTER_REGEX
         .captures(&html)
-        .ok_or(FetchError::ChapterNotFound(Some(
+        .ok_or(SourceError::ChapterNotFound(Some(
             "no match from regex search".into(),
         )))?;
 
     let chapter_data_json = captures
         .get(0)
-        .ok_or(FetchError::ChapterNotFound(Some(
+        .ok_or(SourceError::ChapterNotFound(Some(
             "no result from regex capture".into(),
         )))?
         .as_str()
@@ -70,32 +74,34 @@ pub fn parse_manga_up_from_html(html: String) -> Result<Manga, FetchError> {
         .replace("\\", "");
 
     let chapter_data: MangaUpData =
-        serde_json::from_str(&chapter_data_json).map_err(FetchError::JsonDeserializeError)?;
+        serde_json::from_str(&chapter_data_json).map_err(SourceError::JsonDeserializeError)?;
 
-    let latest_chapter = chapter_data
+    // chapters are listed from the oldest one
+    let chapters = chapter_data
         .chapters
-        .last()
-        .ok_or(FetchError::ChapterNotFound(Some(
-            "chapters is empty".into(),
-        )))?;
-
-    Ok(Manga {
-        title,
-        cover_url: latest_chapter.url_thumbnail.to_owned(),
-        author,
-        latest_chapter_title: format!("{} {}", latest_chapter.sub_name, latest_chapter.name)
-            .trim()
-            .to_owned(),
-        latest_chapter_url: format!(
-            "https://www.manga-up.com/titles/{}/chapters/{}",
-            chapter_data.title_id, latest_chapter.id
-        ),
-        latest_chapter_release_date: Local::now().fixed_offset(),
-        latest_chapter_publish_day: Local::now().weekday(),
-    })
+        .iter()
+        .rev()
+        .map(|chapter| {
+            MangaChapter::new(
+                format!("{} {}", chapter.sub_name, chapter.name)
+                    .trim()
+                    .to_owned(),
+                format!(
+                    "https://www.manga-up.com/titles/{}/chapters/{}",
+                    chapter_data.title_id, chapter.id
+                ),
+                chapter.url_thumbnail.to_owned(),
+                Local::now().fixed_offset(),
+            )
+        })
+        .collect();
+
+    Manga::from_chapters(title, None, author, chapters).ok_or(SourceError::ChapterNotFound(Some(
+        "chapters is empty".into(),
+    )))
 }
 
-pub async fn fetch_mangaup(client: Client, manga_id: &str) -> Result<Manga, FetchError> {
+pub async fn fetch_mangaup(client: Client, manga_id: &str) -> Result<Manga, SourceError> {
     let url = format!("https://www.manga-up.com/titles/{manga_id}");
 
     let mut counter = 0;
@@ -106,12 +112,12 @@ pub async fn fetch_mangaup(client: Client, manga_id: &str) -> Result<Manga, Fetc
             .get(&url)
             .send()
             .await
-            .map_err(FetchError::ReqwestError)?
+            .map_err(SourceError::ReqwestError)?
             .error_for_status()
-            .map_err(FetchError::ReqwestError)?
+            .map_err(SourceError::ReqwestError)?
             .text()
             .await
-            .map_err(FetchError::ReqwestError)?;
+            .map_err(SourceError::ReqwestError)?;
 
         if !html.is_empty() || counter > 10 {
             if html.is_empty() {
@@ -127,6 +133,33 @@ pub async fn fetch_mangaup(client: Client, manga_id: &str) -> Result<Manga, Fetc
     }
 }
 
+pub struct MangaUpSource;
+
+impl SourceFetcher for MangaUpSource {
+    fn source(&self) -> MangaSource {
+        MangaSource::MangaUp
+    }
+
+    fn fetch<'a>(&'a self, ctx: &'a FetchContext, manga_id: &'a str) -> FetchFuture<'a> {
+        Box::pin(fetch_mangaup(ctx.client.clone(), manga_id))
+    }
+
+    fn series_url(&self, manga_id: &str) -> String {
+        format!("https://www.manga-up.com/titles/{manga_id}")
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            undated_chapters: true,
+            ..Default::default()
+        }
+    }
+}
+
+pub fn register(registry: &mut SourceRegistry) {
+    registry.register(MangaUpSource);
+}
+
 #[cfg(test)]
 mod tests {
     use std::fs;
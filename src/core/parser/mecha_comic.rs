@@ -1,10 +1,15 @@
-use chrono::{Datelike, Local};
+use chrono::Local;
 use reqwest::Client;
 use scraper::{selectable::Selectable, Html, Selector};
 
-use crate::core::{fetch::FetchError, types::Manga};
+use crate::core::{
+    fetch::SourceError,
+    parser::parsed_chapters,
+    source::{Capabilities, FetchContext, FetchFuture, SourceFetcher, SourceRegistry},
+    types::{Chapter, Manga, MangaSource},
+};
 
-fn parse_mecha_comic_from_html(html: String) -> Result<Manga, FetchError> {
+fn parse_mecha_comic_from_html(html: String) -> Result<Manga, SourceError> {
     let title_selector = Selector::parse(r#"div[class="p-bookInfo_title"] > h1"#).unwrap();
     let cover_selector = Selector::parse(
         r#"div[class="p-bookInfo_jacket u-position-relative"] > img[class="jacket_image_l"]"#,
@@ -24,108 +29,111 @@ fn parse_mecha_comic_from_html(html: String) -> Result<Manga, FetchError> {
     let title = document
         .select(&title_selector)
         .next()
-        .ok_or(FetchError::PageNotFound(Some("title not found".into())))?
+        .ok_or(SourceError::PageNotFound(Some("title not found".into())))?
         .inner_html();
 
     let cover_url = document
         .select(&cover_selector)
         .next()
-        .ok_or(FetchError::PageNotFound(Some(
+        .ok_or(SourceError::PageNotFound(Some(
             "cover element not found".into(),
         )))?
         .attr("src")
-        .ok_or(FetchError::PageNotFound(Some("cover src is empty".into())))?;
+        .ok_or(SourceError::PageNotFound(Some("cover src is empty".into())))?;
 
     let author = document
         .select(&author_selector)
         .next()
-        .ok_or(FetchError::PageNotFound(Some("author not found".into())))?
+        .ok_or(SourceError::PageNotFound(Some("author not found".into())))?
         .inner_html();
 
-    if let Some(latest_chapter_element) = document.select(&chapter_section_selector).next_back() {
-        let chapter_num = latest_chapter_element
-            .select(&chapter_num_selector)
-            .next()
-            .ok_or(FetchError::ChapterNotFound(Some(
-                "chapter num element not found".into(),
-            )))?
-            .first_child()
-            .ok_or(FetchError::ChapterNotFound(Some(
-                "chapter num child is empty".into(),
-            )))?
-            .value()
-            .as_text()
-            .ok_or(FetchError::ChapterNotFound(Some(
-                "chapter num child is not text".into(),
-            )))?
-            .trim();
-
-        let chapter_title = latest_chapter_element
-            .select(&chapter_title_selector)
-            .next()
-            .ok_or(FetchError::ChapterNotFound(Some(
-                "chapter title not found".into(),
-            )))?
-            .inner_html();
-
-        let chapter_url = latest_chapter_element
-            .select(&chapter_url_selector)
-            .next()
-            .ok_or(FetchError::ChapterNotFound(Some(
-                "chapter url not found".into(),
-            )))?
-            .attr("href")
-            .ok_or(FetchError::ChapterNotFound(Some(
-                "chapter url href attribute is empty".into(),
-            )))?;
-
-        // no information about chapter release date
-        return Ok(Manga {
-            title,
-            cover_url: cover_url.to_owned(),
-            author,
-            latest_chapter_title: format!("{} {}", chapter_num, chapter_title.trim()),
-            latest_chapter_url: format!("https://mechacomic.jp{chapter_url}"),
-            latest_chapter_release_date: Local::now().fixed_offset(),
-            latest_chapter_publish_day: Local::now().weekday(),
-        });
-    }
-
-    Err(FetchError::ChapterNotFound(Some(
-        "chapter section element not found".into(),
-    )))
+    // sections are listed from the oldest chapter
+    let chapters = parsed_chapters(
+        MangaSource::MechaComic,
+        document.select(&chapter_section_selector).rev().map(
+            |chapter_element| -> Result<Chapter, SourceError> {
+                let chapter_num = chapter_element
+                    .select(&chapter_num_selector)
+                    .next()
+                    .ok_or(SourceError::ChapterNotFound(Some(
+                        "chapter num element not found".into(),
+                    )))?
+                    .first_child()
+                    .ok_or(SourceError::ChapterNotFound(Some(
+                        "chapter num child is empty".into(),
+                    )))?
+                    .value()
+                    .as_text()
+                    .ok_or(SourceError::ChapterNotFound(Some(
+                        "chapter num child is not text".into(),
+                    )))?
+                    .trim();
+
+                let chapter_title = chapter_element
+                    .select(&chapter_title_selector)
+                    .next()
+                    .ok_or(SourceError::ChapterNotFound(Some(
+                        "chapter title not found".into(),
+                    )))?
+                    .inner_html();
+
+                let chapter_url = chapter_element
+                    .select(&chapter_url_selector)
+                    .next()
+                    .ok_or(SourceError::ChapterNotFound(Some(
+                        "chapter url not found".into(),
+                    )))?
+                    .attr("href")
+                    .ok_or(SourceError::ChapterNotFound(Some(
+                        "chapter url href attribute is empty".into(),
+                    )))?;
+
+                // no information about chapter release date
+                Ok(Chapter::new(
+                    format!("{} {}", chapter_num, chapter_title.trim()),
+                    format!("https://mechacomic.jp{chapter_url}"),
+                    cover_url.to_owned(),
+                    Local::now().fixed_offset(),
+                ))
+            },
+        ),
+    )?;
+
+    Manga::from_chapters(title, Some(cover_url.to_owned()), author, chapters).ok_or(
+        SourceError::ChapterNotFound(Some("chapter section element not found".into())),
+    )
 }
 
-fn find_latest_chapter_number(html: String) -> Result<i32, FetchError> {
+fn find_latest_chapter_number(html: String) -> Result<i32, SourceError> {
     let chapter_number_selector = Selector::parse(r#"div[class="u-inlineBlock"] > span"#).unwrap();
     let document = Html::parse_document(&html);
 
     document
         .select(&chapter_number_selector)
         .next()
-        .ok_or(FetchError::PageNotFound(Some(
+        .ok_or(SourceError::PageNotFound(Some(
             "no match for chapter number selector".into(),
         )))?
         .inner_html()
         .replace("／", "")
         .replace("話へ", "")
         .parse()
-        .map_err(|e| FetchError::PageNotFound(Some(format!("Page number parse error: {e}"))))
+        .map_err(|e| SourceError::PageNotFound(Some(format!("Page number parse error: {e}"))))
 }
 
-pub async fn fetch_mecha_comic(client: Client, manga_id: &str) -> Result<Manga, FetchError> {
+pub async fn fetch_mecha_comic(client: Client, manga_id: &str) -> Result<Manga, SourceError> {
     let url = format!("https://mechacomic.jp/books/{manga_id}");
 
     let html = client
         .get(&url)
         .send()
         .await
-        .map_err(FetchError::ReqwestError)?
+        .map_err(SourceError::ReqwestError)?
         .error_for_status()
-        .map_err(FetchError::ReqwestError)?
+        .map_err(SourceError::ReqwestError)?
         .text()
         .await
-        .map_err(FetchError::ReqwestError)?;
+        .map_err(SourceError::ReqwestError)?;
 
     let latest_chap_num = find_latest_chapter_number(html)?;
 
@@ -134,16 +142,43 @@ pub async fn fetch_mecha_comic(client: Client, manga_id: &str) -> Result<Manga,
         .query(&[("chapter_number", latest_chap_num)])
         .send()
         .await
-        .map_err(FetchError::ReqwestError)?
+        .map_err(SourceError::ReqwestError)?
         .error_for_status()
-        .map_err(FetchError::ReqwestError)?
+        .map_err(SourceError::ReqwestError)?
         .text()
         .await
-        .map_err(FetchError::ReqwestError)?;
+        .map_err(SourceError::ReqwestError)?;
 
     parse_mecha_comic_from_html(html)
 }
 
+pub struct MechaComicSource;
+
+impl SourceFetcher for MechaComicSource {
+    fn source(&self) -> MangaSource {
+        MangaSource::MechaComic
+    }
+
+    fn fetch<'a>(&'a self, ctx: &'a FetchContext, manga_id: &'a str) -> FetchFuture<'a> {
+        Box::pin(fetch_mecha_comic(ctx.client.clone(), manga_id))
+    }
+
+    fn series_url(&self, manga_id: &str) -> String {
+        format!("https://mechacomic.jp/books/{manga_id}")
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            undated_chapters: true,
+            ..Default::default()
+        }
+    }
+}
+
+pub fn register(registry: &mut SourceRegistry) {
+    registry.register(MechaComicSource);
+}
+
 #[cfg(test)]
 mod tests {
     use std::fs;
@@ -158,7 +193,13 @@ mod tests {
             dbg!(&path);
             let html = fs::read_to_string(path.unwrap().path()).unwrap();
             let _ = find_latest_chapter_number(html.clone()).unwrap();
-            let _ = parse_mecha_comic_from_html(html).unwrap();
+            let sections = Html::parse_document(&html)
+                .select(
+                    &Selector::parse(r#"div[class="p-chapterInfo p-chapterInfo-comic"]"#).unwrap(),
+                )
+                .count();
+            let manga = parse_mecha_comic_from_html(html).unwrap();
+            assert_eq!(manga.chapters.len(), sections);
         }
     }
 }
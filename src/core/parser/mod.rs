@@ -1,3 +1,9 @@
+use super::{
+    fetch::SourceError,
+    source::SourceRegistry,
+    types::{Chapter, MangaSource},
+};
+
 pub mod cdata_rss;
 pub mod comic_fuz;
 pub mod comic_pixiv;
@@ -9,3 +15,69 @@ pub mod mecha_comic;
 pub mod rss_manga;
 pub mod urasunday;
 pub mod yanmaga;
+
+pub fn register_all(registry: &mut SourceRegistry) {
+    cdata_rss::register(registry);
+    comic_fuz::register(registry);
+    comic_pixiv::register(registry);
+    comic_walker::register(registry);
+    gangan_online::register(registry);
+    ganma::register(registry);
+    manga_up::register(registry);
+    mecha_comic::register(registry);
+    rss_manga::register(registry);
+    urasunday::register(registry);
+    yanmaga::register(registry);
+}
+
+/// the chapters which parsed, a broken entry is logged and skipped so one odd chapter doesn't
+/// fail the whole series. Only fails when none of the entries parsed
+pub fn parsed_chapters(
+    source: MangaSource,
+    entries: impl IntoIterator<Item = Result<Chapter, SourceError>>,
+) -> Result<Vec<Chapter>, SourceError> {
+    let mut chapters = Vec::new();
+    let mut last_error = None;
+
+    for entry in entries {
+        match entry {
+            Ok(chapter) => chapters.push(chapter),
+            Err(e) => {
+                println!("{source}: skipping chapter, {e}");
+                last_error = Some(e);
+            }
+        }
+    }
+
+    match last_error {
+        Some(e) if chapters.is_empty() => Err(e),
+        _ => Ok(chapters),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Utc;
+
+    use super::*;
+
+    #[test]
+    fn skip_broken_chapters() {
+        let chapter = Chapter::new(
+            "1".into(),
+            "https://example.com/1".into(),
+            "".into(),
+            Utc::now().fixed_offset(),
+        );
+        let broken = || Err(SourceError::ChapterNotFound(Some("title not found".into())));
+
+        let chapters =
+            parsed_chapters(MangaSource::Yanmaga, [broken(), Ok(chapter.clone())]).unwrap();
+        assert_eq!(chapters, vec![chapter]);
+
+        assert!(parsed_chapters(MangaSource::Yanmaga, [broken(), broken()]).is_err());
+        assert!(parsed_chapters(MangaSource::Yanmaga, [])
+            .unwrap()
+            .is_empty());
+    }
+}
@@ -1,10 +1,164 @@
-use chrono::{DateTime, Datelike, FixedOffset};
-use chrono_tz::Japan;
-use reqwest::Client;
+use chrono::{DateTime, FixedOffset};
+use reqwest::{Client, Url};
 use serde::Deserialize;
 use serde_xml_rs::from_str;
 
-use crate::core::{fetch::FetchError, types::Manga};
+use crate::core::{
+    fetch::SourceError,
+    source::{
+        match_url_template, site_host, FetchContext, FetchFuture, SourceFetcher, SourceRegistry,
+    },
+    types::{Chapter, Manga, MangaSource},
+};
+
+/// magazine pocket pages are served from other hosts than its feed, with zero padded title ids
+const MAGAZINE_POCKET_PAGES: [&str; 2] = [
+    "https://pocket.shonenmagazine.com/title/{manga_id}",
+    "https://mgpk-web.magazinepocket.com/title/{manga_id}",
+];
+
+/// Sites sharing the same rss layout, titles are formatted as `site_name（series title）`
+pub struct RssSource {
+    source: MangaSource,
+    feed_url: &'static str,
+    site_name: &'static str,
+}
+
+impl RssSource {
+    pub const fn new(source: MangaSource, feed_url: &'static str, site_name: &'static str) -> Self {
+        Self {
+            source,
+            feed_url,
+            site_name,
+        }
+    }
+}
+
+impl SourceFetcher for RssSource {
+    fn source(&self) -> MangaSource {
+        self.source.clone()
+    }
+
+    fn fetch<'a>(&'a self, ctx: &'a FetchContext, manga_id: &'a str) -> FetchFuture<'a> {
+        Box::pin(fetch_generic_rss(
+            ctx.client.clone(),
+            self.feed_url.replace("{manga_id}", manga_id),
+        ))
+    }
+
+    fn series_url(&self, manga_id: &str) -> String {
+        self.feed_url.replace("{manga_id}", manga_id)
+    }
+
+    // gigaviewer series ids only exist in the feed, the pages are addressed by episode
+    fn series_page_url(&self, _manga_id: &str) -> Option<String> {
+        None
+    }
+
+    fn cleanup_title(&self, title: &str) -> String {
+        let removed_suffix = title.replace(self.site_name, "").trim().to_owned();
+
+        // drop the brackets around the series title, an empty title has none to drop
+        let mut chars = removed_suffix.chars();
+        chars.next();
+        chars.next_back();
+        chars.as_str().to_owned()
+    }
+
+    fn hosts(&self) -> Vec<String> {
+        let pages: &[&str] = match self.source {
+            MangaSource::MagazinePocket => &MAGAZINE_POCKET_PAGES,
+            _ => &[],
+        };
+
+        [self.feed_url]
+            .iter()
+            .chain(pages)
+            .filter_map(|template| Url::parse(template).ok())
+            .filter_map(|url| url.host_str().map(site_host))
+            .collect()
+    }
+
+    fn manga_id_from_url(&self, url: &Url) -> Option<String> {
+        match_url_template(self.feed_url, url).or_else(|| match self.source {
+            MangaSource::MagazinePocket => MAGAZINE_POCKET_PAGES
+                .iter()
+                .find_map(|template| match_url_template(template, url))
+                .map(|id| id.trim_start_matches('0').to_owned())
+                .filter(|id| !id.is_empty()),
+            _ => None,
+        })
+    }
+
+    fn replace_episode_url(&self, url: &str) -> String {
+        // for now this is for megazine pocket since the new url is not working yet
+        match self.source {
+            MangaSource::MagazinePocket => url
+                .replace("mgpk-web.magazinepocket.com", "pocket.shonenmagazine.com")
+                .to_owned(),
+            _ => url.to_owned(),
+        }
+    }
+}
+
+pub fn register(registry: &mut SourceRegistry) {
+    let sources = [
+        RssSource::new(
+            MangaSource::ShounenJumpPlus,
+            "https://shonenjumpplus.com/rss/series/{manga_id}",
+            "少年ジャンプ＋",
+        ),
+        RssSource::new(
+            MangaSource::ComicEarthStar,
+            "https://comic-earthstar.com/rss/series/{manga_id}",
+            "コミック アース・スター｜毎週木曜・最新話更新！無料で漫画が読めるWEBコミック誌",
+        ),
+        RssSource::new(
+            MangaSource::KurageBunch,
+            "https://kuragebunch.com/rss/series/{manga_id}",
+            "くらげバンチ",
+        ),
+        RssSource::new(
+            MangaSource::ComicDays,
+            "https://comic-days.com/rss/series/{manga_id}",
+            "コミックDAYS",
+        ),
+        RssSource::new(
+            MangaSource::MagazinePocket,
+            "https://mgpk-cdn.magazinepocket.com/static/rss/{manga_id}/feed.xml",
+            "マガポケ",
+        ),
+        RssSource::new(
+            MangaSource::TonariYoungJump,
+            "https://tonarinoyj.jp/rss/series/{manga_id}",
+            "となりのヤングジャンプ",
+        ),
+        RssSource::new(
+            MangaSource::SundayWebry,
+            "https://www.sunday-webry.com/rss/series/{manga_id}",
+            "サンデーうぇぶり",
+        ),
+        RssSource::new(
+            MangaSource::ComicAction,
+            "https://comic-action.com/rss/series/{manga_id}",
+            "webアクション｜双葉社発のマンガサイト",
+        ),
+        RssSource::new(
+            MangaSource::ComicGardo,
+            "https://comic-gardo.com/rss/series/{manga_id}",
+            "コミックガルド",
+        ),
+        RssSource::new(
+            MangaSource::IchijinPlus,
+            "https://ichicomi.com/rss/series/{manga_id}",
+            "一迅プラス",
+        ),
+    ];
+
+    for source in sources {
+        registry.register(source);
+    }
+}
 
 #[derive(Debug, Deserialize)]
 pub struct Rss {
@@ -26,24 +180,24 @@ pub struct Channel {
 
 #[cfg(feature = "ssr")]
 impl TryFrom<Channel> for Manga {
-    type Error = FetchError;
+    type Error = SourceError;
 
     fn try_from(value: Channel) -> Result<Self, Self::Error> {
-        let latest_chapter = value
+        let author = value
             .item
             .first()
-            .ok_or(FetchError::ChapterNotFound(None))?;
-        let release_date = &latest_chapter.pub_date.with_timezone(&Japan);
-
-        Ok(Self {
-            title: value.title,
-            cover_url: latest_chapter.enclosure.url.clone(),
-            author: latest_chapter.author.clone(),
-            latest_chapter_title: latest_chapter.title.clone(),
-            latest_chapter_url: latest_chapter.link.clone(),
-            latest_chapter_release_date: latest_chapter.pub_date,
-            latest_chapter_publish_day: release_date.weekday(),
-        })
+            .ok_or(SourceError::ChapterNotFound(None))?
+            .author
+            .clone();
+
+        let chapters = value
+            .item
+            .into_iter()
+            .map(|item| Chapter::new(item.title, item.link, item.enclosure.url, item.pub_date))
+            .collect();
+
+        Manga::from_chapters(value.title, None, author, chapters)
+            .ok_or(SourceError::ChapterNotFound(None))
     }
 }
 
@@ -102,24 +256,24 @@ pub mod rfc2822 {
     }
 }
 
-fn from_rss_xml(xml: &str) -> Result<Manga, FetchError> {
+fn from_rss_xml(xml: &str) -> Result<Manga, SourceError> {
     let rss: Rss =
-        from_str(xml).map_err(|e| FetchError::XmlDeserializeError(Some(e.to_string())))?;
+        from_str(xml).map_err(|e| SourceError::XmlDeserializeError(Some(e.to_string())))?;
 
     Manga::try_from(rss.channel)
 }
 
-pub async fn fetch_generic_rss(client: Client, url: String) -> Result<Manga, FetchError> {
+pub async fn fetch_generic_rss(client: Client, url: String) -> Result<Manga, SourceError> {
     let rss = client
         .get(url)
         .send()
         .await
-        .map_err(FetchError::ReqwestError)?
+        .map_err(SourceError::ReqwestError)?
         .error_for_status()
-        .map_err(FetchError::ReqwestError)?
+        .map_err(SourceError::ReqwestError)?
         .text()
         .await
-        .map_err(FetchError::ReqwestError)?;
+        .map_err(SourceError::ReqwestError)?;
 
     from_rss_xml(&rss)
 }
@@ -1,10 +1,17 @@
-use crate::core::{fetch::FetchError, types::Manga};
-use chrono::{Datelike, Days, Local, NaiveDate, NaiveTime, TimeZone};
-use chrono_tz::Japan;
+use crate::core::{
+    fetch::SourceError,
+    parser::parsed_chapters,
+    source::{
+        match_url_template, Capabilities, FetchContext, FetchFuture, SourceFetcher, SourceRegistry,
+    },
+    types::{Chapter, Manga, MangaSource},
+};
+use chrono::{Days, Local, NaiveDate, NaiveTime, TimeZone};
 use fantoccini::ClientBuilder;
+use reqwest::Url;
 use scraper::{Html, Selector};
 
-pub fn parse_urasunday_from_html(html: String, manga_id: &str) -> Result<Manga, FetchError> {
+pub fn parse_urasunday_from_html(html: String, manga_id: &str) -> Result<Manga, SourceError> {
     let document = Html::parse_document(&html);
 
     let title_selector = Selector::parse(r#"#aboutTitle > div > div > div > h2"#).unwrap();
@@ -20,113 +27,116 @@ pub fn parse_urasunday_from_html(html: String, manga_id: &str) -> Result<Manga,
     let title = document
         .select(&title_selector)
         .next()
-        .ok_or(FetchError::PageNotFound(Some(
+        .ok_or(SourceError::PageNotFound(Some(
             "series title not found".into(),
         )))?
         .inner_html();
     let author = document
         .select(&author_selector)
         .next()
-        .ok_or(FetchError::PageNotFound(Some("author not found".into())))?
+        .ok_or(SourceError::PageNotFound(Some("author not found".into())))?
         .inner_html();
 
-    let mut latest_chapter_iter = document.select(&chapter_selector);
-    //latest_chapter_iter.next();
-
-    if let Some(lastest_chapter_fragment) = latest_chapter_iter.next() {
-        let chapter_title = lastest_chapter_fragment
-            .select(&chapter_title_selector)
-            .map(|e| e.inner_html())
-            .reduce(|acc, s| format!("{acc} {s}"))
-            .ok_or(FetchError::ChapterNotFound(Some(
-                "chapter title not found".into(),
-            )))?;
-
-        let chapter_release_date = {
-            let mut date = Local::now().checked_add_days(Days::new(1)).unwrap();
-
-            if lastest_chapter_fragment
-                .select(&chapter_not_released_selector)
-                .next()
-                .is_none()
-            {
-                let raw = lastest_chapter_fragment
-                    .select(&chapter_release_date_selector)
+    let chapters = parsed_chapters(
+        MangaSource::Urasunday,
+        document.select(&chapter_selector).map(
+            |chapter_fragment| -> Result<Chapter, SourceError> {
+                let chapter_title = chapter_fragment
+                    .select(&chapter_title_selector)
+                    .map(|e| e.inner_html())
+                    .reduce(|acc, s| format!("{acc} {s}"))
+                    .ok_or(SourceError::ChapterNotFound(Some(
+                        "chapter title not found".into(),
+                    )))?;
+
+                let not_released = chapter_fragment
+                    .select(&chapter_not_released_selector)
                     .next()
-                    .ok_or(FetchError::ChapterNotFound(Some(
-                        "release date not found".into(),
-                    )))?
-                    .inner_html();
-
-                let naive_date = NaiveDate::parse_from_str(&raw, "%Y/%m/%d").map_err(|e| {
-                    FetchError::ChapterNotFound(Some(format!(
-                        "Error parsing date {} : {}",
-                        &raw, e
-                    )))
-                })?;
-
-                date = Local
-                    .from_local_datetime(&naive_date.and_time(NaiveTime::default()))
-                    .unwrap();
-            }
-
-            date
-        };
-
-        let chapter_img = lastest_chapter_fragment
-            .select(&chapter_img_selector)
-            .next()
-            .ok_or(FetchError::ChapterNotFound(Some("cover not found".into())))?
-            .attr("src")
-            .ok_or(FetchError::ChapterNotFound(Some(
-                "cover not found in src attribute".into(),
-            )))?;
-
-        let chapter_id = parse_chapter_id_from_url(chapter_img)?;
-
-        return Ok(Manga {
-            title: title.trim().into(),
-            cover_url: chapter_img.into(),
-            author: author.trim().into(),
-            latest_chapter_title: chapter_title,
-            latest_chapter_url: format!(
-                "https://manga-one.com/manga/{manga_id}/chapter/{chapter_id}"
-            ),
-            latest_chapter_release_date: chapter_release_date.fixed_offset(),
-            latest_chapter_publish_day: chapter_release_date.with_timezone(&Japan).weekday(),
-        });
-    }
-
-    Err(FetchError::ChapterNotFound(None))
+                    .is_some();
+
+                let chapter_release_date = {
+                    let mut date = Local::now().checked_add_days(Days::new(1)).unwrap();
+
+                    if !not_released {
+                        let raw = chapter_fragment
+                            .select(&chapter_release_date_selector)
+                            .next()
+                            .ok_or(SourceError::ChapterNotFound(Some(
+                                "release date not found".into(),
+                            )))?
+                            .inner_html();
+
+                        let naive_date =
+                            NaiveDate::parse_from_str(&raw, "%Y/%m/%d").map_err(|e| {
+                                SourceError::ChapterNotFound(Some(format!(
+                                    "Error parsing date {} : {}",
+                                    &raw, e
+                                )))
+                            })?;
+
+                        date = Local
+                            .from_local_datetime(&naive_date.and_time(NaiveTime::default()))
+                            .unwrap();
+                    }
+
+                    date
+                };
+
+                let chapter_img = chapter_fragment
+                    .select(&chapter_img_selector)
+                    .next()
+                    .ok_or(SourceError::ChapterNotFound(Some("cover not found".into())))?
+                    .attr("src")
+                    .ok_or(SourceError::ChapterNotFound(Some(
+                        "cover not found in src attribute".into(),
+                    )))?;
+
+                let chapter_id = parse_chapter_id_from_url(chapter_img)?;
+
+                Ok(Chapter {
+                    released: !not_released,
+                    ..Chapter::new(
+                        chapter_title,
+                        format!("https://manga-one.com/manga/{manga_id}/chapter/{chapter_id}"),
+                        chapter_img.into(),
+                        chapter_release_date.fixed_offset(),
+                    )
+                })
+            },
+        ),
+    )?;
+
+    Manga::from_chapters(title.trim().into(), None, author.trim().into(), chapters)
+        .ok_or(SourceError::ChapterNotFound(None))
 }
 
-pub fn parse_chapter_id_from_url(url: &str) -> Result<&str, FetchError> {
+pub fn parse_chapter_id_from_url(url: &str) -> Result<&str, SourceError> {
     let remove_prefix = url
         .split("chapter/")
         .last()
-        .ok_or(FetchError::ChapterNotFound(Some(
+        .ok_or(SourceError::ChapterNotFound(Some(
             "prefix split not foud".into(),
         )))?;
 
     remove_prefix
         .split(".webp")
         .next()
-        .ok_or(FetchError::ChapterNotFound(Some(
+        .ok_or(SourceError::ChapterNotFound(Some(
             "suffix split not foud".into(),
         )))
 }
 
-pub async fn fetch_urasunday(webdriver_url: &str, manga_id: &str) -> Result<Manga, FetchError> {
+pub async fn fetch_urasunday(webdriver_url: &str, manga_id: &str) -> Result<Manga, SourceError> {
     let url = format!("https://urasunday.com/title/{manga_id}/chapter/1234");
     let wv_client = ClientBuilder::native()
         .connect(webdriver_url)
         .await
-        .map_err(FetchError::WebDriverSessionError)?;
+        .map_err(SourceError::WebDriverSessionError)?;
 
     wv_client
         .goto(&url)
         .await
-        .map_err(FetchError::WebDriverCmdError)?;
+        .map_err(SourceError::WebDriverCmdError)?;
 
     wv_client
         .wait()
@@ -134,19 +144,56 @@ pub async fn fetch_urasunday(webdriver_url: &str, manga_id: &str) -> Result<Mang
             r#"div[class = "rounded-lg shadow-small"]"#,
         ))
         .await
-        .map_err(FetchError::WebDriverCmdError)?;
+        .map_err(SourceError::WebDriverCmdError)?;
 
     let html = wv_client
         .find(fantoccini::Locator::Css("html"))
         .await
-        .map_err(FetchError::WebDriverCmdError)?
+        .map_err(SourceError::WebDriverCmdError)?
         .html(false)
         .await
-        .map_err(FetchError::WebDriverCmdError)?;
+        .map_err(SourceError::WebDriverCmdError)?;
 
     parse_urasunday_from_html(html, manga_id)
 }
 
+pub struct UrasundaySource;
+
+impl SourceFetcher for UrasundaySource {
+    fn source(&self) -> MangaSource {
+        MangaSource::Urasunday
+    }
+
+    fn fetch<'a>(&'a self, ctx: &'a FetchContext, manga_id: &'a str) -> FetchFuture<'a> {
+        Box::pin(fetch_urasunday(&ctx.webdriver_url, manga_id))
+    }
+
+    fn series_url(&self, manga_id: &str) -> String {
+        format!("https://urasunday.com/title/{manga_id}")
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            needs_webdriver: true,
+            ..Default::default()
+        }
+    }
+
+    // chapters are read on manga-one which shares the series ids
+    fn hosts(&self) -> Vec<String> {
+        vec!["urasunday.com".into(), "manga-one.com".into()]
+    }
+
+    fn manga_id_from_url(&self, url: &Url) -> Option<String> {
+        match_url_template(&self.series_url("{manga_id}"), url)
+            .or_else(|| match_url_template("https://manga-one.com/manga/{manga_id}", url))
+    }
+}
+
+pub fn register(registry: &mut SourceRegistry) {
+    registry.register(UrasundaySource);
+}
+
 #[cfg(test)]
 mod tests {
     use std::fs;
@@ -1,11 +1,15 @@
-use chrono::{Datelike, Local, NaiveDate, NaiveTime, TimeZone};
-use chrono_tz::Japan;
+use chrono::{Local, NaiveDate, NaiveTime, TimeZone};
 use reqwest::Client;
 use scraper::{selectable::Selectable, Html, Selector};
 
-use crate::core::{fetch::FetchError, types::Manga};
+use crate::core::{
+    fetch::SourceError,
+    parser::parsed_chapters,
+    source::{FetchContext, FetchFuture, SourceFetcher, SourceRegistry},
+    types::{Chapter, Manga, MangaSource},
+};
 
-pub fn parse_yanmaga_from_html(html: String) -> Result<Manga, FetchError> {
+pub fn parse_yanmaga_from_html(html: String) -> Result<Manga, SourceError> {
     let document = Html::parse_document(&html);
     let title_selector = Selector::parse(r#"h1[class="detailv2-outline-title"]"#).unwrap();
     let author_selector =
@@ -31,141 +35,157 @@ pub fn parse_yanmaga_from_html(html: String) -> Result<Manga, FetchError> {
     let title = document
         .select(&title_selector)
         .next()
-        .ok_or(FetchError::PageNotFound(Some("Title not found".into())))?
+        .ok_or(SourceError::PageNotFound(Some("Title not found".into())))?
         .inner_html();
     let author = document
         .select(&author_selector)
         .next()
-        .ok_or(FetchError::PageNotFound(Some("Author not found".into())))?
+        .ok_or(SourceError::PageNotFound(Some("Author not found".into())))?
         .inner_html();
 
-    // get latest chapter
-    let latest_chapter =
+    let chapters = parsed_chapters(
+        MangaSource::Yanmaga,
         document
             .select(&chapter_selector)
-            .next()
-            .ok_or(FetchError::ChapterNotFound(Some(
-                "zero result from chapter selector".into(),
-            )))?;
-
-    if let Some(not_released) = latest_chapter.select(&chapter_not_released_selector).next() {
-        let mut childrens = not_released.child_elements();
-        let chapter_title = childrens
-            .next()
-            .ok_or(FetchError::ChapterNotFound(Some("title not found".into())))?
-            .inner_html();
-        let chapter_release_date = {
-            let raw_date = childrens
-                .next()
-                .ok_or(FetchError::ChapterNotFound(Some(
-                    "release date not found".into(),
-                )))?
-                .inner_html();
-            let date_only = raw_date
-                .split('(')
-                .next()
-                .ok_or(FetchError::ChapterNotFound(Some(format!(
-                    "error extracting date from : {}",
-                    &raw_date
-                ))))?;
-            let naive_date = NaiveDate::parse_from_str(date_only, "%Y/%m/%d").map_err(|e| {
-                FetchError::ChapterNotFound(Some(format!("{e}, error parsing date : {date_only}")))
-            })?;
-
-            Local
-                .from_local_datetime(&naive_date.and_time(NaiveTime::default()))
-                .unwrap()
-        };
-
-        let cover_url = latest_chapter
-            .select(&cover_url_selector)
-            .next()
-            .ok_or(FetchError::ChapterNotFound(Some("cover not found".into())))?
-            .attr("src")
-            .ok_or(FetchError::ChapterNotFound(Some(
-                "src attribute is empty".into(),
-            )))?;
-
-        Ok(Manga {
-            title,
-            cover_url: cover_url.to_string(),
-            author,
-            latest_chapter_title: chapter_title,
-            latest_chapter_url: "".into(),
-            latest_chapter_release_date: chapter_release_date.into(),
-            latest_chapter_publish_day: chapter_release_date.with_timezone(&Japan).weekday(),
-        })
-    } else {
-        let chapter_title = latest_chapter
-            .select(&chapter_title_selector)
-            .next()
-            .ok_or(FetchError::ChapterNotFound(Some("title not found".into())))?
-            .inner_html();
-
-        let chapter_release_date = NaiveDate::parse_from_str(
-            latest_chapter
-                .select(&chapter_release_date_selector)
-                .next()
-                .ok_or(FetchError::ChapterNotFound(Some(
-                    "release date not found".into(),
-                )))?
-                .inner_html()
-                .as_str(),
-            "%Y/%m/%d",
-        )
-        .map_err(|e| FetchError::ChapterNotFound(Some(format!("error parsing date : {e}"))))?;
-
-        let chapter_release_date = Local
-            .from_local_datetime(&chapter_release_date.and_time(NaiveTime::default()))
-            .unwrap();
-
-        let cover_url = latest_chapter
-            .select(&cover_url_selector)
-            .next()
-            .ok_or(FetchError::ChapterNotFound(Some("cover not found".into())))?
-            .attr("src")
-            .ok_or(FetchError::ChapterNotFound(Some(
-                "src attribute is empty".into(),
-            )))?;
-
-        let chapter_url = latest_chapter
-            .select(&chapter_url_selector)
-            .next()
-            .ok_or(FetchError::ChapterNotFound(Some("url not found".into())))?
-            .attr("href")
-            .ok_or(FetchError::ChapterNotFound(Some(
-                "href attribute is empty".into(),
-            )))?;
-
-        Ok(Manga {
-            title,
-            cover_url: cover_url.to_string(),
-            author,
-            latest_chapter_title: chapter_title,
-            latest_chapter_url: format!("https://yanmaga.jp{chapter_url}"),
-            latest_chapter_release_date: chapter_release_date.into(),
-            latest_chapter_publish_day: chapter_release_date.with_timezone(&Japan).weekday(),
-        })
-    }
+            .map(|chapter| -> Result<Chapter, SourceError> {
+                let cover_url = chapter
+                    .select(&cover_url_selector)
+                    .next()
+                    .ok_or(SourceError::ChapterNotFound(Some("cover not found".into())))?
+                    .attr("src")
+                    .ok_or(SourceError::ChapterNotFound(Some(
+                        "src attribute is empty".into(),
+                    )))?;
+
+                if let Some(not_released) = chapter.select(&chapter_not_released_selector).next() {
+                    let mut childrens = not_released.child_elements();
+                    let chapter_title = childrens
+                        .next()
+                        .ok_or(SourceError::ChapterNotFound(Some("title not found".into())))?
+                        .inner_html();
+                    let chapter_release_date = {
+                        let raw_date = childrens
+                            .next()
+                            .ok_or(SourceError::ChapterNotFound(Some(
+                                "release date not found".into(),
+                            )))?
+                            .inner_html();
+                        let date_only =
+                            raw_date
+                                .split('(')
+                                .next()
+                                .ok_or(SourceError::ChapterNotFound(Some(format!(
+                                    "error extracting date from : {}",
+                                    &raw_date
+                                ))))?;
+                        let naive_date =
+                            NaiveDate::parse_from_str(date_only, "%Y/%m/%d").map_err(|e| {
+                                SourceError::ChapterNotFound(Some(format!(
+                                    "{e}, error parsing date : {date_only}"
+                                )))
+                            })?;
+
+                        Local
+                            .from_local_datetime(&naive_date.and_time(NaiveTime::default()))
+                            .unwrap()
+                    };
+
+                    Ok(Chapter {
+                        released: false,
+                        ..Chapter::new(
+                            chapter_title,
+                            "".into(),
+                            cover_url.to_string(),
+                            chapter_release_date.into(),
+                        )
+                    })
+                } else {
+                    let chapter_title = chapter
+                        .select(&chapter_title_selector)
+                        .next()
+                        .ok_or(SourceError::ChapterNotFound(Some("title not found".into())))?
+                        .inner_html();
+
+                    let chapter_release_date = NaiveDate::parse_from_str(
+                        chapter
+                            .select(&chapter_release_date_selector)
+                            .next()
+                            .ok_or(SourceError::ChapterNotFound(Some(
+                                "release date not found".into(),
+                            )))?
+                            .inner_html()
+                            .as_str(),
+                        "%Y/%m/%d",
+                    )
+                    .map_err(|e| {
+                        SourceError::ChapterNotFound(Some(format!("error parsing date : {e}")))
+                    })?;
+
+                    let chapter_release_date = Local
+                        .from_local_datetime(&chapter_release_date.and_time(NaiveTime::default()))
+                        .unwrap();
+
+                    let chapter_url = chapter
+                        .select(&chapter_url_selector)
+                        .next()
+                        .ok_or(SourceError::ChapterNotFound(Some("url not found".into())))?
+                        .attr("href")
+                        .ok_or(SourceError::ChapterNotFound(Some(
+                            "href attribute is empty".into(),
+                        )))?;
+
+                    Ok(Chapter::new(
+                        chapter_title,
+                        format!("https://yanmaga.jp{chapter_url}"),
+                        cover_url.to_string(),
+                        chapter_release_date.into(),
+                    ))
+                }
+            }),
+    )?;
+
+    Manga::from_chapters(title, None, author, chapters).ok_or(SourceError::ChapterNotFound(Some(
+        "zero result from chapter selector".into(),
+    )))
 }
 
-pub async fn fetch_yanmaga(client: Client, manga_id: &str) -> Result<Manga, FetchError> {
+pub async fn fetch_yanmaga(client: Client, manga_id: &str) -> Result<Manga, SourceError> {
     let url = format!("https://yanmaga.jp/comics/{manga_id}?sort=newer");
 
     let html = client
         .get(url)
         .send()
         .await
-        .map_err(FetchError::ReqwestError)?
+        .map_err(SourceError::ReqwestError)?
         .error_for_status()
-        .map_err(FetchError::ReqwestError)?
+        .map_err(SourceError::ReqwestError)?
         .text()
         .await
-        .map_err(FetchError::ReqwestError)?;
+        .map_err(SourceError::ReqwestError)?;
 
     parse_yanmaga_from_html(html)
 }
 
+pub struct YanmagaSource;
+
+impl SourceFetcher for YanmagaSource {
+    fn source(&self) -> MangaSource {
+        MangaSource::Yanmaga
+    }
+
+    fn fetch<'a>(&'a self, ctx: &'a FetchContext, manga_id: &'a str) -> FetchFuture<'a> {
+        Box::pin(fetch_yanmaga(ctx.client.clone(), manga_id))
+    }
+
+    fn series_url(&self, manga_id: &str) -> String {
+        format!("https://yanmaga.jp/comics/{manga_id}")
+    }
+}
+
+pub fn register(registry: &mut SourceRegistry) {
+    registry.register(YanmagaSource);
+}
+
 #[cfg(test)]
 mod tests {
     use std::fs;
@@ -179,7 +199,9 @@ mod tests {
         for path in paths {
             dbg!(&path);
             let html = fs::read_to_string(path.unwrap().path()).unwrap();
-            let _ = parse_yanmaga_from_html(html).unwrap();
+            let manga = parse_yanmaga_from_html(html).unwrap();
+            assert!(manga.chapters.len() > 1);
+            assert_eq!(manga.latest_chapter_title, manga.chapters[0].title);
         }
     }
 }
@@ -0,0 +1,198 @@
+use reqwest::Url;
+use scraper::{Html, Selector};
+use thiserror::Error;
+
+use super::{
+    source::{registry, site_host, FetchContext, SourceFetcher},
+    types::MangaSource,
+};
+
+#[derive(Debug, Error)]
+pub enum ResolveError {
+    #[error("{0} is not a valid url")]
+    InvalidUrl(String),
+
+    #[error("{0} is not from a supported site")]
+    Unsupported(String),
+
+    #[error("no series found from {0}")]
+    SeriesNotFound(String),
+
+    #[error("{url} can't be fetched ({cause})")]
+    Fetch { url: String, cause: reqwest::Error },
+}
+
+/// the source and series id of a page of a supported site, episode pages which don't carry the
+/// series id in their url are fetched to find a link to their series
+pub async fn resolve_url(
+    ctx: &FetchContext,
+    input: &str,
+) -> Result<(MangaSource, String), ResolveError> {
+    let url = Url::parse(input.trim()).map_err(|_| ResolveError::InvalidUrl(input.into()))?;
+    let fetchers = site_fetchers(&url);
+    if fetchers.is_empty() {
+        return Err(ResolveError::Unsupported(input.into()));
+    }
+
+    if let Some(found) = match_url(&fetchers, &url) {
+        return Ok(found);
+    }
+
+    let fetch_error = |cause| ResolveError::Fetch {
+        url: url.to_string(),
+        cause,
+    };
+    let html = ctx
+        .client
+        .get(url.clone())
+        .send()
+        .await
+        .and_then(|r| r.error_for_status())
+        .map_err(fetch_error)?
+        .text()
+        .await
+        .map_err(fetch_error)?;
+
+    find_series_link(&fetchers, &url, &html).ok_or(ResolveError::SeriesNotFound(input.into()))
+}
+
+fn site_fetchers(url: &Url) -> Vec<&'static dyn SourceFetcher> {
+    let Some(host) = url.host_str().map(site_host) else {
+        return Vec::new();
+    };
+
+    registry()
+        .iter()
+        .filter(|fetcher| fetcher.hosts().contains(&host))
+        .collect()
+}
+
+fn match_url(fetchers: &[&dyn SourceFetcher], url: &Url) -> Option<(MangaSource, String)> {
+    fetchers.iter().find_map(|fetcher| {
+        fetcher
+            .manga_id_from_url(url)
+            .map(|id| (fetcher.source(), id))
+    })
+}
+
+/// the first link of the page pointing to a series of the site, like the rss feed of gigaviewer
+/// episodes or the series link of comici episodes
+fn find_series_link(
+    fetchers: &[&dyn SourceFetcher],
+    page_url: &Url,
+    html: &str,
+) -> Option<(MangaSource, String)> {
+    let document = Html::parse_document(html);
+    let selector = Selector::parse("link[href], a[href]").unwrap();
+
+    document
+        .select(&selector)
+        .filter_map(|element| element.value().attr("href"))
+        .filter_map(|href| page_url.join(href).ok())
+        .find_map(|url| match_url(fetchers, &url))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn resolve(input: &str) -> Option<(MangaSource, String)> {
+        let url = Url::parse(input).unwrap();
+        match_url(&site_fetchers(&url), &url)
+    }
+
+    #[test]
+    fn resolve_series_and_episode_urls() {
+        let cases = [
+            (
+                "https://shonenjumpplus.com/rss/series/10834108156641784251",
+                MangaSource::ShounenJumpPlus,
+                "10834108156641784251",
+            ),
+            (
+                "https://comic-growl.com/series/fa5a1c0d2a2b1",
+                MangaSource::ComicGrowl,
+                "fa5a1c0d2a2b1",
+            ),
+            (
+                "https://comic-walker.com/detail/KC_003002_S/episodes/KC_0030020000200011_E",
+                MangaSource::ComicWalker,
+                "KC_003002_S",
+            ),
+            (
+                "https://www.manga-up.com/titles/1234/chapters/5678",
+                MangaSource::MangaUp,
+                "1234",
+            ),
+            (
+                "https://manga-one.com/manga/300/chapter/1",
+                MangaSource::Urasunday,
+                "300",
+            ),
+            (
+                "https://comic-fuz.com/manga/42",
+                MangaSource::ComicFuz,
+                "42",
+            ),
+        ];
+
+        for (url, source, id) in cases {
+            assert_eq!(resolve(url), Some((source, id.to_string())), "{url}");
+        }
+    }
+
+    #[test]
+    fn resolve_magazine_pocket_pages() {
+        let expected = Some((MangaSource::MagazinePocket, "2628".to_string()));
+
+        for url in [
+            "https://pocket.shonenmagazine.com/title/02628/episode/416618",
+            "https://mgpk-web.magazinepocket.com/title/02628/episode/416618",
+            "https://mgpk-cdn.magazinepocket.com/static/rss/2628/feed.xml",
+        ] {
+            assert_eq!(resolve(url), expected, "{url}");
+        }
+    }
+
+    #[test]
+    fn episode_urls_without_series_id() {
+        // found from the page instead
+        assert_eq!(
+            resolve("https://shonenjumpplus.com/episode/3269754496"),
+            None
+        );
+        assert_eq!(resolve("https://comic-fuz.com/manga/viewer/1234"), None);
+    }
+
+    #[test]
+    fn unsupported_sites() {
+        let url = Url::parse("https://example.com/series/1").unwrap();
+        assert!(site_fetchers(&url).is_empty());
+    }
+
+    #[test]
+    fn find_series_from_episode_page() {
+        let page_url = Url::parse("https://shonenjumpplus.com/episode/3269754496").unwrap();
+        let html = r#"
+            <html><head>
+                <link rel="canonical" href="https://shonenjumpplus.com/episode/3269754496">
+                <link rel="alternate" type="application/rss+xml" href="https://shonenjumpplus.com/rss/series/10834108156641784251">
+            </head></html>
+        "#;
+
+        assert_eq!(
+            find_series_link(&site_fetchers(&page_url), &page_url, html),
+            Some((
+                MangaSource::ShounenJumpPlus,
+                "10834108156641784251".to_string()
+            ))
+        );
+
+        let page_url = Url::parse("https://comic-growl.com/episodes/abc").unwrap();
+        let html = r#"<a href="/series/fa5a1c0d2a2b1">back to series</a>"#;
+        assert_eq!(
+            find_series_link(&site_fetchers(&page_url), &page_url, html),
+            Some((MangaSource::ComicGrowl, "fa5a1c0d2a2b1".to_string()))
+        );
+    }
+}
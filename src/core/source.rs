@@ -0,0 +1,219 @@
+use std::{collections::HashMap, future::Future, pin::Pin, sync::LazyLock};
+
+use http::header;
+use reqwest::Url;
+
+use super::{
+    fetch::SourceError,
+    parser,
+    types::{Manga, MangaSource},
+};
+
+const USER_AGENT: &str = "Mozilla/5.0 (X11; Linux x86_64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/135.0.0.0 Safari/537.36";
+
+static REGISTRY: LazyLock<SourceRegistry> = LazyLock::new(|| {
+    let mut registry = SourceRegistry::default();
+    parser::register_all(&mut registry);
+    registry
+});
+
+pub type FetchFuture<'a> = Pin<Box<dyn Future<Output = Result<Manga, SourceError>> + Send + 'a>>;
+
+/// Shared resources handed to every fetcher
+pub struct FetchContext {
+    pub client: reqwest::Client,
+    pub webdriver_url: String,
+}
+
+impl FetchContext {
+    pub fn new(webdriver_url: &str) -> Self {
+        let client = {
+            let mut headers = header::HeaderMap::new();
+            headers.insert("User-Agent", header::HeaderValue::from_static(USER_AGENT));
+            reqwest::Client::builder()
+                .default_headers(headers)
+                .build()
+                .unwrap()
+        };
+
+        Self {
+            client,
+            webdriver_url: webdriver_url.to_owned(),
+        }
+    }
+}
+
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Capabilities {
+    /// source is scraped through selenium instead of plain http requests
+    pub needs_webdriver: bool,
+    /// source doesn't publish release dates, chapters are dated when they're fetched
+    pub undated_chapters: bool,
+}
+
+/// Implemented by every parser module to plug a site into [`MangaSource::fetch`]
+pub trait SourceFetcher: Send + Sync {
+    fn source(&self) -> MangaSource;
+
+    fn fetch<'a>(&'a self, ctx: &'a FetchContext, manga_id: &'a str) -> FetchFuture<'a>;
+
+    /// page (or feed) where the series chapters are read from
+    fn series_url(&self, manga_id: &str) -> String;
+
+    /// page of the series a reader can open, None when the source only exposes a feed
+    fn series_page_url(&self, manga_id: &str) -> Option<String> {
+        Some(self.series_url(manga_id))
+    }
+
+    fn cleanup_title(&self, title: &str) -> String {
+        title.to_owned()
+    }
+
+    fn replace_episode_url(&self, url: &str) -> String {
+        url.to_owned()
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        Capabilities::default()
+    }
+
+    /// hosts serving the site pages, `www.` is ignored
+    fn hosts(&self) -> Vec<String> {
+        Url::parse(&self.series_url("{manga_id}"))
+            .ok()
+            .and_then(|url| url.host_str().map(site_host))
+            .into_iter()
+            .collect()
+    }
+
+    /// the series id when `url` points to the series, pages nested under the series url (like
+    /// episodes on most sites) included
+    fn manga_id_from_url(&self, url: &Url) -> Option<String> {
+        match_url_template(&self.series_url("{manga_id}"), url)
+    }
+}
+
+pub fn site_host(host: &str) -> String {
+    host.trim_start_matches("www.").to_owned()
+}
+
+/// the path segment of `url` standing where `{manga_id}` is in `template`, segments following
+/// the id are ignored
+pub fn match_url_template(template: &str, url: &Url) -> Option<String> {
+    let template = Url::parse(template).ok()?;
+    if template.host_str().map(site_host) != url.host_str().map(site_host) {
+        return None;
+    }
+
+    let mut segments = url.path_segments()?;
+    for expected in template.path_segments()? {
+        let segment = segments.next().filter(|s| !s.is_empty())?;
+        if expected == "%7Bmanga_id%7D" || expected == "{manga_id}" {
+            return Some(segment.to_owned());
+        }
+        if expected != segment {
+            return None;
+        }
+    }
+
+    None
+}
+
+#[derive(Default)]
+pub struct SourceRegistry {
+    fetchers: HashMap<MangaSource, Box<dyn SourceFetcher>>,
+}
+
+impl SourceRegistry {
+    pub fn register(&mut self, fetcher: impl SourceFetcher + 'static) {
+        let source = fetcher.source();
+        if self
+            .fetchers
+            .insert(source.clone(), Box::new(fetcher))
+            .is_some()
+        {
+            panic!("{source} is registered more than once");
+        }
+    }
+
+    pub fn get(&self, source: &MangaSource) -> Option<&dyn SourceFetcher> {
+        self.fetchers.get(source).map(|f| f.as_ref())
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &dyn SourceFetcher> {
+        self.fetchers.values().map(|f| f.as_ref())
+    }
+}
+
+pub fn registry() -> &'static SourceRegistry {
+    &REGISTRY
+}
+
+#[cfg(test)]
+mod tests {
+    use strum::IntoEnumIterator;
+
+    use super::*;
+
+    #[test]
+    fn test_every_source_is_registered() {
+        for source in MangaSource::iter() {
+            let fetcher = registry()
+                .get(&source)
+                .unwrap_or_else(|| panic!("{source} has no fetcher"));
+            assert_eq!(fetcher.source(), source);
+        }
+    }
+
+    #[test]
+    fn test_match_url_template() {
+        let url = |s: &str| Url::parse(s).unwrap();
+        let template = "https://comic-walker.com/detail/{manga_id}";
+
+        assert_eq!(
+            match_url_template(template, &url("https://comic-walker.com/detail/KC_1")),
+            Some("KC_1".into())
+        );
+        assert_eq!(
+            match_url_template(
+                template,
+                &url("https://www.comic-walker.com/detail/KC_1/episodes/KC_1_01?ref=top")
+            ),
+            Some("KC_1".into())
+        );
+        assert_eq!(
+            match_url_template(template, &url("https://comic-walker.com/detail/")),
+            None
+        );
+        assert_eq!(
+            match_url_template(template, &url("https://comic-walker.com/ranking/1")),
+            None
+        );
+        assert_eq!(
+            match_url_template(template, &url("https://example.com/detail/KC_1")),
+            None
+        );
+    }
+
+    #[test]
+    fn test_urasunday_needs_webdriver() {
+        let fetcher = registry().get(&MangaSource::Urasunday).unwrap();
+        assert!(fetcher.capabilities().needs_webdriver);
+
+        let fetcher = registry().get(&MangaSource::ComicPixiv).unwrap();
+        assert!(!fetcher.capabilities().needs_webdriver);
+    }
+
+    #[test]
+    fn test_undated_sources() {
+        for source in [
+            MangaSource::GanganOnline,
+            MangaSource::MangaUp,
+            MangaSource::MechaComic,
+            MangaSource::GANMA,
+        ] {
+            assert!(!source.has_release_dates(), "{source}");
+        }
+        assert!(MangaSource::Yanmaga.has_release_dates());
+    }
+}
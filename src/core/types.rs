@@ -1,4 +1,5 @@
-use chrono::{DateTime, FixedOffset, Weekday};
+use chrono::{DateTime, Datelike, FixedOffset, Utc, Weekday};
+use chrono_tz::Japan;
 use serde::{Deserialize, Serialize};
 use strum_macros::{Display, EnumIter, EnumString};
 
@@ -9,6 +10,12 @@ pub struct MangaQuery {
     pub author: Option<String>,
     pub chapter_title: Option<String>,
     pub day: Option<Weekday>,
+    /// only series with a released chapter the reader hasn't read yet
+    #[serde(default)]
+    pub unread_only: bool,
+    /// only series followed by this user and their read state, set by the server from the session
+    #[serde(skip)]
+    pub subscriber: Option<i64>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -108,6 +115,43 @@ pub enum MangaSource {
     ComicMedu,
 }
 
+/// classification of a failed fetch, decides how the caller should react to it
+#[derive(Deserialize, Serialize, Clone, Copy, Debug, Display, EnumString, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "ssr", derive(sqlx::Type))]
+#[cfg_attr(feature = "ssr", sqlx(type_name = "FetchErrorKind"))]
+pub enum FetchErrorKind {
+    /// network hiccup or server error, retrying later is likely to work
+    #[strum(to_string = "Transient network error")]
+    Transient,
+
+    /// page was retrieved but the expected content can't be parsed
+    #[strum(to_string = "Site layout changed")]
+    LayoutChanged,
+
+    #[strum(to_string = "Not found")]
+    NotFound,
+
+    /// the site refuse our requests (403, 429)
+    #[strum(to_string = "Blocked")]
+    Blocked,
+
+    #[strum(to_string = "Webdriver unavailable")]
+    WebDriverUnavailable,
+
+    /// the deployment can't fetch the source at all, e.g. no fetcher is registered for it
+    #[strum(to_string = "Source unsupported")]
+    Unsupported,
+}
+
+impl FetchErrorKind {
+    pub fn is_transient(&self) -> bool {
+        matches!(
+            self,
+            FetchErrorKind::Transient | FetchErrorKind::WebDriverUnavailable
+        )
+    }
+}
+
 #[derive(Deserialize, Serialize, Clone, Debug)]
 pub struct Manga {
     pub title: String,
@@ -117,4 +161,168 @@ pub struct Manga {
     pub latest_chapter_url: String,
     pub latest_chapter_release_date: DateTime<FixedOffset>,
     pub latest_chapter_publish_day: Weekday,
+    /// every chapter found on the source page, ordered from the newest one
+    #[serde(default)]
+    pub chapters: Vec<Chapter>,
+}
+
+impl Manga {
+    /// `latest_chapter_*` fields are taken from the first chapter, when `cover_url` is None the
+    /// latest chapter thumbnail is used as the cover
+    pub fn from_chapters(
+        title: String,
+        cover_url: Option<String>,
+        author: String,
+        chapters: Vec<Chapter>,
+    ) -> Option<Self> {
+        let latest_chapter = chapters.first()?;
+
+        Some(Self {
+            title,
+            cover_url: cover_url.unwrap_or(latest_chapter.thumbnail_url.clone()),
+            author,
+            latest_chapter_title: latest_chapter.title.clone(),
+            latest_chapter_url: latest_chapter.url.clone(),
+            latest_chapter_release_date: latest_chapter.release_date,
+            latest_chapter_publish_day: latest_chapter.release_date.with_timezone(&Japan).weekday(),
+            chapters,
+        })
+    }
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq)]
+pub struct Chapter {
+    pub title: String,
+    pub url: String,
+    pub thumbnail_url: String,
+    pub release_date: DateTime<FixedOffset>,
+    pub released: bool,
+}
+
+impl Chapter {
+    /// released flag is derived from the release date, parsers which can tell an unreleased
+    /// chapter from the page should overwrite it
+    pub fn new(
+        title: String,
+        url: String,
+        thumbnail_url: String,
+        release_date: DateTime<FixedOffset>,
+    ) -> Self {
+        Self {
+            title,
+            url,
+            thumbnail_url,
+            release_date,
+            released: release_date <= Utc::now(),
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct ChapterRecord {
+    pub chapter_id: String,
+    pub title: String,
+    pub url: String,
+    /// None for sources which don't publish release dates
+    pub release_date: Option<DateTime<FixedOffset>>,
+    pub first_seen: DateTime<FixedOffset>,
+}
+
+/// outcome of a manual refresh of tracked series
+#[derive(Deserialize, Serialize, Clone, Debug, Default)]
+pub struct RefreshResult {
+    pub released: usize,
+    pub upcoming: usize,
+    pub no_change: usize,
+    /// series which couldn't be fetched, with the reason
+    pub failed: Vec<(MangaSource, String, String)>,
+    pub notification_failures: usize,
+}
+
+/// outcome of the latest fetches of a tracked series
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct SeriesHealth {
+    pub last_success: Option<DateTime<FixedOffset>>,
+    pub last_attempt: DateTime<FixedOffset>,
+    pub consecutive_failures: i32,
+    /// kept after the series recovers, `consecutive_failures` tells whether it is still relevant
+    pub last_error: Option<String>,
+    pub last_error_kind: Option<FetchErrorKind>,
+    /// suspended series are skipped by the update job until `next_probe_at`
+    pub suspended: bool,
+    pub next_probe_at: Option<DateTime<FixedOffset>>,
+}
+
+impl SeriesHealth {
+    pub fn is_failing(&self) -> bool {
+        self.consecutive_failures > 0
+    }
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct TrackedManga {
+    pub source: MangaSource,
+    pub manga_id: String,
+    pub manga: Manga,
+    pub tags: Vec<String>,
+    /// None when the series has never been checked
+    pub health: Option<SeriesHealth>,
+    /// None when no chapter of the series has been marked read
+    pub last_read: Option<ReadState>,
+    /// the latest chapter is out and isn't the last one read
+    pub unread: bool,
+}
+
+/// the last chapter of a series marked read
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct ReadState {
+    pub chapter_title: String,
+    pub read_at: DateTime<FixedOffset>,
+}
+
+/// one execution of the update job
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct JobRun {
+    pub id: i64,
+    pub started_at: DateTime<FixedOffset>,
+    /// None while the job is still running (or if it crashed)
+    pub finished_at: Option<DateTime<FixedOffset>>,
+    pub checked: i32,
+    pub skipped: i32,
+    pub released: i32,
+    pub upcoming: i32,
+    pub no_change: i32,
+    pub failed: i32,
+    pub notification_failures: i32,
+    /// why the run was aborted
+    pub error: Option<String>,
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct JobRunError {
+    pub source: MangaSource,
+    pub manga_id: String,
+    pub error_kind: Option<FetchErrorKind>,
+    pub error: String,
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct JobRunDetail {
+    pub run: JobRun,
+    pub errors: Vec<JobRunError>,
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq)]
+pub struct User {
+    pub id: i64,
+    pub username: String,
+}
+
+/// a token letting scripts call the api as its user, the token itself is only shown once
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct ApiToken {
+    pub id: i64,
+    pub name: String,
+    pub created_at: DateTime<FixedOffset>,
+    pub last_used_at: Option<DateTime<FixedOffset>>,
 }
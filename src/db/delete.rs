@@ -1,4 +1,4 @@
-use sqlx::{PgPool, QueryBuilder};
+use sqlx::{PgConnection, PgPool, QueryBuilder};
 
 use crate::core::types::MangaSource;
 
@@ -18,3 +18,66 @@ where
 
     Ok(query_result.rows_affected())
 }
+
+pub async fn delete_session(token_hash: &str, pool: &PgPool) -> Result<(), sqlx::Error> {
+    sqlx::query("delete from sessions where token_hash = $1")
+        .bind(token_hash)
+        .execute(pool)
+        .await?;
+
+    Ok(())
+}
+
+pub async fn delete_subscriptions<I>(
+    user_id: i64,
+    manga_list: I,
+    conn: &mut PgConnection,
+) -> Result<u64, sqlx::Error>
+where
+    I: IntoIterator<Item = (MangaSource, String)>,
+{
+    let mut query_builder = QueryBuilder::new("delete from subscriptions where user_id = ");
+    query_builder.push_bind(user_id);
+    query_builder.push(" and (source, manga_id) in ");
+    query_builder.push_tuples(manga_list, |mut b, (source, id)| {
+        b.push_bind(source);
+        b.push_bind(id);
+    });
+
+    let query_result = query_builder.build().execute(conn).await?;
+
+    Ok(query_result.rows_affected())
+}
+
+/// delete the listed series created by a follow that nobody follows anymore so the update job
+/// stops fetching them, series added otherwise are shared and kept
+pub async fn delete_unfollowed_series<I>(
+    manga_list: I,
+    conn: &mut PgConnection,
+) -> Result<u64, sqlx::Error>
+where
+    I: IntoIterator<Item = (MangaSource, String)>,
+{
+    let mut query_builder = QueryBuilder::new("delete from series where (source, manga_id) in ");
+    query_builder.push_tuples(manga_list, |mut b, (source, id)| {
+        b.push_bind(source);
+        b.push_bind(id);
+    });
+    query_builder.push(
+        " and added_by_follow and not exists (select 1 from subscriptions s where s.source = series.source and s.manga_id = series.manga_id)",
+    );
+
+    let query_result = query_builder.build().execute(conn).await?;
+
+    Ok(query_result.rows_affected())
+}
+
+pub async fn delete_api_token(user_id: i64, id: i64, pool: &PgPool) -> Result<u64, sqlx::Error> {
+    let query_result = sqlx::query("delete from api_tokens where user_id = $1 and id = $2")
+        .bind(user_id)
+        .bind(id)
+        .execute(pool)
+        .await?;
+
+    Ok(query_result.rows_affected())
+}
@@ -1,6 +1,10 @@
-use super::model::{DbWeekday, MangaRow};
+use super::model::{
+    ApiTokenRow, ChapterRow, DbWeekday, JobRunErrorRow, JobRunRow, MangaRow, OutboxRow,
+    ReadStateRow, SeriesHealthRow, UserRow,
+};
 use crate::core::types::{MangaQuery, MangaSource, Paginated};
-use sqlx::{FromRow, PgPool, QueryBuilder, Row};
+use chrono::NaiveDateTime;
+use sqlx::{FromRow, PgConnection, PgPool, QueryBuilder, Row};
 
 pub async fn get_manga(
     source: &MangaSource,
@@ -17,6 +21,87 @@ pub async fn get_manga(
     Ok(row)
 }
 
+pub async fn get_chapters(
+    source: &MangaSource,
+    manga_id: &str,
+    pool: &PgPool,
+) -> Result<Vec<ChapterRow>, sqlx::Error> {
+    let rows = sqlx::query_as::<_, ChapterRow>(
+        r#"
+        select * from chapters
+        where source = $1 and manga_id = $2
+        order by release_date desc nulls last, first_seen desc
+        "#,
+    )
+    .bind(source)
+    .bind(manga_id)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows)
+}
+
+/// stored chapters of the given series
+pub async fn get_series_chapters(
+    series: &[(MangaSource, String)],
+    pool: &PgPool,
+) -> Result<Vec<ChapterRow>, sqlx::Error> {
+    if series.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let mut query = QueryBuilder::new("select * from chapters where (source, manga_id) in ");
+    query.push_tuples(series, |mut b, (source, manga_id)| {
+        b.push_bind(source.clone()).push_bind(manga_id.clone());
+    });
+
+    let rows = query.build_query_as::<ChapterRow>().fetch_all(pool).await?;
+
+    Ok(rows)
+}
+
+/// release date of every known chapter, used to learn the cadence of each series
+pub async fn get_chapter_release_dates(
+    pool: &PgPool,
+) -> Result<Vec<(MangaSource, String, NaiveDateTime)>, sqlx::Error> {
+    let rows = sqlx::query_as::<_, (MangaSource, String, NaiveDateTime)>(
+        "select source, manga_id, release_date from chapters where release_date is not null",
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows)
+}
+
+pub async fn get_series_health(
+    series: &[(MangaSource, String)],
+    pool: &PgPool,
+) -> Result<Vec<SeriesHealthRow>, sqlx::Error> {
+    if series.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let mut query = QueryBuilder::new("select * from series_health where (source, manga_id) in ");
+    query.push_tuples(series, |mut b, (source, manga_id)| {
+        b.push_bind(source.clone()).push_bind(manga_id.clone());
+    });
+
+    let rows = query
+        .build_query_as::<SeriesHealthRow>()
+        .fetch_all(pool)
+        .await?;
+
+    Ok(rows)
+}
+
+pub async fn get_all_series_health(pool: &PgPool) -> Result<Vec<SeriesHealthRow>, sqlx::Error> {
+    let rows = sqlx::query_as::<_, SeriesHealthRow>("select * from series_health")
+        .fetch_all(pool)
+        .await?;
+
+    Ok(rows)
+}
+
 pub async fn get_manga_paginated(
     page_number: i64,
     page_size: i64,
@@ -59,6 +144,22 @@ pub async fn get_manga_paginated(
         query.push_bind(DbWeekday::from(*day));
     }
 
+    if let Some(user_id) = query_option.subscriber {
+        query.push(
+            " AND exists (select 1 from subscriptions s where s.source = series.source and s.manga_id = series.manga_id and s.user_id = ",
+        );
+        query.push_bind(user_id);
+        query.push(")");
+    }
+
+    if query_option.unread_only {
+        query.push(
+            " AND latest_chapter_released AND not exists (select 1 from read_states r where r.source = series.source and r.manga_id = series.manga_id and r.chapter_title = series.latest_chapter_title and coalesce(r.user_id, 0) = coalesce(",
+        );
+        query.push_bind(query_option.subscriber);
+        query.push("::bigint, 0))");
+    }
+
     query.push(" ) select *, count(*) over () as total_count from cte ORDER BY manga_id LIMIT ");
     query.push_bind(page_size);
     query.push(" OFFSET ");
@@ -88,3 +189,214 @@ pub async fn get_manga_paginated(
         },
     })
 }
+
+pub async fn get_job_runs_paginated(
+    page_number: i64,
+    page_size: i64,
+    pool: &PgPool,
+) -> Result<Paginated<Vec<JobRunRow>>, sqlx::Error> {
+    let rows = sqlx::query_as::<_, JobRunRow>(
+        "select * from job_runs order by started_at desc, id desc limit $1 offset $2",
+    )
+    .bind(page_size)
+    .bind((page_number - 1) * page_size)
+    .fetch_all(pool)
+    .await?;
+
+    let total_rows: i64 = sqlx::query_scalar("select count(*) from job_runs")
+        .fetch_one(pool)
+        .await?;
+
+    Ok(Paginated {
+        data: rows,
+        total_page: (total_rows + page_size - 1) / page_size,
+    })
+}
+
+pub async fn get_job_run(id: i64, pool: &PgPool) -> Result<JobRunRow, sqlx::Error> {
+    sqlx::query_as::<_, JobRunRow>("select * from job_runs where id = $1")
+        .bind(id)
+        .fetch_one(pool)
+        .await
+}
+
+pub async fn get_job_run_errors(
+    run_id: i64,
+    pool: &PgPool,
+) -> Result<Vec<JobRunErrorRow>, sqlx::Error> {
+    sqlx::query_as::<_, JobRunErrorRow>(
+        "select * from job_run_errors where run_id = $1 order by source, manga_id",
+    )
+    .bind(run_id)
+    .fetch_all(pool)
+    .await
+}
+
+/// events not delivered yet to `sink`, oldest first
+pub async fn get_pending_outbox(sink: &str, pool: &PgPool) -> Result<Vec<OutboxRow>, sqlx::Error> {
+    sqlx::query_as::<_, OutboxRow>(
+        "select * from notification_outbox where sink = $1 and delivered_at is null order by id",
+    )
+    .bind(sink)
+    .fetch_all(pool)
+    .await
+}
+
+/// lock the oldest pending events of `sink` until the transaction ends, events already claimed
+/// by another dispatcher are skipped so each one is delivered once
+pub async fn claim_pending_outbox(
+    sink: &str,
+    limit: i64,
+    conn: &mut PgConnection,
+) -> Result<Vec<OutboxRow>, sqlx::Error> {
+    sqlx::query_as::<_, OutboxRow>(
+        r#"
+        select * from notification_outbox
+        where sink = $1 and delivered_at is null
+        order by id
+        limit $2
+        for update skip locked
+        "#,
+    )
+    .bind(sink)
+    .bind(limit)
+    .fetch_all(conn)
+    .await
+}
+
+/// series whose next chapter isn't out yet and is expected between `from` and `to`
+pub async fn get_upcoming_series(
+    from: NaiveDateTime,
+    to: NaiveDateTime,
+    pool: &PgPool,
+) -> Result<Vec<MangaRow>, sqlx::Error> {
+    sqlx::query_as::<_, MangaRow>(
+        r#"
+        select * from series
+        where not latest_chapter_released and latest_chapter_release_date between $1 and $2
+        order by latest_chapter_release_date
+        "#,
+    )
+    .bind(from)
+    .bind(to)
+    .fetch_all(pool)
+    .await
+}
+
+/// series whose upcoming chapter was expected between `from` and `to` and still isn't released,
+/// leaving out the suspended ones and the ones attempted since `attempted_before`
+pub async fn get_due_releases(
+    from: NaiveDateTime,
+    to: NaiveDateTime,
+    attempted_before: NaiveDateTime,
+    pool: &PgPool,
+) -> Result<Vec<MangaRow>, sqlx::Error> {
+    sqlx::query_as::<_, MangaRow>(
+        r#"
+        select s.* from series s
+        left join series_health h on h.source = s.source and h.manga_id = s.manga_id
+        where not s.latest_chapter_released
+            and s.latest_chapter_release_date between $1 and $2
+            and not coalesce(h.suspended, false)
+            and (h.last_attempt is null or h.last_attempt < $3)
+        order by s.latest_chapter_release_date
+        "#,
+    )
+    .bind(from)
+    .bind(to)
+    .bind(attempted_before)
+    .fetch_all(pool)
+    .await
+}
+
+/// release date of the next chapter expected after `after`
+pub async fn get_next_release(
+    after: NaiveDateTime,
+    pool: &PgPool,
+) -> Result<Option<NaiveDateTime>, sqlx::Error> {
+    sqlx::query_scalar(
+        r#"
+        select min(latest_chapter_release_date) from series
+        where not latest_chapter_released and latest_chapter_release_date > $1
+        "#,
+    )
+    .bind(after)
+    .fetch_one(pool)
+    .await
+}
+
+pub async fn get_user_by_name(username: &str, pool: &PgPool) -> Result<UserRow, sqlx::Error> {
+    sqlx::query_as::<_, UserRow>("select * from users where username = $1")
+        .bind(username)
+        .fetch_one(pool)
+        .await
+}
+
+/// the user owning an unexpired session
+pub async fn get_session_user(
+    token_hash: &str,
+    now: NaiveDateTime,
+    pool: &PgPool,
+) -> Result<UserRow, sqlx::Error> {
+    sqlx::query_as::<_, UserRow>(
+        r#"
+        select u.* from sessions s join users u on u.id = s.user_id
+        where s.token_hash = $1 and s.expires_at > $2
+        "#,
+    )
+    .bind(token_hash)
+    .bind(now)
+    .fetch_one(pool)
+    .await
+}
+
+/// read states of the listed series, `user_id` is None for the anonymous one
+pub async fn get_read_states(
+    user_id: Option<i64>,
+    manga_list: &[(MangaSource, String)],
+    pool: &PgPool,
+) -> Result<Vec<ReadStateRow>, sqlx::Error> {
+    if manga_list.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let mut query =
+        QueryBuilder::new("select * from read_states where coalesce(user_id, 0) = coalesce(");
+    query.push_bind(user_id);
+    query.push("::bigint, 0) and (source, manga_id) in ");
+    query.push_tuples(manga_list, |mut b, (source, id)| {
+        b.push_bind(source);
+        b.push_bind(id);
+    });
+
+    query.build_query_as::<ReadStateRow>().fetch_all(pool).await
+}
+
+/// the listed series the user follows
+pub async fn get_followed_series(
+    user_id: i64,
+    manga_list: &[(MangaSource, String)],
+    pool: &PgPool,
+) -> Result<Vec<(MangaSource, String)>, sqlx::Error> {
+    if manga_list.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let mut query =
+        QueryBuilder::new("select source, manga_id from subscriptions where user_id = ");
+    query.push_bind(user_id);
+    query.push(" and (source, manga_id) in ");
+    query.push_tuples(manga_list, |mut b, (source, id)| {
+        b.push_bind(source);
+        b.push_bind(id);
+    });
+
+    query.build_query_as().fetch_all(pool).await
+}
+
+pub async fn get_api_tokens(user_id: i64, pool: &PgPool) -> Result<Vec<ApiTokenRow>, sqlx::Error> {
+    sqlx::query_as::<_, ApiTokenRow>("select * from api_tokens where user_id = $1 order by id")
+        .bind(user_id)
+        .fetch_all(pool)
+        .await
+}
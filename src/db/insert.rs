@@ -1,8 +1,16 @@
-use sqlx::PgPool;
+use std::collections::HashSet;
 
-use crate::core::{types::Manga, types::MangaSource};
+use chrono::NaiveDateTime;
+use sqlx::{PgConnection, PgPool, QueryBuilder};
 
-use super::model::MangaRow;
+use sqlx::types::Json;
+
+use crate::{
+    core::{types::Manga, types::MangaSource},
+    notify::ReleaseEvent,
+};
+
+use super::model::{ChapterRow, MangaRow, SeriesHealthRow};
 
 pub async fn insert_manga(
     source: MangaSource,
@@ -10,7 +18,20 @@ pub async fn insert_manga(
     info: Manga,
     pool: &PgPool,
 ) -> Result<(), sqlx::Error> {
+    let chapter_rows: Vec<_> = info
+        .chapters
+        .iter()
+        .map(|chapter| ChapterRow::from_chapter(source.clone(), manga_id.clone(), chapter))
+        .collect();
+    let health_row = SeriesHealthRow::success(source.clone(), manga_id.clone());
     let manga_row = MangaRow::from_manga(manga_id, source, info);
+    let chapter_rows = if chapter_rows.is_empty() {
+        vec![ChapterRow::from_manga_row(&manga_row)]
+    } else {
+        chapter_rows
+    };
+
+    let mut trx = pool.begin().await?;
 
     sqlx::query(r#"
         INSERT INTO series
@@ -29,8 +50,238 @@ pub async fn insert_manga(
         .bind(manga_row.latest_chapter_publish_day)
         .bind(manga_row.latest_chapter_released)
         .bind(manga_row.last_update)
+        .execute(&mut *trx)
+        .await?;
+
+    insert_chapters(chapter_rows.iter(), &mut *trx).await?;
+    upsert_series_health([&health_row], &mut *trx).await?;
+
+    trx.commit().await?;
+
+    Ok(())
+}
+
+/// insert chapters to the history table, chapters that already exist keep their first_seen and
+/// recorded release date and can't go back to unreleased
+pub async fn insert_chapters<'a>(
+    chapters: impl IntoIterator<Item = &'a ChapterRow>,
+    conn: &mut PgConnection,
+) -> Result<(), sqlx::Error> {
+    // a chapter can only be upserted once per statement, keep the first occurence
+    let mut seen = HashSet::new();
+    let chapters: Vec<_> = chapters
+        .into_iter()
+        .filter(|c| seen.insert((c.source.clone(), c.manga_id.clone(), c.chapter_id.clone())))
+        .collect();
+
+    if chapters.is_empty() {
+        return Ok(());
+    }
+
+    let mut query_builder = QueryBuilder::new(
+        r#"
+        insert into chapters
+        (source, manga_id, chapter_id, title, url, release_date, first_seen, released)
+        "#,
+    );
+
+    query_builder.push_values(chapters, |mut b, row| {
+        b.push_bind(row.source.clone())
+            .push_bind(row.manga_id.clone())
+            .push_bind(row.chapter_id.clone())
+            .push_bind(row.title.clone())
+            .push_bind(row.url.clone())
+            .push_bind(row.release_date)
+            .push_bind(row.first_seen)
+            .push_bind(row.released);
+    });
+
+    query_builder.push(
+        r#"
+        on conflict (source, manga_id, chapter_id) do update
+        set url = excluded.url, release_date = coalesce(chapters.release_date, excluded.release_date),
+        released = chapters.released or excluded.released
+        "#,
+    );
+
+    query_builder.build().execute(conn).await?;
+
+    Ok(())
+}
+
+/// write the health of each series, rows of series which don't exist anymore are skipped
+pub async fn upsert_series_health<'a>(
+    rows: impl IntoIterator<Item = &'a SeriesHealthRow>,
+    conn: &mut PgConnection,
+) -> Result<(), sqlx::Error> {
+    let rows: Vec<_> = rows.into_iter().collect();
+
+    if rows.is_empty() {
+        return Ok(());
+    }
+
+    let mut query_builder = QueryBuilder::new(
+        r#"
+        insert into series_health
+        (source, manga_id, last_success, last_attempt, consecutive_failures, last_error, last_error_kind, suspended, next_probe_at)
+        select v.* from (
+        "#,
+    );
+
+    query_builder.push_values(rows, |mut b, row| {
+        b.push_bind(row.source.clone())
+            .push_bind(row.manga_id.clone())
+            .push_bind(row.last_success)
+            .push_bind(row.last_attempt)
+            .push_bind(row.consecutive_failures)
+            .push_bind(row.last_error.clone())
+            .push_bind(row.last_error_kind)
+            .push_bind(row.suspended)
+            .push_bind(row.next_probe_at);
+    });
+
+    query_builder.push(
+        r#"
+        ) as v (source, manga_id, last_success, last_attempt, consecutive_failures, last_error, last_error_kind, suspended, next_probe_at)
+        where exists (select 1 from series s where s.source = v.source and s.manga_id = v.manga_id)
+        on conflict (source, manga_id) do update
+        set last_success = excluded.last_success,
+            last_attempt = excluded.last_attempt,
+            consecutive_failures = excluded.consecutive_failures,
+            last_error = excluded.last_error,
+            last_error_kind = excluded.last_error_kind,
+            suspended = excluded.suspended,
+            next_probe_at = excluded.next_probe_at
+        "#,
+    );
+
+    query_builder.build().execute(conn).await?;
+
+    Ok(())
+}
+
+/// mark the scheduled job `name` as ran for `tick`, returns false when it already ran for it
+pub async fn claim_scheduled_tick(
+    name: &str,
+    tick: NaiveDateTime,
+    conn: &mut PgConnection,
+) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query(
+        r#"
+        insert into scheduled_jobs (name, last_tick) values ($1, $2)
+        on conflict (name) do update set last_tick = excluded.last_tick
+        where scheduled_jobs.last_tick < excluded.last_tick
+        "#,
+    )
+    .bind(name)
+    .bind(tick)
+    .execute(conn)
+    .await?;
+
+    Ok(result.rows_affected() == 1)
+}
+
+/// record the start of an update job run, returns the run id
+pub async fn insert_job_run(started_at: NaiveDateTime, pool: &PgPool) -> Result<i64, sqlx::Error> {
+    sqlx::query_scalar("insert into job_runs (started_at) values ($1) returning id")
+        .bind(started_at)
+        .fetch_one(pool)
+        .await
+}
+
+/// queue events in the notification outbox, each one paired with the name of its sink.
+/// Suppressed events are recorded as handled and never sent
+pub async fn insert_outbox<'a, I>(
+    events: I,
+    suppressed: bool,
+    conn: &mut PgConnection,
+) -> Result<(), sqlx::Error>
+where
+    I: IntoIterator<Item = (&'a str, &'a ReleaseEvent)>,
+{
+    let mut events = events.into_iter().peekable();
+    if events.peek().is_none() {
+        return Ok(());
+    }
+
+    let delivered_at = suppressed.then(|| chrono::offset::Local::now().naive_local());
+    let mut query_builder = QueryBuilder::new(
+        "insert into notification_outbox (sink, event, suppressed, delivered_at) ",
+    );
+    query_builder.push_values(events, |mut b, (sink, event)| {
+        b.push_bind(sink)
+            .push_bind(Json(event))
+            .push_bind(suppressed)
+            .push_bind(delivered_at);
+    });
+
+    query_builder.build().execute(conn).await?;
+
+    Ok(())
+}
+
+/// create a user, returns its id
+pub async fn insert_user(
+    username: &str,
+    password_hash: &str,
+    pool: &PgPool,
+) -> Result<i64, sqlx::Error> {
+    sqlx::query_scalar("insert into users (username, password_hash) values ($1, $2) returning id")
+        .bind(username)
+        .bind(password_hash)
+        .fetch_one(pool)
+        .await
+}
+
+pub async fn insert_session(
+    token_hash: &str,
+    user_id: i64,
+    expires_at: NaiveDateTime,
+    pool: &PgPool,
+) -> Result<(), sqlx::Error> {
+    sqlx::query("insert into sessions (token_hash, user_id, expires_at) values ($1, $2, $3)")
+        .bind(token_hash)
+        .bind(user_id)
+        .bind(expires_at)
         .execute(pool)
         .await?;
 
     Ok(())
 }
+
+/// follow the series for the user, already followed series are left untouched
+pub async fn insert_subscriptions<I>(
+    user_id: i64,
+    manga_list: I,
+    pool: &PgPool,
+) -> Result<u64, sqlx::Error>
+where
+    I: IntoIterator<Item = (MangaSource, String)>,
+{
+    let mut query_builder =
+        QueryBuilder::new("insert into subscriptions (user_id, source, manga_id) ");
+    query_builder.push_values(manga_list, |mut b, (source, id)| {
+        b.push_bind(user_id).push_bind(source).push_bind(id);
+    });
+    query_builder.push(" on conflict do nothing");
+
+    let query_result = query_builder.build().execute(pool).await?;
+
+    Ok(query_result.rows_affected())
+}
+
+pub async fn insert_api_token(
+    user_id: i64,
+    name: &str,
+    token_hash: &str,
+    pool: &PgPool,
+) -> Result<i64, sqlx::Error> {
+    sqlx::query_scalar(
+        "insert into api_tokens (user_id, name, token_hash) values ($1, $2, $3) returning id",
+    )
+    .bind(user_id)
+    .bind(name)
+    .bind(token_hash)
+    .fetch_one(pool)
+    .await
+}
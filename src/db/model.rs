@@ -1,7 +1,28 @@
-use crate::core::{types::Manga, types::MangaSource};
+use crate::core::{
+    types::ApiToken, types::Chapter, types::ChapterRecord, types::FetchErrorKind, types::Manga,
+    types::MangaSource, types::ReadState, types::SeriesHealth, types::User,
+};
 use chrono::TimeZone;
-use chrono::{Local, NaiveDateTime, Weekday};
+use chrono::{DateTime, Datelike, FixedOffset, Local, NaiveDateTime, Utc, Weekday};
 use chrono_tz::Japan;
+use sqlx::types::Json;
+
+use crate::notify::ReleaseEvent;
+
+/// release dates are stored as japan local time whatever the offset the source gave, they're
+/// compared against this rather than the local time of the host
+pub fn japan_now() -> NaiveDateTime {
+    Utc::now().with_timezone(&Japan).naive_local()
+}
+
+/// a stored release date back as a point in time
+pub fn from_japan_time(release_date: &NaiveDateTime) -> DateTime<FixedOffset> {
+    Japan
+        .from_local_datetime(release_date)
+        .single()
+        .unwrap()
+        .fixed_offset()
+}
 
 #[derive(sqlx::FromRow, Debug)]
 pub struct MangaRow {
@@ -16,12 +37,15 @@ pub struct MangaRow {
     pub latest_chapter_publish_day: DbWeekday,
     pub latest_chapter_released: bool,
     pub last_update: NaiveDateTime,
+    pub tags: Vec<String>,
 }
 
 impl MangaRow {
     pub fn from_manga(manga_id: String, source: MangaSource, info: Manga) -> Self {
-        let current_dt = chrono::offset::Utc::now();
-        let release_dt = info.latest_chapter_release_date.naive_local();
+        let release_dt = info
+            .latest_chapter_release_date
+            .with_timezone(&Japan)
+            .naive_local();
         let wd: DbWeekday = info.latest_chapter_publish_day.into();
 
         Self {
@@ -34,12 +58,42 @@ impl MangaRow {
             latest_chapter_url: info.latest_chapter_url,
             latest_chapter_release_date: release_dt,
             latest_chapter_publish_day: wd,
-            latest_chapter_released: current_dt.with_timezone(&Japan)
-                >= Japan.from_local_datetime(&release_dt).unwrap(),
+            latest_chapter_released: japan_now() >= release_dt,
             last_update: chrono::offset::Local::now().naive_local(),
+            tags: Vec::new(),
         }
     }
 
+    /// row describing the series state right after `chapter` came out
+    pub fn from_chapter(
+        manga_id: String,
+        source: MangaSource,
+        info: &Manga,
+        chapter: &Chapter,
+    ) -> Self {
+        let cover_url = match chapter.thumbnail_url.as_str() {
+            "" => info.cover_url.clone(),
+            thumbnail => thumbnail.to_owned(),
+        };
+
+        let mut row = Self::from_manga(
+            manga_id,
+            source,
+            Manga {
+                title: info.title.clone(),
+                cover_url,
+                author: info.author.clone(),
+                latest_chapter_title: chapter.title.clone(),
+                latest_chapter_url: chapter.url.clone(),
+                latest_chapter_release_date: chapter.release_date,
+                latest_chapter_publish_day: chapter.release_date.with_timezone(&Japan).weekday(),
+                chapters: Vec::new(),
+            },
+        );
+        row.latest_chapter_released &= chapter.released;
+        row
+    }
+
     pub fn into_manga(self) -> Manga {
         Manga {
             title: self.title,
@@ -47,12 +101,217 @@ impl MangaRow {
             author: self.author,
             latest_chapter_title: self.latest_chapter_title,
             latest_chapter_url: self.latest_chapter_url,
-            latest_chapter_release_date: Local
-                .from_local_datetime(&self.latest_chapter_release_date)
+            latest_chapter_release_date: from_japan_time(&self.latest_chapter_release_date),
+            latest_chapter_publish_day: self.latest_chapter_publish_day.into(),
+            chapters: Vec::new(),
+        }
+    }
+}
+
+#[derive(sqlx::FromRow, Debug)]
+pub struct ChapterRow {
+    pub source: MangaSource,
+    pub manga_id: String,
+    pub chapter_id: String,
+    pub title: String,
+    pub url: String,
+    /// None for sources which don't publish release dates
+    pub release_date: Option<NaiveDateTime>,
+    pub first_seen: NaiveDateTime,
+    pub released: bool,
+}
+
+impl ChapterRow {
+    /// chapters are identified by their title, same as the diffing in the update job
+    pub fn from_manga_row(row: &MangaRow) -> Self {
+        Self {
+            source: row.source.clone(),
+            manga_id: row.manga_id.clone(),
+            chapter_id: row.latest_chapter_title.clone(),
+            title: row.latest_chapter_title.clone(),
+            url: row.latest_chapter_url.clone(),
+            release_date: row
+                .source
+                .has_release_dates()
+                .then_some(row.latest_chapter_release_date),
+            first_seen: row.last_update,
+            released: row.latest_chapter_released,
+        }
+    }
+
+    pub fn from_chapter(source: MangaSource, manga_id: String, chapter: &Chapter) -> Self {
+        Self {
+            release_date: source
+                .has_release_dates()
+                .then(|| chapter.release_date.with_timezone(&Japan).naive_local()),
+            source,
+            manga_id,
+            chapter_id: chapter.title.clone(),
+            title: chapter.title.clone(),
+            url: chapter.url.clone(),
+            first_seen: chrono::offset::Local::now().naive_local(),
+            released: chapter.released,
+        }
+    }
+
+    pub fn into_record(self) -> ChapterRecord {
+        ChapterRecord {
+            chapter_id: self.chapter_id,
+            title: self.title,
+            url: self.url,
+            release_date: self.release_date.as_ref().map(from_japan_time),
+            first_seen: Local
+                .from_local_datetime(&self.first_seen)
                 .single()
                 .unwrap()
                 .fixed_offset(),
-            latest_chapter_publish_day: self.latest_chapter_publish_day.into(),
+        }
+    }
+}
+
+#[derive(sqlx::FromRow, Debug, Clone)]
+pub struct SeriesHealthRow {
+    pub source: MangaSource,
+    pub manga_id: String,
+    pub last_success: Option<NaiveDateTime>,
+    pub last_attempt: NaiveDateTime,
+    pub consecutive_failures: i32,
+    pub last_error: Option<String>,
+    pub last_error_kind: Option<FetchErrorKind>,
+    pub suspended: bool,
+    /// when a suspended series gets checked again
+    pub next_probe_at: Option<NaiveDateTime>,
+}
+
+impl SeriesHealthRow {
+    /// health of a series which has never been checked
+    pub fn new(source: MangaSource, manga_id: String) -> Self {
+        Self {
+            source,
+            manga_id,
+            last_success: None,
+            last_attempt: chrono::offset::Local::now().naive_local(),
+            consecutive_failures: 0,
+            last_error: None,
+            last_error_kind: None,
+            suspended: false,
+            next_probe_at: None,
+        }
+    }
+
+    pub fn success(source: MangaSource, manga_id: String) -> Self {
+        let mut row = Self::new(source, manga_id);
+        row.succeeded();
+        row
+    }
+
+    pub fn succeeded(&mut self) {
+        let now = chrono::offset::Local::now().naive_local();
+
+        self.last_success = Some(now);
+        self.last_attempt = now;
+        self.consecutive_failures = 0;
+        self.suspended = false;
+        self.next_probe_at = None;
+    }
+
+    /// `kind` is None when the failure isn't a fetch error (e.g. the task panicked), suspension
+    /// is left to the caller
+    pub fn failed(&mut self, kind: Option<FetchErrorKind>, error: String) {
+        self.last_attempt = chrono::offset::Local::now().naive_local();
+        self.consecutive_failures += 1;
+        self.last_error = Some(error);
+        self.last_error_kind = kind;
+    }
+
+    pub fn into_health(self) -> SeriesHealth {
+        SeriesHealth {
+            last_success: self.last_success.map(|dt| {
+                Local
+                    .from_local_datetime(&dt)
+                    .single()
+                    .unwrap()
+                    .fixed_offset()
+            }),
+            last_attempt: Local
+                .from_local_datetime(&self.last_attempt)
+                .single()
+                .unwrap()
+                .fixed_offset(),
+            consecutive_failures: self.consecutive_failures,
+            last_error: self.last_error,
+            last_error_kind: self.last_error_kind,
+            suspended: self.suspended,
+            next_probe_at: self.next_probe_at.map(|dt| {
+                Local
+                    .from_local_datetime(&dt)
+                    .single()
+                    .unwrap()
+                    .fixed_offset()
+            }),
+        }
+    }
+}
+
+#[derive(sqlx::FromRow, Debug, Default)]
+pub struct JobRunRow {
+    pub id: i64,
+    pub started_at: NaiveDateTime,
+    pub finished_at: Option<NaiveDateTime>,
+    pub checked: i32,
+    pub skipped: i32,
+    pub released: i32,
+    pub upcoming: i32,
+    pub no_change: i32,
+    pub failed: i32,
+    pub notification_failures: i32,
+    pub error: Option<String>,
+}
+
+impl JobRunRow {
+    pub fn into_job_run(self) -> JobRun {
+        JobRun {
+            id: self.id,
+            started_at: Local
+                .from_local_datetime(&self.started_at)
+                .single()
+                .unwrap()
+                .fixed_offset(),
+            finished_at: self.finished_at.map(|dt| {
+                Local
+                    .from_local_datetime(&dt)
+                    .single()
+                    .unwrap()
+                    .fixed_offset()
+            }),
+            checked: self.checked,
+            skipped: self.skipped,
+            released: self.released,
+            upcoming: self.upcoming,
+            no_change: self.no_change,
+            failed: self.failed,
+            notification_failures: self.notification_failures,
+            error: self.error,
+        }
+    }
+}
+
+#[derive(sqlx::FromRow, Debug)]
+pub struct JobRunErrorRow {
+    pub run_id: i64,
+    pub source: MangaSource,
+    pub manga_id: String,
+    pub error_kind: Option<FetchErrorKind>,
+    pub error: String,
+}
+
+impl JobRunErrorRow {
+    pub fn into_job_run_error(self) -> JobRunError {
+        JobRunError {
+            source: self.source,
+            manga_id: self.manga_id,
+            error_kind: self.error_kind,
+            error: self.error,
         }
     }
 }
@@ -96,3 +355,87 @@ impl From<DbWeekday> for Weekday {
         }
     }
 }
+
+/// a release event waiting to be delivered to one sink
+#[derive(sqlx::FromRow, Debug)]
+pub struct OutboxRow {
+    pub id: i64,
+    pub sink: String,
+    pub event: Json<ReleaseEvent>,
+    pub created_at: NaiveDateTime,
+    pub attempts: i32,
+    pub last_error: Option<String>,
+    pub delivered_at: Option<NaiveDateTime>,
+    pub suppressed: bool,
+}
+
+#[derive(sqlx::FromRow, Debug)]
+pub struct UserRow {
+    pub id: i64,
+    pub username: String,
+    pub password_hash: String,
+    pub created_at: NaiveDateTime,
+}
+
+impl UserRow {
+    pub fn into_user(self) -> User {
+        User {
+            id: self.id,
+            username: self.username,
+        }
+    }
+}
+
+#[derive(sqlx::FromRow, Debug)]
+pub struct ReadStateRow {
+    pub user_id: Option<i64>,
+    pub source: MangaSource,
+    pub manga_id: String,
+    pub chapter_title: String,
+    pub chapter_url: String,
+    pub read_at: NaiveDateTime,
+}
+
+impl ReadStateRow {
+    pub fn into_read_state(self) -> ReadState {
+        ReadState {
+            chapter_title: self.chapter_title,
+            read_at: Local
+                .from_local_datetime(&self.read_at)
+                .single()
+                .unwrap()
+                .fixed_offset(),
+        }
+    }
+}
+
+#[derive(sqlx::FromRow, Debug)]
+pub struct ApiTokenRow {
+    pub id: i64,
+    pub user_id: i64,
+    pub name: String,
+    pub token_hash: String,
+    pub created_at: NaiveDateTime,
+    pub last_used_at: Option<NaiveDateTime>,
+}
+
+impl ApiTokenRow {
+    pub fn into_api_token(self) -> ApiToken {
+        ApiToken {
+            id: self.id,
+            name: self.name,
+            created_at: Local
+                .from_local_datetime(&self.created_at)
+                .single()
+                .unwrap()
+                .fixed_offset(),
+            last_used_at: self.last_used_at.map(|dt| {
+                Local
+                    .from_local_datetime(&dt)
+                    .single()
+                    .unwrap()
+                    .fixed_offset()
+            }),
+        }
+    }
+}
@@ -1,12 +1,39 @@
-use sqlx::{PgPool, QueryBuilder};
+use std::collections::HashMap;
 
-use super::model::MangaRow;
+use sqlx::{PgConnection, PgPool, QueryBuilder};
 
-pub async fn update_manga_batch(
-    latest_data: impl Iterator<Item = &MangaRow>,
+use crate::{core::types::MangaSource, notify::ReleaseEvent};
+
+use super::{
+    insert::{insert_chapters, insert_outbox},
+    model::{ChapterRow, JobRunErrorRow, JobRunRow, MangaRow, UserRow},
+};
+
+/// write the new state of the series, the release events are queued in the notification outbox
+/// within the same transaction so a release can't be marked as seen without being announced
+pub async fn update_manga_batch<'a, 'b>(
+    latest_data: impl Iterator<Item = &'a MangaRow>,
+    outbox: impl IntoIterator<Item = (&'b str, &'b ReleaseEvent)>,
+    suppressed: bool,
     pool: &PgPool,
 ) -> Result<(), sqlx::Error> {
-    let mut trx = pool.begin().await.expect("Error begin transaction");
+    let latest_data: Vec<_> = latest_data.collect();
+
+    // the same series can appear several times (one row per new chapter), the last one wins
+    let mut latest_index = HashMap::new();
+    for (idx, row) in latest_data.iter().enumerate() {
+        latest_index.insert((row.source.clone(), row.manga_id.clone()), idx);
+    }
+    let series_data: Vec<_> = latest_data
+        .iter()
+        .enumerate()
+        .filter(|(idx, row)| {
+            latest_index.get(&(row.source.clone(), row.manga_id.clone())) == Some(idx)
+        })
+        .map(|(_, row)| *row)
+        .collect();
+
+    let mut trx = pool.begin().await?;
 
     // create temp table
     sqlx::query(
@@ -35,7 +62,7 @@ pub async fn update_manga_batch(
         "#,
     );
 
-    query_builder.push_values(latest_data, |mut b, row| {
+    query_builder.push_values(series_data, |mut b, row| {
         b.push_bind(row.source.clone())
             .push_bind(row.manga_id.clone())
             .push_bind(row.title.clone())
@@ -65,6 +92,15 @@ pub async fn update_manga_batch(
 
         from update_table as u 
         where u.source = s.source and u.manga_id = s.manga_id
+        -- an older stored chapter coming out doesn't replace the newer one the series points to
+        and (
+            u.latest_chapter_title = s.latest_chapter_title
+            or not exists (
+                select 1 from chapters as c
+                where c.source = s.source and c.manga_id = s.manga_id
+                and c.chapter_id = u.latest_chapter_title
+            )
+        )
     "#,
     );
 
@@ -74,7 +110,225 @@ pub async fn update_manga_batch(
         .execute(&mut *trx)
         .await?;
 
+    let chapters: Vec<_> = latest_data
+        .iter()
+        .map(|row| ChapterRow::from_manga_row(row))
+        .collect();
+    insert_chapters(chapters.iter(), &mut *trx).await?;
+
+    insert_outbox(outbox, suppressed, &mut *trx).await?;
+
+    trx.commit().await?;
+
+    Ok(())
+}
+
+/// lift the suspension of the given series, they get checked again on the next job run
+pub async fn resume_series_bulk<I>(manga_list: I, pool: &PgPool) -> Result<u64, sqlx::Error>
+where
+    I: IntoIterator<Item = (MangaSource, String)>,
+{
+    let mut query_builder = QueryBuilder::new(
+        r#"
+        update series_health
+        set suspended = false, next_probe_at = null, consecutive_failures = 0
+        where suspended and (source, manga_id) in
+        "#,
+    );
+    query_builder.push_tuples(manga_list, |mut b, (source, id)| {
+        b.push_bind(source);
+        b.push_bind(id);
+    });
+
+    let query_result = query_builder.build().execute(pool).await?;
+
+    Ok(query_result.rows_affected())
+}
+
+/// replace the tags of the given series, returns the number of series updated
+pub async fn set_series_tags_bulk<I>(
+    manga_list: I,
+    tags: &[String],
+    pool: &PgPool,
+) -> Result<u64, sqlx::Error>
+where
+    I: IntoIterator<Item = (MangaSource, String)>,
+{
+    let mut query_builder = QueryBuilder::new("update series set tags = ");
+    query_builder.push_bind(tags);
+    query_builder.push(" where (source, manga_id) in ");
+    query_builder.push_tuples(manga_list, |mut b, (source, id)| {
+        b.push_bind(source);
+        b.push_bind(id);
+    });
+
+    let query_result = query_builder.build().execute(pool).await?;
+
+    Ok(query_result.rows_affected())
+}
+
+/// flag a series as created by a follow, it is deleted once nobody follows it anymore
+pub async fn mark_added_by_follow(
+    source: &MangaSource,
+    manga_id: &str,
+    pool: &PgPool,
+) -> Result<u64, sqlx::Error> {
+    let query_result =
+        sqlx::query("update series set added_by_follow = true where source = $1 and manga_id = $2")
+            .bind(source)
+            .bind(manga_id)
+            .execute(pool)
+            .await?;
+
+    Ok(query_result.rows_affected())
+}
+
+/// store the outcome of a job run started with `insert_job_run`
+pub async fn finish_job_run(
+    run: &JobRunRow,
+    errors: &[JobRunErrorRow],
+    pool: &PgPool,
+) -> Result<(), sqlx::Error> {
+    let mut trx = pool.begin().await?;
+
+    sqlx::query(
+        r#"
+        update job_runs
+        set finished_at = $2, checked = $3, skipped = $4, released = $5, upcoming = $6,
+            no_change = $7, failed = $8, notification_failures = $9, error = $10
+        where id = $1
+        "#,
+    )
+    .bind(run.id)
+    .bind(run.finished_at)
+    .bind(run.checked)
+    .bind(run.skipped)
+    .bind(run.released)
+    .bind(run.upcoming)
+    .bind(run.no_change)
+    .bind(run.failed)
+    .bind(run.notification_failures)
+    .bind(&run.error)
+    .execute(&mut *trx)
+    .await?;
+
+    if !errors.is_empty() {
+        let mut query_builder = QueryBuilder::new(
+            "insert into job_run_errors (run_id, source, manga_id, error_kind, error) ",
+        );
+        query_builder.push_values(errors, |mut b, row| {
+            b.push_bind(row.run_id)
+                .push_bind(row.source.clone())
+                .push_bind(row.manga_id.clone())
+                .push_bind(row.error_kind)
+                .push_bind(row.error.clone());
+        });
+        query_builder.build().execute(&mut *trx).await?;
+    }
+
     trx.commit().await?;
 
     Ok(())
 }
+
+pub async fn mark_outbox_delivered(
+    ids: &[i64],
+    conn: &mut PgConnection,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "update notification_outbox set delivered_at = now(), attempts = attempts + 1 where id = any($1)",
+    )
+    .bind(ids)
+    .execute(conn)
+    .await?;
+
+    Ok(())
+}
+
+/// settle the pending events of sinks missing from `sinks`, they were removed from the config and
+/// would stay pending forever. They are recorded as suppressed along the reason
+pub async fn drop_unconfigured_outbox(sinks: &[&str], pool: &PgPool) -> Result<u64, sqlx::Error> {
+    let query_result = sqlx::query(
+        r#"
+        update notification_outbox
+        set delivered_at = now(), suppressed = true, last_error = 'sink is not configured anymore'
+        where delivered_at is null and sink <> all($1)
+        "#,
+    )
+    .bind(sinks)
+    .execute(pool)
+    .await?;
+
+    Ok(query_result.rows_affected())
+}
+
+/// keep the events pending, they are tried again on the next dispatch
+pub async fn mark_outbox_failed(
+    ids: &[i64],
+    error: &str,
+    conn: &mut PgConnection,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "update notification_outbox set attempts = attempts + 1, last_error = $2 where id = any($1)",
+    )
+    .bind(ids)
+    .bind(error)
+    .execute(conn)
+    .await?;
+
+    Ok(())
+}
+
+/// mark the current latest chapter of the given series as read, returns the number of series.
+/// Series whose latest chapter isn't released yet are left out
+pub async fn mark_read_bulk<I>(
+    user_id: Option<i64>,
+    manga_list: I,
+    read_at: chrono::NaiveDateTime,
+    pool: &PgPool,
+) -> Result<u64, sqlx::Error>
+where
+    I: IntoIterator<Item = (MangaSource, String)>,
+{
+    let mut query_builder = QueryBuilder::new(
+        "insert into read_states (user_id, source, manga_id, chapter_title, chapter_url, read_at) select ",
+    );
+    query_builder.push_bind(user_id);
+    query_builder.push(", source, manga_id, latest_chapter_title, latest_chapter_url, ");
+    query_builder.push_bind(read_at);
+    query_builder.push(" from series where latest_chapter_released and (source, manga_id) in ");
+    query_builder.push_tuples(manga_list, |mut b, (source, id)| {
+        b.push_bind(source);
+        b.push_bind(id);
+    });
+    query_builder.push(
+        r#"
+        on conflict ((coalesce(user_id, 0)), source, manga_id) do update
+        set chapter_title = excluded.chapter_title, chapter_url = excluded.chapter_url, read_at = excluded.read_at
+        "#,
+    );
+
+    let query_result = query_builder.build().execute(pool).await?;
+
+    Ok(query_result.rows_affected())
+}
+
+/// the owner of an api token, its last use is recorded
+pub async fn use_api_token(
+    token_hash: &str,
+    now: chrono::NaiveDateTime,
+    pool: &PgPool,
+) -> Result<UserRow, sqlx::Error> {
+    sqlx::query_as::<_, UserRow>(
+        r#"
+        with used as (
+            update api_tokens set last_used_at = $2 where token_hash = $1 returning user_id
+        )
+        select u.* from users u join used on u.id = used.user_id
+        "#,
+    )
+    .bind(token_hash)
+    .bind(now)
+    .fetch_one(pool)
+    .await
+}
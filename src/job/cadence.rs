@@ -0,0 +1,269 @@
+use std::{collections::HashMap, env, str::FromStr};
+
+use chrono::{Duration, NaiveDateTime};
+use strum_macros::{Display, EnumString};
+
+use crate::{core::types::MangaSource, db::model::MangaRow};
+
+/// how the update job picks the series it checks on a run
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Display, EnumString)]
+#[strum(serialize_all = "snake_case")]
+pub enum ScheduleMode {
+    /// every series on every run
+    #[default]
+    All,
+
+    /// series expected to release soon on every run, the others once in a while
+    Cadence,
+}
+
+#[derive(Debug, Clone)]
+pub struct CadencePolicy {
+    pub mode: ScheduleMode,
+    /// series are checked on every run from `window` before their expected release
+    pub window: Duration,
+    /// series far from a release are still checked at least this often
+    pub fallback_interval: Duration,
+}
+
+impl Default for CadencePolicy {
+    fn default() -> Self {
+        Self {
+            mode: ScheduleMode::default(),
+            window: Duration::hours(12),
+            fallback_interval: Duration::hours(24),
+        }
+    }
+}
+
+impl CadencePolicy {
+    pub fn from_env() -> Self {
+        let default = Self::default();
+
+        Self {
+            mode: env::var("SCHEDULE_MODE")
+                .ok()
+                .and_then(|v| ScheduleMode::from_str(&v).ok())
+                .unwrap_or(default.mode),
+            window: env::var("CADENCE_WINDOW_HOURS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .map(Duration::hours)
+                .unwrap_or(default.window),
+            fallback_interval: env::var("CADENCE_FALLBACK_HOURS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .map(Duration::hours)
+                .unwrap_or(default.fallback_interval),
+        }
+    }
+
+    /// whether `series` should be fetched on this run, `since_attempt` is the time since it was
+    /// last fetched (None if never) and `now` is in japan time like the release dates
+    pub fn is_due(
+        &self,
+        series: &MangaRow,
+        cadence: &Cadence,
+        since_attempt: Option<Duration>,
+        now: NaiveDateTime,
+    ) -> bool {
+        if self.mode == ScheduleMode::All {
+            return true;
+        }
+
+        let Some(since_attempt) = since_attempt else {
+            return true;
+        };
+
+        // an announced chapter is about to come out
+        if !series.latest_chapter_released
+            && series.latest_chapter_release_date - self.window <= now
+        {
+            return true;
+        }
+
+        // around the expected release, until it shows up or a whole period went by (hiatus)
+        let expected = cadence.next_expected;
+        if expected - self.window <= now && now <= expected + cadence.interval {
+            return true;
+        }
+
+        since_attempt >= self.fallback_interval
+    }
+}
+
+/// release rhythm of a series learned from its chapter history
+#[derive(Debug, Clone, PartialEq)]
+pub struct Cadence {
+    /// median gap between two releases
+    pub interval: Duration,
+    pub next_expected: NaiveDateTime,
+}
+
+impl Cadence {
+    const MIN_INTERVAL: Duration = Duration::days(1);
+    const MAX_INTERVAL: Duration = Duration::days(60);
+
+    /// `release_dates` are the known chapters of the series, in any order. Series with a single
+    /// known chapter are assumed to be weekly
+    pub fn estimate(series: &MangaRow, release_dates: &[NaiveDateTime]) -> Self {
+        let mut dates: Vec<_> = release_dates
+            .iter()
+            .copied()
+            .chain([series.latest_chapter_release_date])
+            .collect();
+        dates.sort();
+        dates.dedup();
+
+        // several chapters released at once (or backfilled) say nothing about the rhythm
+        let mut gaps: Vec<_> = dates
+            .windows(2)
+            .map(|w| w[1] - w[0])
+            .filter(|gap| *gap >= Self::MIN_INTERVAL)
+            .collect();
+        gaps.sort();
+
+        let interval = gaps
+            .get(gaps.len() / 2)
+            .copied()
+            .unwrap_or(Duration::weeks(1))
+            .clamp(Self::MIN_INTERVAL, Self::MAX_INTERVAL);
+
+        let latest = *dates.last().unwrap();
+        let next_expected = if series.latest_chapter_released {
+            latest + interval
+        } else {
+            latest
+        };
+
+        Self {
+            interval,
+            next_expected,
+        }
+    }
+}
+
+/// group chapter release dates per series
+pub fn release_history(
+    rows: Vec<(MangaSource, String, NaiveDateTime)>,
+) -> HashMap<(MangaSource, String), Vec<NaiveDateTime>> {
+    let mut history: HashMap<_, Vec<_>> = HashMap::new();
+
+    for (source, manga_id, release_date) in rows {
+        history
+            .entry((source, manga_id))
+            .or_default()
+            .push(release_date);
+    }
+
+    history
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+
+    use super::*;
+    use crate::db::model::DbWeekday;
+
+    fn date(day: u32, hour: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2026, 10, day)
+            .unwrap()
+            .and_hms_opt(hour, 0, 0)
+            .unwrap()
+    }
+
+    fn series(latest_release: NaiveDateTime, released: bool) -> MangaRow {
+        MangaRow {
+            source: MangaSource::ShounenJumpPlus,
+            manga_id: "1".into(),
+            cover_url: String::new(),
+            author: String::new(),
+            title: "title".into(),
+            latest_chapter_title: "ch".into(),
+            latest_chapter_url: String::new(),
+            latest_chapter_release_date: latest_release,
+            latest_chapter_publish_day: DbWeekday::Mon,
+            latest_chapter_released: released,
+            last_update: latest_release,
+            tags: Vec::new(),
+        }
+    }
+
+    fn cadence_policy() -> CadencePolicy {
+        CadencePolicy {
+            mode: ScheduleMode::Cadence,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn estimate_weekly_cadence() {
+        let row = series(date(19, 0), true);
+        let cadence = Cadence::estimate(&row, &[date(5, 0), date(12, 0), date(12, 0)]);
+
+        assert_eq!(cadence.interval, Duration::weeks(1));
+        assert_eq!(cadence.next_expected, date(26, 0));
+    }
+
+    #[test]
+    fn estimate_ignores_batch_releases() {
+        let row = series(date(15, 0), true);
+        let cadence = Cadence::estimate(&row, &[date(1, 0), date(1, 1), date(1, 2), date(8, 0)]);
+
+        assert_eq!(cadence.interval, Duration::weeks(1));
+    }
+
+    #[test]
+    fn estimate_single_chapter_is_weekly() {
+        let row = series(date(15, 0), true);
+        let cadence = Cadence::estimate(&row, &[]);
+
+        assert_eq!(cadence.interval, Duration::weeks(1));
+        assert_eq!(cadence.next_expected, date(22, 0));
+    }
+
+    #[test]
+    fn due_around_expected_release() {
+        let policy = cadence_policy();
+        let row = series(date(12, 0), true);
+        let cadence = Cadence::estimate(&row, &[date(5, 0)]);
+        let since = |hours| Some(Duration::hours(hours));
+
+        // next release expected on the 19th at midnight
+        assert!(!policy.is_due(&row, &cadence, since(1), date(18, 11)));
+        assert!(policy.is_due(&row, &cadence, since(3), date(18, 13)));
+        // overdue, keep checking until the chapter shows up
+        assert!(policy.is_due(&row, &cadence, since(1), date(20, 1)));
+    }
+
+    #[test]
+    fn due_when_upcoming_chapter_is_near() {
+        let policy = cadence_policy();
+        let row = series(date(20, 0), false);
+        let cadence = Cadence::estimate(&row, &[date(6, 0), date(13, 0)]);
+
+        assert!(!policy.is_due(&row, &cadence, Some(Duration::hours(1)), date(18, 1)));
+        assert!(policy.is_due(&row, &cadence, Some(Duration::hours(1)), date(19, 13)));
+    }
+
+    #[test]
+    fn others_fall_back_to_daily_check() {
+        let policy = cadence_policy();
+        let row = series(date(1, 0), true);
+        let cadence = Cadence::estimate(&row, &[]);
+
+        assert!(!policy.is_due(&row, &cadence, Some(Duration::hours(23)), date(3, 23)));
+        assert!(policy.is_due(&row, &cadence, Some(Duration::hours(24)), date(4, 0)));
+        assert!(policy.is_due(&row, &cadence, None, date(3, 1)));
+    }
+
+    #[test]
+    fn all_mode_is_always_due() {
+        let policy = CadencePolicy::default();
+        let row = series(date(1, 0), true);
+        let cadence = Cadence::estimate(&row, &[]);
+
+        assert!(policy.is_due(&row, &cadence, Some(Duration::hours(1)), date(3, 1)));
+    }
+}
@@ -1 +1,4 @@
+pub mod cadence;
+pub mod reminder;
+pub mod scheduler;
 pub mod series;
@@ -0,0 +1,284 @@
+use std::{collections::HashMap, env};
+
+use chrono::{Duration, Local, NaiveDateTime};
+use sqlx::PgPool;
+use thiserror::Error;
+
+use super::{
+    scheduler::{try_lock, UPDATE_JOB_LOCK_KEY},
+    series::{check_series, UpdateError, UpdateJob},
+};
+use crate::{
+    db::{
+        inquiry::{get_due_releases, get_next_release, get_series_health},
+        model::japan_now,
+    },
+    notify::build_sinks,
+};
+
+#[derive(Debug, Error)]
+pub enum ReminderError {
+    #[error("error looking for due releases: {0}")]
+    Db(#[from] sqlx::Error),
+
+    #[error(transparent)]
+    Update(#[from] UpdateError),
+}
+
+/// check a series right when its upcoming chapter is expected to go live instead of waiting for
+/// the next update job run
+#[derive(Debug, Clone)]
+pub struct ReminderPolicy {
+    /// left to the site to publish the chapter before checking
+    pub delay: Duration,
+    /// a chapter still not out is checked again after this
+    pub retry_interval: Duration,
+    /// past this the release is left to the update job
+    pub window: Duration,
+    /// longest sleep between two looks at the upcoming releases, picks up the series added or
+    /// found upcoming meanwhile
+    pub poll_interval: Duration,
+    /// wait before trying again when the update job is running
+    pub lock_retry: Duration,
+}
+
+impl Default for ReminderPolicy {
+    fn default() -> Self {
+        Self {
+            delay: Duration::minutes(2),
+            retry_interval: Duration::minutes(10),
+            window: Duration::hours(2),
+            poll_interval: Duration::minutes(5),
+            lock_retry: Duration::minutes(1),
+        }
+    }
+}
+
+impl ReminderPolicy {
+    /// None unless RELEASE_REMINDER is set to true
+    pub fn from_env() -> Option<Self> {
+        if !env::var("RELEASE_REMINDER").is_ok_and(|v| v == "true") {
+            return None;
+        }
+
+        let default = Self::default();
+        let minutes = |key: &str, default: Duration| {
+            env::var(key)
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .map(Duration::minutes)
+                .unwrap_or(default)
+        };
+
+        Some(Self {
+            delay: minutes("RELEASE_REMINDER_DELAY_MINUTES", default.delay),
+            retry_interval: minutes("RELEASE_REMINDER_RETRY_MINUTES", default.retry_interval),
+            window: minutes("RELEASE_REMINDER_WINDOW_MINUTES", default.window),
+            poll_interval: default.poll_interval,
+            lock_retry: default.lock_retry,
+        })
+    }
+
+    /// how long to sleep from `now` until `next_release` is due
+    pub fn next_wake(&self, next_release: Option<NaiveDateTime>, now: NaiveDateTime) -> Duration {
+        match next_release {
+            Some(release) => {
+                (release + self.delay - now).clamp(Duration::zero(), self.poll_interval)
+            }
+            None => self.poll_interval,
+        }
+    }
+}
+
+/// release dates are stored as japan wall clock time, see `japan_now`
+pub async fn run_reminders(policy: ReminderPolicy, job: UpdateJob, pool: PgPool) {
+    loop {
+        let locked = match check_due_releases(&policy, &job, &pool).await {
+            Ok(Some(_)) => false,
+            Ok(None) => {
+                println!(
+                    "update job is running, release reminders retry in {} minutes",
+                    policy.lock_retry.num_minutes()
+                );
+                true
+            }
+            Err(e) => {
+                println!("Release reminder error: {e}");
+                false
+            }
+        };
+
+        let wait = match locked {
+            true => policy.lock_retry,
+            false => {
+                let now = japan_now();
+                let next_release = get_next_release(now, &pool).await.unwrap_or_else(|e| {
+                    println!("Release reminder error: {e}");
+                    None
+                });
+
+                policy.next_wake(next_release, now)
+            }
+        };
+        tokio::time::sleep(wait.to_std().unwrap_or_default()).await;
+    }
+}
+
+/// check the series whose chapter just became due, returns the number of releases found or None
+/// when the update job holds the lock
+pub async fn check_due_releases(
+    policy: &ReminderPolicy,
+    job: &UpdateJob,
+    pool: &PgPool,
+) -> Result<Option<usize>, ReminderError> {
+    // the update job checks every series anyway, racing it would announce releases twice. A
+    // scheduled tick firing meanwhile waits for this pass rather than being skipped
+    let mut trx = pool.begin().await?;
+    if !try_lock(UPDATE_JOB_LOCK_KEY, &mut *trx).await? {
+        return Ok(None);
+    }
+
+    let now = japan_now();
+    let due = get_due_releases(
+        now - policy.window,
+        now - policy.delay,
+        Local::now().naive_local() - policy.retry_interval,
+        pool,
+    )
+    .await?;
+    if due.is_empty() {
+        return Ok(Some(0));
+    }
+
+    let keys: Vec<_> = due
+        .iter()
+        .map(|row| (row.source.clone(), row.manga_id.clone()))
+        .collect();
+    let health: HashMap<_, _> = get_series_health(&keys, pool)
+        .await?
+        .into_iter()
+        .map(|h| ((h.source.clone(), h.manga_id.clone()), h))
+        .collect();
+    let sinks = build_sinks(&job.sinks).map_err(UpdateError::Notifier)?;
+
+    let summary = check_series(
+        due,
+        health,
+        &sinks,
+        true,
+        &job.webdriver_url,
+        &job.suspend_policy,
+        pool,
+    )
+    .await?;
+    println!(
+        "release reminder checked {} series, {} released",
+        summary.checked, summary.released
+    );
+
+    trx.commit().await?;
+
+    Ok(Some(summary.released))
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+    use chrono_tz::Japan;
+
+    use super::*;
+    use crate::{
+        core::types::{Manga, MangaSource},
+        db::insert::insert_manga,
+        testcontainer::postgres_container::get_test_db,
+    };
+
+    fn at(hour: u32, minute: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2026, 10, 18)
+            .unwrap()
+            .and_hms_opt(hour, minute, 0)
+            .unwrap()
+    }
+
+    #[test]
+    fn wake_up_after_the_next_release() {
+        let policy = ReminderPolicy::default();
+
+        assert_eq!(
+            policy.next_wake(Some(at(12, 1)), at(12, 0)),
+            Duration::minutes(3)
+        );
+        assert_eq!(
+            policy.next_wake(Some(at(15, 0)), at(12, 0)),
+            policy.poll_interval
+        );
+        assert_eq!(policy.next_wake(None, at(12, 0)), policy.poll_interval);
+    }
+
+    #[tokio::test]
+    async fn due_release_waits_for_retry_interval() {
+        let db = get_test_db("reminder_due_release").await.unwrap();
+        let now = japan_now();
+        let release_date = (now - Duration::minutes(5))
+            .and_local_timezone(Japan)
+            .unwrap()
+            .fixed_offset();
+
+        insert_manga(
+            MangaSource::ShounenJumpPlus,
+            "1".into(),
+            Manga {
+                title: "title".into(),
+                cover_url: String::new(),
+                author: String::new(),
+                latest_chapter_title: "ch".into(),
+                latest_chapter_url: String::new(),
+                latest_chapter_release_date: release_date,
+                latest_chapter_publish_day: chrono::Weekday::Mon,
+                chapters: Vec::new(),
+            },
+            &db.0,
+        )
+        .await
+        .unwrap();
+        sqlx::query("update series set latest_chapter_released = false")
+            .execute(&db.0)
+            .await
+            .unwrap();
+
+        let policy = ReminderPolicy::default();
+        let due = |attempted_before| {
+            get_due_releases(
+                now - policy.window,
+                now - policy.delay,
+                attempted_before,
+                &db.0,
+            )
+        };
+
+        // just checked when the series was added
+        let attempted_before = Local::now().naive_local() - policy.retry_interval;
+        assert!(due(attempted_before).await.unwrap().is_empty());
+
+        let attempted_before = Local::now().naive_local() + Duration::minutes(1);
+        assert_eq!(due(attempted_before).await.unwrap().len(), 1);
+
+        assert!(get_next_release(now - Duration::hours(1), &db.0)
+            .await
+            .unwrap()
+            .is_some());
+        assert_eq!(get_next_release(now, &db.0).await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn due_releases_wait_for_update_job() {
+        let db = get_test_db("reminder_locked").await.unwrap();
+        let job = UpdateJob::from_env(Vec::new(), "".into());
+
+        let mut running = db.0.begin().await.unwrap();
+        assert!(try_lock(UPDATE_JOB_LOCK_KEY, &mut *running).await.unwrap());
+
+        let result = check_due_releases(&ReminderPolicy::default(), &job, &db.0).await;
+        assert_eq!(result.unwrap(), None);
+    }
+}
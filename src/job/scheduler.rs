@@ -0,0 +1,246 @@
+use std::{env, str::FromStr};
+
+use chrono::{NaiveDateTime, Utc};
+use chrono_tz::Tz;
+use cron::Schedule;
+use sqlx::{Connection, PgConnection, PgPool};
+use thiserror::Error;
+
+use crate::db::insert::claim_scheduled_tick;
+
+use super::series::UpdateJob;
+
+/// key of the postgres advisory lock held by the replica running the update job
+pub const UPDATE_JOB_LOCK_KEY: i64 = 0x6d61_6e67_6121;
+const UPDATE_JOB_NAME: &str = "update_series";
+
+#[derive(Debug, Error)]
+pub enum SchedulerError {
+    #[error("invalid cron expression {expression:?}: {source}")]
+    InvalidExpression {
+        expression: String,
+        source: cron::error::Error,
+    },
+
+    #[error("unknown timezone {0:?}")]
+    InvalidTimezone(String),
+}
+
+#[derive(Debug, Clone)]
+pub struct SchedulerConfig {
+    pub expression: String,
+    pub schedule: Schedule,
+    pub timezone: Tz,
+}
+
+impl SchedulerConfig {
+    /// None when UPDATE_SCHEDULE isn't set, the update job is then expected to be started by
+    /// the `update` command (e.g. from a kubernetes cronjob)
+    pub fn from_env() -> Result<Option<Self>, SchedulerError> {
+        let Ok(expression) = env::var("UPDATE_SCHEDULE") else {
+            return Ok(None);
+        };
+        let timezone = env::var("UPDATE_SCHEDULE_TIMEZONE").unwrap_or("UTC".into());
+
+        Self::new(&expression, &timezone).map(Some)
+    }
+
+    pub fn new(expression: &str, timezone: &str) -> Result<Self, SchedulerError> {
+        let schedule =
+            parse_schedule(expression).map_err(|source| SchedulerError::InvalidExpression {
+                expression: expression.to_owned(),
+                source,
+            })?;
+        let timezone = Tz::from_str(timezone)
+            .map_err(|_| SchedulerError::InvalidTimezone(timezone.to_owned()))?;
+
+        Ok(Self {
+            expression: expression.to_owned(),
+            schedule,
+            timezone,
+        })
+    }
+}
+
+/// the cron crate expects a seconds field, the 5 fields expression of a kubernetes cronjob is
+/// accepted as well
+fn parse_schedule(expression: &str) -> Result<Schedule, cron::error::Error> {
+    let expression = expression.trim();
+
+    match expression.split_whitespace().count() {
+        5 => Schedule::from_str(&format!("0 {expression}")),
+        _ => Schedule::from_str(expression),
+    }
+}
+
+pub async fn run_scheduler(config: SchedulerConfig, job: UpdateJob, pool: PgPool) {
+    loop {
+        let Some(tick) = config.schedule.upcoming(config.timezone).next() else {
+            println!("update schedule has no upcoming run, stopping scheduler");
+            return;
+        };
+
+        let wait = (tick.with_timezone(&Utc) - Utc::now())
+            .to_std()
+            .unwrap_or_default();
+        tokio::time::sleep(wait).await;
+
+        if let Err(e) = run_tick(&job, &pool, tick.naive_utc()).await {
+            println!("Scheduled update job error: {e}");
+        }
+    }
+}
+
+/// every replica wakes up on each tick and waits for the lock, the first one claiming the tick
+/// runs the job. The claim is committed on its own so no transaction stays open while the job
+/// runs, the other replicas find the tick taken once they get the lock
+async fn run_tick(job: &UpdateJob, pool: &PgPool, tick: NaiveDateTime) -> Result<(), sqlx::Error> {
+    let mut conn = lock_connection(pool).await?;
+
+    if !claim_scheduled_tick(UPDATE_JOB_NAME, tick, &mut conn).await? {
+        println!("update job already ran for {tick}, skipping");
+        return conn.close().await;
+    }
+
+    println!("start updating series");
+    if !job.run(pool).await {
+        println!("scheduled update job for {tick} failed, see job run history");
+    }
+
+    conn.close().await
+}
+
+pub async fn try_lock(key: i64, conn: &mut PgConnection) -> Result<bool, sqlx::Error> {
+    sqlx::query_scalar("select pg_try_advisory_xact_lock($1)")
+        .bind(key)
+        .fetch_one(conn)
+        .await
+}
+
+/// wait for the lock held by the session rather than a transaction, it is released when the
+/// connection closes
+pub async fn lock(key: i64, conn: &mut PgConnection) -> Result<(), sqlx::Error> {
+    sqlx::query("select pg_advisory_lock($1)")
+        .bind(key)
+        .execute(conn)
+        .await?;
+
+    Ok(())
+}
+
+/// a connection holding the update job lock, closing it releases the lock. It is detached so
+/// the lock never goes back to the pool, a crashed replica can't keep it either
+async fn lock_connection(pool: &PgPool) -> Result<PgConnection, sqlx::Error> {
+    let mut conn = pool.acquire().await?.detach();
+    lock(UPDATE_JOB_LOCK_KEY, &mut conn).await?;
+
+    Ok(conn)
+}
+
+/// run the job for the `update` command which has no later tick to fall back on, it waits for
+/// a run started by the server to finish. The lock is held by a dedicated connection so no
+/// transaction stays open for the whole run
+pub async fn run_locked(job: &UpdateJob, pool: &PgPool) -> Result<bool, sqlx::Error> {
+    let conn = lock_connection(pool).await?;
+
+    let succeeded = job.run(pool).await;
+
+    conn.close().await?;
+
+    Ok(succeeded)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testcontainer::postgres_container::get_test_db;
+
+    #[test]
+    fn parse_kubernetes_cron_expression() {
+        let config = SchedulerConfig::new("0 */6 * * *", "Asia/Jakarta").unwrap();
+        let mut upcoming = config.schedule.upcoming(config.timezone);
+
+        let first = upcoming.next().unwrap();
+        let second = upcoming.next().unwrap();
+        assert_eq!((second - first).num_hours(), 6);
+    }
+
+    #[test]
+    fn reject_invalid_schedule() {
+        assert!(matches!(
+            SchedulerConfig::new("every day", "UTC"),
+            Err(SchedulerError::InvalidExpression { .. })
+        ));
+        assert!(matches!(
+            SchedulerConfig::new("0 0 * * *", "Mars/Olympus"),
+            Err(SchedulerError::InvalidTimezone(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn only_one_replica_gets_the_lock() {
+        let db = get_test_db("scheduler_lock").await.unwrap();
+        let tick = Utc::now().naive_utc();
+
+        let mut first = db.0.begin().await.unwrap();
+        let mut second = db.0.begin().await.unwrap();
+
+        assert!(try_lock(UPDATE_JOB_LOCK_KEY, &mut *first).await.unwrap());
+        assert!(!try_lock(UPDATE_JOB_LOCK_KEY, &mut *second).await.unwrap());
+
+        assert!(claim_scheduled_tick(UPDATE_JOB_NAME, tick, &mut *first)
+            .await
+            .unwrap());
+        first.commit().await.unwrap();
+        second.rollback().await.unwrap();
+
+        // lock is released with the transaction but the tick can't be claimed twice
+        let mut third = db.0.begin().await.unwrap();
+        assert!(try_lock(UPDATE_JOB_LOCK_KEY, &mut *third).await.unwrap());
+        assert!(!claim_scheduled_tick(UPDATE_JOB_NAME, tick, &mut *third)
+            .await
+            .unwrap());
+    }
+
+    #[tokio::test]
+    async fn session_lock_held_until_closed() {
+        let db = get_test_db("scheduler_session_lock").await.unwrap();
+
+        let mut conn = db.0.acquire().await.unwrap().detach();
+        lock(UPDATE_JOB_LOCK_KEY, &mut conn).await.unwrap();
+
+        let mut trx = db.0.begin().await.unwrap();
+        assert!(!try_lock(UPDATE_JOB_LOCK_KEY, &mut *trx).await.unwrap());
+        trx.rollback().await.unwrap();
+
+        conn.close().await.unwrap();
+
+        let mut trx = db.0.begin().await.unwrap();
+        assert!(try_lock(UPDATE_JOB_LOCK_KEY, &mut *trx).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn tick_waits_for_release_reminder() {
+        let db = get_test_db("scheduler_tick_waits").await.unwrap();
+        let tick = Utc::now().naive_utc();
+        let job = UpdateJob::from_env(Vec::new(), "".into());
+
+        // a release reminder pass holds the lock when the tick fires
+        let mut reminder = db.0.begin().await.unwrap();
+        assert!(try_lock(UPDATE_JOB_LOCK_KEY, &mut *reminder).await.unwrap());
+
+        let pool = db.0.clone();
+        let scheduled = tokio::spawn(async move { run_tick(&job, &pool, tick).await });
+        tokio::time::sleep(std::time::Duration::from_millis(500)).await;
+        assert!(!scheduled.is_finished());
+
+        reminder.commit().await.unwrap();
+        scheduled.await.unwrap().unwrap();
+
+        // the tick ran once the reminder was done instead of being skipped
+        let mut conn = db.0.acquire().await.unwrap();
+        assert!(!claim_scheduled_tick(UPDATE_JOB_NAME, tick, &mut conn)
+            .await
+            .unwrap());
+    }
+}
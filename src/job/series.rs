@@ -1,12 +1,30 @@
-use std::{num::NonZeroU32, sync::Arc, time::Duration};
+use std::{collections::HashMap, env, fmt, num::NonZeroU32, sync::Arc, time::Duration};
 
+use chrono::NaiveDateTime;
 use governor::{DefaultKeyedRateLimiter, Jitter, Quota, RateLimiter};
-use serenity::all::{CreateEmbed, ExecuteWebhook, Http, Webhook};
 use sqlx::PgPool;
+use thiserror::Error;
 
+use super::cadence::{release_history, Cadence, CadencePolicy, ScheduleMode};
 use crate::{
-    core::types::{MangaQuery, MangaSource},
-    db::{inquiry::get_manga_paginated, model::MangaRow, update::update_manga_batch},
+    core::{
+        fetch::FetchError,
+        types::{Chapter, FetchErrorKind, Manga, MangaQuery, MangaSource},
+    },
+    db::{
+        inquiry::{
+            get_all_series_health, get_chapter_release_dates, get_manga_paginated,
+            get_series_chapters,
+        },
+        insert::{insert_job_run, upsert_series_health},
+        model::{japan_now, ChapterRow, JobRunErrorRow, JobRunRow, MangaRow, SeriesHealthRow},
+        update::{finish_job_run, update_manga_batch},
+    },
+    notify::{
+        build_sinks,
+        outbox::{dispatch, routed_events},
+        NotifyError, ReleaseEvent, ReleaseKind, Sink, SinkConfig,
+    },
 };
 
 #[derive(Debug)]
@@ -16,54 +34,475 @@ pub enum DiffingResult {
     Released(MangaRow),
 }
 
-pub async fn update_series(webhook_url: String, webdriver_url: String, pool: &PgPool) {
-    // retrieve series from db (paginated) based on the current day
-    // for each series check for latest update
+#[derive(Debug, Error)]
+pub enum UpdateError {
+    #[error("error retrieving series from db: {0}")]
+    RetrieveSeries(sqlx::Error),
+
+    #[error("error updating series in db: {0}")]
+    UpdateSeries(sqlx::Error),
+
+    #[error("error recording series health in db: {0}")]
+    RecordHealth(sqlx::Error),
+
+    #[error("error setting up notification sinks: {0}")]
+    Notifier(NotifyError),
+
+    #[error("error accessing notification outbox: {0}")]
+    Outbox(sqlx::Error),
+}
+
+#[derive(Debug)]
+pub struct SeriesFailure {
+    pub source: MangaSource,
+    pub manga_id: String,
+    /// None when the task panicked instead of returning a fetch error
+    pub kind: Option<FetchErrorKind>,
+    pub error: String,
+}
+
+#[derive(Debug, Default)]
+pub struct UpdateSummary {
+    pub checked: usize,
+    /// series left out of the run, suspended or not expected to release yet
+    pub skipped: usize,
+    pub released: usize,
+    pub upcoming: usize,
+    pub no_change: usize,
+    pub failed: Vec<SeriesFailure>,
+    pub newly_suspended: Vec<(MangaSource, String)>,
+    pub notification_failures: usize,
+}
+
+impl fmt::Display for UpdateSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "checked: {}, skipped: {}, released: {}, upcoming: {}, no change: {}, failed: {}, notification failures: {}",
+            self.checked,
+            self.skipped,
+            self.released,
+            self.upcoming,
+            self.no_change,
+            self.failed.len(),
+            self.notification_failures
+        )?;
+
+        for failure in &self.failed {
+            writeln!(
+                f,
+                "  {} {}: {}",
+                failure.source, failure.manga_id, failure.error
+            )?;
+        }
+
+        for (source, manga_id) in &self.newly_suspended {
+            writeln!(f, "  suspended {source} {manga_id}")?;
+        }
+
+        Ok(())
+    }
+}
+
+/// limits above which the update job is reported as failed
+#[derive(Debug, Clone)]
+pub struct UpdateThresholds {
+    pub max_failure_ratio: f64,
+    pub max_notification_failures: usize,
+}
+
+impl Default for UpdateThresholds {
+    fn default() -> Self {
+        Self {
+            max_failure_ratio: 0.5,
+            max_notification_failures: 0,
+        }
+    }
+}
+
+impl UpdateThresholds {
+    pub fn from_env() -> Self {
+        let default = Self::default();
+
+        Self {
+            max_failure_ratio: env::var("UPDATE_MAX_FAILURE_RATIO")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(default.max_failure_ratio),
+            max_notification_failures: env::var("UPDATE_MAX_NOTIFICATION_FAILURES")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(default.max_notification_failures),
+        }
+    }
+}
+
+/// series failing `after_failures` runs in a row are suspended, they are then probed again after
+/// `base_delay`, doubled on every failed probe up to `max_delay`
+#[derive(Debug, Clone)]
+pub struct SuspendPolicy {
+    pub after_failures: i32,
+    pub base_delay: chrono::Duration,
+    pub max_delay: chrono::Duration,
+}
+
+impl Default for SuspendPolicy {
+    fn default() -> Self {
+        Self {
+            after_failures: 5,
+            base_delay: chrono::Duration::hours(6),
+            max_delay: chrono::Duration::days(7),
+        }
+    }
+}
+
+impl SuspendPolicy {
+    pub fn from_env() -> Self {
+        let default = Self::default();
+
+        Self {
+            after_failures: env::var("SUSPEND_AFTER_FAILURES")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(default.after_failures),
+            base_delay: env::var("SUSPEND_BASE_DELAY_HOURS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .map(chrono::Duration::hours)
+                .unwrap_or(default.base_delay),
+            max_delay: env::var("SUSPEND_MAX_DELAY_HOURS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .map(chrono::Duration::hours)
+                .unwrap_or(default.max_delay),
+        }
+    }
+
+    /// delay before the next probe of a series that failed `failures` runs in a row,
+    /// None when it shouldn't be suspended yet
+    pub fn probe_delay(&self, failures: i32) -> Option<chrono::Duration> {
+        if failures < self.after_failures {
+            return None;
+        }
+
+        // cap the exponent, the max delay is reached long before anyway
+        let exponent = (failures - self.after_failures).min(16) as u32;
+        let delay = self.base_delay * 2_i32.pow(exponent);
+
+        Some(delay.min(self.max_delay))
+    }
+
+    /// suspend the series (or push back its next probe) when it failed too many times
+    pub fn apply(&self, health: &mut SeriesHealthRow) {
+        if let Some(delay) = self.probe_delay(health.consecutive_failures) {
+            health.suspended = true;
+            health.next_probe_at = Some(health.last_attempt + delay);
+        }
+    }
+
+    pub fn is_due(&self, health: &SeriesHealthRow, now: NaiveDateTime) -> bool {
+        !health.suspended || health.next_probe_at.is_none_or(|probe_at| probe_at <= now)
+    }
+}
+
+impl UpdateSummary {
+    pub fn exceeds(&self, thresholds: &UpdateThresholds) -> bool {
+        let failure_ratio = match self.checked {
+            0 => 0.0,
+            checked => self.failed.len() as f64 / checked as f64,
+        };
+
+        failure_ratio > thresholds.max_failure_ratio
+            || self.notification_failures > thresholds.max_notification_failures
+    }
+}
+
+/// everything the update job needs besides the db, shared by the `update` command and the
+/// in-process scheduler
+#[derive(Debug, Clone)]
+pub struct UpdateJob {
+    pub sinks: Vec<SinkConfig>,
+    pub webdriver_url: String,
+    pub suspend_policy: SuspendPolicy,
+    pub cadence_policy: CadencePolicy,
+    pub thresholds: UpdateThresholds,
+}
+
+impl UpdateJob {
+    pub fn from_env(sinks: Vec<SinkConfig>, webdriver_url: String) -> Self {
+        Self {
+            sinks,
+            webdriver_url,
+            suspend_policy: SuspendPolicy::from_env(),
+            cadence_policy: CadencePolicy::from_env(),
+            thresholds: UpdateThresholds::from_env(),
+        }
+    }
+
+    /// run the job, print its summary and record it in job_runs, returns false when the run
+    /// should be considered failed
+    pub async fn run(&self, pool: &PgPool) -> bool {
+        let started_at = chrono::offset::Local::now().naive_local();
+        // failing to record the run must not prevent it
+        let run_id = insert_job_run(started_at, pool)
+            .await
+            .inspect_err(|e| println!("Error recording job run: {e}"))
+            .ok();
+
+        let result = update_series(self, pool).await;
+
+        if let Some(run_id) = run_id {
+            let (run, errors) = job_run_rows(run_id, started_at, &result);
+            if let Err(e) = finish_job_run(&run, &errors, pool).await {
+                println!("Error recording job run: {e}");
+            }
+        }
+
+        match result {
+            Ok(summary) => {
+                println!("{summary}");
+                if summary.exceeds(&self.thresholds) {
+                    println!(
+                        "Update series job exceeded failure thresholds {:?}",
+                        self.thresholds
+                    );
+                    return false;
+                }
+
+                true
+            }
+            Err(e) => {
+                println!("Update series job failed: {e}");
+                false
+            }
+        }
+    }
+}
+
+fn job_run_rows(
+    run_id: i64,
+    started_at: NaiveDateTime,
+    result: &Result<UpdateSummary, UpdateError>,
+) -> (JobRunRow, Vec<JobRunErrorRow>) {
+    let mut run = JobRunRow {
+        id: run_id,
+        started_at,
+        finished_at: Some(chrono::offset::Local::now().naive_local()),
+        ..Default::default()
+    };
+
+    let summary = match result {
+        Ok(summary) => summary,
+        Err(e) => {
+            run.error = Some(e.to_string());
+            return (run, Vec::new());
+        }
+    };
+
+    run.checked = summary.checked as i32;
+    run.skipped = summary.skipped as i32;
+    run.released = summary.released as i32;
+    run.upcoming = summary.upcoming as i32;
+    run.no_change = summary.no_change as i32;
+    run.failed = summary.failed.len() as i32;
+    run.notification_failures = summary.notification_failures as i32;
+
+    let errors = summary
+        .failed
+        .iter()
+        .map(|failure| JobRunErrorRow {
+            run_id,
+            source: failure.source.clone(),
+            manga_id: failure.manga_id.clone(),
+            error_kind: failure.kind,
+            error: failure.error.clone(),
+        })
+        .collect();
+
+    (run, errors)
+}
+
+pub async fn update_series(job: &UpdateJob, pool: &PgPool) -> Result<UpdateSummary, UpdateError> {
+    let UpdateJob {
+        sinks,
+        webdriver_url,
+        suspend_policy,
+        cadence_policy,
+        ..
+    } = job;
+
+    // retrieve series from db (paginated), then keep the ones due for a check on this run
     let mut page_counter = 1;
     let mut all_series: Vec<MangaRow> = Vec::new();
 
     loop {
         println!("fetching series from db: page {page_counter}");
-        let series = get_manga_paginated(page_counter, 10, MangaQuery::default(), pool).await;
-
-        if let Ok(mut result) = series {
-            if result.data.is_empty() {
-                break;
-            }
+        let mut result = get_manga_paginated(page_counter, 10, MangaQuery::default(), pool)
+            .await
+            .map_err(UpdateError::RetrieveSeries)?;
 
-            all_series.append(&mut result.data);
-        } else {
-            panic!("Error retriving series from db {series:?}");
+        if result.data.is_empty() {
+            break;
         }
 
+        all_series.append(&mut result.data);
         page_counter += 1;
     }
 
+    let all_health: HashMap<_, _> = get_all_series_health(pool)
+        .await
+        .map_err(UpdateError::RetrieveSeries)?
+        .into_iter()
+        .map(|h| ((h.source.clone(), h.manga_id.clone()), h))
+        .collect();
+
+    let history = match cadence_policy.mode {
+        ScheduleMode::All => HashMap::new(),
+        ScheduleMode::Cadence => release_history(
+            get_chapter_release_dates(pool)
+                .await
+                .map_err(UpdateError::RetrieveSeries)?,
+        ),
+    };
+
+    // suspended series are left alone until their next probe, the others until they're
+    // expected to release
+    let now = chrono::offset::Local::now().naive_local();
+    let release_now = japan_now();
+    let total_series = all_series.len();
+    let all_series: Vec<_> = all_series
+        .into_iter()
+        .filter(|series| {
+            let key = (series.source.clone(), series.manga_id.clone());
+            let health = all_health.get(&key);
+            if !health.is_none_or(|health| suspend_policy.is_due(health, now)) {
+                return false;
+            }
+
+            let release_dates = history.get(&key).map_or(&[][..], |dates| dates.as_slice());
+            let cadence = Cadence::estimate(series, release_dates);
+            let since_attempt = health.map(|h| now - h.last_attempt);
+            cadence_policy.is_due(series, &cadence, since_attempt, release_now)
+        })
+        .collect();
+
+    let sinks = build_sinks(sinks).map_err(UpdateError::Notifier)?;
+
+    let skipped = total_series - all_series.len();
+    let mut summary = check_series(
+        all_series,
+        all_health,
+        &sinks,
+        true,
+        webdriver_url,
+        suspend_policy,
+        pool,
+    )
+    .await?;
+    summary.skipped = skipped;
+
+    println!("Update series job finished");
+
+    Ok(summary)
+}
+
+/// fetch the given series, record their health, write the changes along their outbox entries
+/// for the routed sinks and dispatch the outbox. Shared by the update job and the manual
+/// refresh, without broadcast the outbox entries are recorded as suppressed and nothing is sent
+pub async fn check_series(
+    series_list: Vec<MangaRow>,
+    mut all_health: HashMap<(MangaSource, String), SeriesHealthRow>,
+    sinks: &[Sink],
+    broadcast: bool,
+    webdriver_url: &str,
+    suspend_policy: &SuspendPolicy,
+    pool: &PgPool,
+) -> Result<UpdateSummary, UpdateError> {
     // generate diff state
     let lim: Arc<DefaultKeyedRateLimiter<MangaSource>> = Arc::new(RateLimiter::keyed(
         Quota::per_second(NonZeroU32::new(1).unwrap()),
     ));
     let mut tasks = vec![];
     let mut task_output = vec![];
+    let mut health_rows = vec![];
+    let mut summary = UpdateSummary {
+        checked: series_list.len(),
+        ..Default::default()
+    };
 
-    for series in all_series {
-        tasks.push(tokio::spawn(diff_update(
-            series,
-            lim.clone(),
-            webdriver_url.clone(),
-        )));
+    let keys: Vec<_> = series_list
+        .iter()
+        .map(|s| (s.source.clone(), s.manga_id.clone()))
+        .collect();
+    let mut stored_chapters: HashMap<_, Vec<ChapterRow>> = HashMap::new();
+    for chapter in get_series_chapters(&keys, pool)
+        .await
+        .map_err(UpdateError::RetrieveSeries)?
+    {
+        stored_chapters
+            .entry((chapter.source.clone(), chapter.manga_id.clone()))
+            .or_default()
+            .push(chapter);
     }
 
-    for task in tasks {
-        let task_result = task.await;
+    for series in series_list {
+        let key = (series.source.clone(), series.manga_id.clone());
+        let known = stored_chapters.remove(&key).unwrap_or_default();
+        tasks.push((
+            key,
+            tokio::spawn(diff_update(
+                series,
+                known,
+                lim.clone(),
+                webdriver_url.to_owned(),
+            )),
+        ));
+    }
 
-        match task_result {
-            Ok(diff) => {
-                task_output.push(diff);
-            }
-            Err(e) => {
-                println!("Error : {e}");
+    for ((source, manga_id), task) in tasks {
+        let key = (source.clone(), manga_id.clone());
+        let mut health = all_health
+            .remove(&key)
+            .unwrap_or_else(|| SeriesHealthRow::new(source.clone(), manga_id.clone()));
+
+        let failure = match task.await {
+            Ok(Ok(mut diff)) => {
+                health.succeeded();
+                health_rows.push(health);
+                task_output.append(&mut diff);
+                continue;
             }
+            Ok(Err(e)) => SeriesFailure {
+                source,
+                manga_id,
+                kind: Some(e.kind),
+                error: e.to_string(),
+            },
+            Err(e) => SeriesFailure {
+                source,
+                manga_id,
+                kind: None,
+                error: e.to_string(),
+            },
+        };
+
+        println!("Error : {}", failure.error);
+        let was_suspended = health.suspended;
+        health.failed(failure.kind, failure.error.clone());
+        suspend_policy.apply(&mut health);
+        if health.suspended && !was_suspended {
+            summary.newly_suspended.push(key);
+        }
+
+        health_rows.push(health);
+        summary.failed.push(failure);
+    }
+
+    for diff in &task_output {
+        match diff {
+            DiffingResult::NoChange => summary.no_change += 1,
+            DiffingResult::Upcoming(_) => summary.upcoming += 1,
+            DiffingResult::Released(_) => summary.released += 1,
         }
     }
 
@@ -76,109 +515,374 @@ pub async fn update_series(webhook_url: String, webdriver_url: String, pool: &Pg
         })
         .collect();
 
+    let mut conn = pool.acquire().await.map_err(UpdateError::RecordHealth)?;
+    upsert_series_health(health_rows.iter(), &mut conn)
+        .await
+        .map_err(UpdateError::RecordHealth)?;
+
     // update table
     if !rows.is_empty() {
-        update_manga_batch(rows.into_iter(), pool)
-            .await
-            .expect("Error updating manga details");
+        let events: Vec<_> = task_output.iter().filter_map(release_event).collect();
+        update_manga_batch(
+            rows.into_iter(),
+            routed_events(sinks, &events),
+            !broadcast,
+            pool,
+        )
+        .await
+        .map_err(UpdateError::UpdateSeries)?;
+    }
 
-        // broadcast diff change to webhook and update database
-        broadcast_diff(&webhook_url, task_output).await;
+    // deliver the new events along the ones left over by previous runs
+    if broadcast {
+        summary.notification_failures = dispatch(sinks, pool).await.map_err(UpdateError::Outbox)?;
     }
 
-    println!("Update series job finished")
+    Ok(summary)
 }
 
-pub async fn broadcast_diff(webhook_url: &str, diffs: Vec<DiffingResult>) {
-    let embeds = diffs.iter().filter_map(|d| match d {
+fn release_event(diff: &DiffingResult) -> Option<ReleaseEvent> {
+    match diff {
         DiffingResult::NoChange => None,
-        DiffingResult::Upcoming(manga) => Some(
-            CreateEmbed::new()
-                /*                 .url(&manga.latest_chapter_url) */
-                .title(format!("[UPCOMING] {}", &manga.latest_chapter_title))
-                .image(&manga.cover_url)
-                .field(
-                    "release_date",
-                    format!(
-                        "{}",
-                        &manga
-                            .latest_chapter_release_date
-                            .format("%d-%m-%Y %H:%M:%S")
-                    ),
-                    false,
-                )
-                .field("series_name", &manga.title, false)
-                .field("source", manga.source.to_string(), false)
-                .field("author", &manga.author, false),
-        ),
-        DiffingResult::Released(manga) => Some(
-            CreateEmbed::new()
-                .url(&manga.latest_chapter_url)
-                .title(format!("[RELEASED] {}", &manga.latest_chapter_title))
-                .image(&manga.cover_url)
-                .field("series_name", &manga.title, false)
-                .field("source", manga.source.to_string(), false)
-                .field("author", &manga.author, false),
-        ),
-    });
-
-    let http = Http::new("");
-    let webhook = Webhook::from_url(&http, webhook_url)
-        .await
-        .expect("Invalid webhook url");
-
-    for embed in embeds {
-        webhook
-            .execute(&http, true, ExecuteWebhook::new().embed(embed))
-            .await
-            .expect("Error hitting webhook");
-
-        tokio::time::sleep(Duration::from_millis(500)).await;
+        DiffingResult::Upcoming(manga) => {
+            Some(ReleaseEvent::from_row(ReleaseKind::Upcoming, manga))
+        }
+        DiffingResult::Released(manga) => {
+            Some(ReleaseEvent::from_row(ReleaseKind::Released, manga))
+        }
     }
 }
 
 pub async fn diff_update(
     data: MangaRow,
+    known: Vec<ChapterRow>,
     limiter: Arc<DefaultKeyedRateLimiter<MangaSource>>,
     webdriver_url: String,
-) -> DiffingResult {
-    limiter
-        .until_key_ready_with_jitter(
-            &data.source,
-            Jitter::new(Duration::from_secs(3), Duration::from_secs(1)),
+) -> Result<Vec<DiffingResult>, FetchError> {
+    let mut attempt = 0;
+
+    // for each mangarow retrieve latest update, transient error get one more try
+    let latest_update = loop {
+        limiter
+            .until_key_ready_with_jitter(
+                &data.source,
+                Jitter::new(Duration::from_secs(3), Duration::from_secs(1)),
+            )
+            .await;
+
+        match data.source.fetch(&webdriver_url, &data.manga_id).await {
+            Ok(manga) => break manga,
+            Err(e) if e.kind.is_transient() && attempt == 0 => {
+                println!("Retrying {} {}: {e}", data.source, data.manga_id);
+                attempt += 1;
+            }
+            Err(e) => return Err(e),
+        }
+    };
+
+    Ok(diff_manga(&data, &known, &latest_update))
+}
+
+/// generate diffing result for every chapter listed after the last known chapter and every
+/// stored chapter which came out since, ordered from the oldest one
+/// no change -> chapter title and release status doesn't change
+/// upcoming -> a new chapter appear but it's not released yet
+/// released -> a new chapter appear already released or a stored chapter become released
+pub fn diff_manga(
+    data: &MangaRow,
+    known: &[ChapterRow],
+    latest_update: &Manga,
+) -> Vec<DiffingResult> {
+    let chapters = &latest_update.chapters;
+
+    // tags are set by the user, the diffed rows keep the ones of the stored series
+    let to_row = |chapter: &Chapter| MangaRow {
+        tags: data.tags.clone(),
+        ..MangaRow::from_chapter(
+            data.manga_id.clone(),
+            data.source.clone(),
+            latest_update,
+            chapter,
         )
-        .await;
+    };
 
-    let source = data.source.clone();
+    // release state of the stored chapters, the series row is the reference for its own chapter
+    let mut stored: HashMap<&str, bool> = known
+        .iter()
+        .map(|c| (c.chapter_id.as_str(), c.released))
+        .collect();
+    stored.insert(
+        data.latest_chapter_title.as_str(),
+        data.latest_chapter_released,
+    );
 
-    // for each mangarow retrieve latest update
-    let latest_update = data
-        .source
-        .fetch(&webdriver_url, &data.manga_id)
-        .await
-        .unwrap_or_else(|e| panic!("Fail to fetch {source}: {e:?}"));
+    // when the known chapter is no longer listed, the latest chapter stands for it if it's stored
+    let known_position = chapters
+        .iter()
+        .position(|c| c.title == data.latest_chapter_title)
+        .or_else(|| {
+            chapters
+                .first()
+                .filter(|c| stored.contains_key(c.title.as_str()))
+                .map(|_| 0)
+        });
 
-    // generate diffing result
-    // no change -> chapter title and release status doesn't change
-    // upcoming -> release status change from released to not released and chapter title change
-    // released -> release status change from not released to released
+    let mut result = vec![];
 
-    let update_manga_row = MangaRow::from_manga(data.manga_id, data.source, latest_update);
+    match known_position {
+        Some(idx) => {
+            for (position, chapter) in chapters.iter().enumerate().rev() {
+                let row = to_row(chapter);
+                match stored.get(chapter.title.as_str()).copied() {
+                    // an upcoming chapter can come out before a newer upcoming one
+                    Some(false) if row.latest_chapter_released => {
+                        result.push(DiffingResult::Released(row))
+                    }
+                    Some(_) => (),
+                    // unstored chapters older than the known one aren't new
+                    None if position > idx => (),
+                    None if row.latest_chapter_released => {
+                        result.push(DiffingResult::Released(row))
+                    }
+                    None => result.push(DiffingResult::Upcoming(row)),
+                }
+            }
+        }
+        // nothing listed is known, fallback to compare against the latest chapter
+        None => {
+            let row = match chapters.first() {
+                Some(chapter) => to_row(chapter),
+                None => MangaRow {
+                    tags: data.tags.clone(),
+                    ..MangaRow::from_manga(
+                        data.manga_id.clone(),
+                        data.source.clone(),
+                        latest_update.clone(),
+                    )
+                },
+            };
 
-    if (data
-        .latest_chapter_title
-        .ne(&update_manga_row.latest_chapter_title)
-        || !data.latest_chapter_released)
-        && update_manga_row.latest_chapter_released
-    {
-        DiffingResult::Released(update_manga_row)
-    } else if data
-        .latest_chapter_title
-        .ne(&update_manga_row.latest_chapter_title)
-        && !update_manga_row.latest_chapter_released
-    {
-        DiffingResult::Upcoming(update_manga_row)
-    } else {
-        DiffingResult::NoChange
+            if (data.latest_chapter_title.ne(&row.latest_chapter_title)
+                || !data.latest_chapter_released)
+                && row.latest_chapter_released
+            {
+                result.push(DiffingResult::Released(row));
+            } else if data.latest_chapter_title.ne(&row.latest_chapter_title)
+                && !row.latest_chapter_released
+            {
+                result.push(DiffingResult::Upcoming(row));
+            }
+        }
+    }
+
+    if result.is_empty() {
+        result.push(DiffingResult::NoChange);
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{DateTime, Duration, FixedOffset, Utc};
+
+    use super::*;
+
+    fn chapter(title: &str, release_date: DateTime<FixedOffset>) -> Chapter {
+        Chapter::new(
+            title.into(),
+            format!("https://example.com/{title}"),
+            "".into(),
+            release_date,
+        )
+    }
+
+    fn manga(chapters: Vec<Chapter>) -> Manga {
+        Manga::from_chapters(
+            "title".into(),
+            Some("cover".into()),
+            "author".into(),
+            chapters,
+        )
+        .unwrap()
+    }
+
+    fn known_row(latest: &Manga, chapter_title: &str, released: bool) -> MangaRow {
+        let chapter = latest
+            .chapters
+            .iter()
+            .find(|c| c.title == chapter_title)
+            .cloned()
+            .unwrap_or(Chapter {
+                released,
+                ..chapter(chapter_title, Utc::now().fixed_offset())
+            });
+
+        let mut row =
+            MangaRow::from_chapter("1".into(), MangaSource::ShounenJumpPlus, latest, &chapter);
+        row.latest_chapter_released = released;
+        row
+    }
+
+    fn titles(result: &[DiffingResult]) -> Vec<String> {
+        result
+            .iter()
+            .map(|d| match d {
+                DiffingResult::NoChange => "no change".into(),
+                DiffingResult::Upcoming(row) => format!("upcoming {}", row.latest_chapter_title),
+                DiffingResult::Released(row) => format!("released {}", row.latest_chapter_title),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn summary_exceeds_thresholds() {
+        let failure = || SeriesFailure {
+            source: MangaSource::GammaPlus,
+            manga_id: "1".into(),
+            kind: Some(FetchErrorKind::Blocked),
+            error: "403".into(),
+        };
+        let thresholds = UpdateThresholds::default();
+
+        let summary = UpdateSummary {
+            checked: 4,
+            failed: vec![failure()],
+            ..Default::default()
+        };
+        assert!(!summary.exceeds(&thresholds));
+
+        let summary = UpdateSummary {
+            checked: 4,
+            failed: vec![failure(), failure(), failure()],
+            ..Default::default()
+        };
+        assert!(summary.exceeds(&thresholds));
+
+        let summary = UpdateSummary {
+            checked: 4,
+            notification_failures: 1,
+            ..Default::default()
+        };
+        assert!(summary.exceeds(&thresholds));
+    }
+
+    #[test]
+    fn suspend_after_consecutive_failures() {
+        let policy = SuspendPolicy::default();
+        let mut health = SeriesHealthRow::new(MangaSource::GammaPlus, "1".into());
+
+        for _ in 0..policy.after_failures - 1 {
+            health.failed(Some(FetchErrorKind::NotFound), "404".into());
+            policy.apply(&mut health);
+        }
+        assert!(!health.suspended);
+
+        health.failed(Some(FetchErrorKind::NotFound), "404".into());
+        policy.apply(&mut health);
+        assert!(health.suspended);
+        assert_eq!(
+            health.next_probe_at,
+            Some(health.last_attempt + policy.base_delay)
+        );
+        assert!(!policy.is_due(&health, health.last_attempt));
+        assert!(policy.is_due(&health, health.last_attempt + policy.base_delay));
+
+        health.succeeded();
+        assert!(!health.suspended);
+        assert_eq!(health.consecutive_failures, 0);
+    }
+
+    #[test]
+    fn probe_delay_backs_off_exponentially() {
+        let policy = SuspendPolicy::default();
+        let n = policy.after_failures;
+
+        assert_eq!(policy.probe_delay(n - 1), None);
+        assert_eq!(policy.probe_delay(n), Some(policy.base_delay));
+        assert_eq!(policy.probe_delay(n + 1), Some(policy.base_delay * 2));
+        assert_eq!(policy.probe_delay(n + 2), Some(policy.base_delay * 4));
+        assert_eq!(policy.probe_delay(n + 100), Some(policy.max_delay));
+    }
+
+    #[test]
+    fn diff_no_change() {
+        let past = Utc::now().fixed_offset() - Duration::days(7);
+        let latest = manga(vec![chapter("2", past), chapter("1", past)]);
+        let data = known_row(&latest, "2", true);
+
+        assert_eq!(titles(&diff_manga(&data, &[], &latest)), vec!["no change"]);
+    }
+
+    #[test]
+    fn diff_every_chapter_since_last_known() {
+        let past = Utc::now().fixed_offset() - Duration::days(7);
+        let latest = manga(vec![
+            chapter("4", past),
+            chapter("3", past),
+            chapter("2", past),
+            chapter("1", past),
+        ]);
+        let data = known_row(&latest, "2", true);
+
+        assert_eq!(
+            titles(&diff_manga(&data, &[], &latest)),
+            vec!["released 3", "released 4"]
+        );
+    }
+
+    #[test]
+    fn diff_known_chapter_become_released() {
+        let past = Utc::now().fixed_offset() - Duration::days(7);
+        let future = Utc::now().fixed_offset() + Duration::days(7);
+        let latest = manga(vec![chapter("3", future), chapter("2", past)]);
+        let data = known_row(&latest, "2", false);
+
+        assert_eq!(
+            titles(&diff_manga(&data, &[], &latest)),
+            vec!["released 2", "upcoming 3"]
+        );
+    }
+
+    #[test]
+    fn diff_older_upcoming_chapter_released_first() {
+        let past = Utc::now().fixed_offset() - Duration::days(1);
+        let future = Utc::now().fixed_offset() + Duration::days(7);
+        let stored: Vec<_> = ["4", "3"]
+            .into_iter()
+            .map(|title| {
+                ChapterRow::from_chapter(
+                    MangaSource::ShounenJumpPlus,
+                    "1".into(),
+                    &chapter(title, future),
+                )
+            })
+            .collect();
+
+        // chapter 3 came out earlier than announced, chapter 4 is still upcoming
+        let latest = manga(vec![chapter("4", future), chapter("3", past)]);
+        let data = known_row(&latest, "4", false);
+
+        assert_eq!(
+            titles(&diff_manga(&data, &stored, &latest)),
+            vec!["released 3"]
+        );
+
+        let latest = manga(vec![chapter("4", past), chapter("3", past)]);
+        let mut stored = stored;
+        stored[1].released = true;
+        assert_eq!(
+            titles(&diff_manga(&data, &stored, &latest)),
+            vec!["released 4"]
+        );
+    }
+
+    #[test]
+    fn diff_unknown_chapter_fallback_to_latest() {
+        let past = Utc::now().fixed_offset() - Duration::days(7);
+        let latest = manga(vec![chapter("5", past), chapter("4", past)]);
+        let data = known_row(&latest, "1", true);
+
+        assert_eq!(titles(&diff_manga(&data, &[], &latest)), vec!["released 5"]);
     }
 }
@@ -1,9 +1,13 @@
+#[cfg(feature = "ssr")]
+pub mod api;
 pub mod app;
 pub mod core;
 #[cfg(feature = "ssr")]
 pub mod db;
 #[cfg(feature = "ssr")]
 pub mod job;
+#[cfg(feature = "ssr")]
+pub mod notify;
 pub mod pages;
 pub mod server;
 #[cfg(feature = "ssr")]
@@ -6,7 +6,16 @@ use axum::{
 use leptos::{context::provide_context, logging::log};
 use leptos_axum::handle_server_fns_with_context;
 use manga_tracker::{
-    app::shell, job::series::update_series, state::AppState,
+    api,
+    app::shell,
+    job::{
+        reminder::{run_reminders, ReminderPolicy},
+        scheduler::{run_locked, run_scheduler, SchedulerConfig},
+        series::UpdateJob,
+    },
+    notify::{build_sinks, SinkConfig},
+    server::{auth::AuthConfig, guard::require_api_token},
+    state::AppState,
     testcontainer::selenium_container::Selenium,
 };
 use sqlx::Executor;
@@ -173,16 +182,49 @@ async fn main() {
             load_db().await.expect("Fail loading db connection")
         }
     };
+    let sinks = SinkConfig::from_env().expect("Fail loading notification sinks");
+    if let Err(e) = build_sinks(&sinks) {
+        panic!("Invalid notification sink: {e}");
+    }
+    if sinks.is_empty() {
+        log!("no notification sink configured, releases won't be broadcast");
+    }
+
     let conf = get_configuration(None).unwrap();
     let addr = conf.leptos_options.site_addr;
 
     if let Some(arg) = env::args().nth(1) {
-        let webhook_url = env::var("WEBHOOK_URL").expect("WEBHOOK_URL is not set");
         if arg == "update" {
             println!("start updating series");
-            update_series(webhook_url, selenium_webdriver_url, &db_pool).await;
-            return;
+            let job = UpdateJob::from_env(sinks, selenium_webdriver_url);
+
+            // wait for a release reminder check running on the server to finish
+            match run_locked(&job, &db_pool).await {
+                Ok(true) => return,
+                Ok(false) => std::process::exit(1),
+                Err(e) => {
+                    println!("Error with the update job lock: {e}");
+                    std::process::exit(1);
+                }
+            }
+        }
+    }
+
+    // small deployments can run the update job from the server instead of a cronjob
+    match SchedulerConfig::from_env() {
+        Ok(Some(config)) => {
+            let job = UpdateJob::from_env(sinks.clone(), selenium_webdriver_url.clone());
+            log!("update job scheduled with {}", config.expression);
+            tokio::spawn(run_scheduler(config, job, db_pool.clone()));
         }
+        Ok(None) => (),
+        Err(e) => panic!("Invalid update schedule: {e}"),
+    }
+
+    if let Some(policy) = ReminderPolicy::from_env() {
+        let job = UpdateJob::from_env(sinks.clone(), selenium_webdriver_url.clone());
+        log!("release reminders enabled");
+        tokio::spawn(run_reminders(policy, job, db_pool.clone()));
     }
 
     let leptos_options = conf.leptos_options;
@@ -193,14 +235,21 @@ async fn main() {
         leptos_options,
         pool: db_pool.clone(),
         webdriver_url: selenium_webdriver_url,
+        sinks,
+        auth: AuthConfig::from_env(),
     };
 
     let app = Router::new()
-        .route("/health", get(health))
         .route(
             "/api/{*fn_name}",
             get(server_fn_handler).post(server_fn_handler),
         )
+        .merge(api::router())
+        .route_layer(axum::middleware::from_fn_with_state(
+            app_state.clone(),
+            require_api_token,
+        ))
+        .route("/health", get(health))
         .leptos_routes_with_handler(routes, get(leptos_routes_handler))
         .fallback(leptos_axum::file_and_error_handler::<AppState, _>(shell))
         .with_state(app_state);
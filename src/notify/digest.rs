@@ -0,0 +1,203 @@
+use std::collections::BTreeMap;
+
+use chrono::{Datelike, Duration, NaiveDate, NaiveDateTime, NaiveTime};
+use serde::{Deserialize, Serialize};
+
+use super::{ReleaseEvent, ReleaseKind};
+
+#[derive(Deserialize, Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum DigestPeriod {
+    Daily,
+    /// sent on mondays
+    Weekly,
+}
+
+/// collect the releases of a sink and send them as one message per period instead of a
+/// message per chapter
+#[derive(Deserialize, Clone, Debug, PartialEq)]
+pub struct DigestConfig {
+    pub period: DigestPeriod,
+    /// local hour the digest goes out at, the next update job run after it sends it
+    #[serde(default)]
+    pub hour: u32,
+    /// also list the chapters expected within the next 7 days
+    #[serde(default)]
+    pub upcoming: bool,
+}
+
+impl DigestConfig {
+    /// start of the period `now` falls in, the digest of the previous period is due from then
+    pub fn period_start(&self, now: NaiveDateTime) -> NaiveDateTime {
+        let send_time = NaiveTime::from_hms_opt(self.hour.min(23), 0, 0).unwrap();
+
+        let (day, length) = match self.period {
+            DigestPeriod::Daily => (now.date(), Duration::days(1)),
+            DigestPeriod::Weekly => (
+                now.date() - Duration::days(now.weekday().num_days_from_monday().into()),
+                Duration::weeks(1),
+            ),
+        };
+
+        let start = day.and_time(send_time);
+        match now < start {
+            true => start - length,
+            false => start,
+        }
+    }
+}
+
+/// what a digest sink gets once per period
+#[derive(Serialize, Clone, Debug)]
+pub struct Digest {
+    pub period: DigestPeriod,
+    pub released: Vec<ReleaseEvent>,
+    pub upcoming: Vec<ReleaseEvent>,
+}
+
+impl Digest {
+    pub fn is_empty(&self) -> bool {
+        self.released.is_empty() && self.upcoming.is_empty()
+    }
+
+    pub fn title(&self) -> String {
+        let period = match self.period {
+            DigestPeriod::Daily => "Daily",
+            DigestPeriod::Weekly => "Weekly",
+        };
+
+        format!("{period} digest: {} new chapters", self.released.len())
+    }
+
+    /// released chapters grouped by source then day, followed by the upcoming ones grouped by
+    /// day. Each group is a heading and its lines
+    pub fn groups(&self) -> Vec<(String, Vec<String>)> {
+        let mut released: BTreeMap<(String, NaiveDate), Vec<String>> = BTreeMap::new();
+        for event in &self.released {
+            released
+                .entry((event.source.to_string(), event.release_date.date_naive()))
+                .or_default()
+                .push(format!("{}: {}", event.title, event.chapter_title));
+        }
+
+        let mut upcoming: BTreeMap<NaiveDate, Vec<String>> = BTreeMap::new();
+        for event in &self.upcoming {
+            upcoming
+                .entry(event.release_date.date_naive())
+                .or_default()
+                .push(format!(
+                    "{}: {} ({})",
+                    event.title, event.chapter_title, event.source
+                ));
+        }
+
+        released
+            .into_iter()
+            .map(|((source, day), lines)| (format!("{source}, {}", day.format("%d-%m-%Y")), lines))
+            .chain(
+                upcoming
+                    .into_iter()
+                    .map(|(day, lines)| (format!("Upcoming {}", day.format("%a %d-%m-%Y")), lines)),
+            )
+            .collect()
+    }
+
+    /// plain text body for the sinks without rich formatting
+    pub fn text(&self) -> String {
+        self.groups()
+            .into_iter()
+            .map(|(heading, lines)| format!("{heading}\n- {}", lines.join("\n- ")))
+            .collect::<Vec<_>>()
+            .join("\n\n")
+    }
+}
+
+/// cut `text` to `max` characters for the sinks limiting the message size
+pub fn truncate(text: &str, max: usize) -> String {
+    match text.char_indices().nth(max.saturating_sub(1)) {
+        Some((idx, _)) if text.chars().count() > max => format!("{}…", &text[..idx]),
+        _ => text.to_owned(),
+    }
+}
+
+/// upcoming events only make it to a digest sink through the upcoming listing
+pub fn is_collected(event: &ReleaseEvent) -> bool {
+    event.kind == ReleaseKind::Released
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{core::types::MangaSource, notify::test_server::sample_event};
+
+    fn at(day: u32, hour: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2026, 10, day)
+            .unwrap()
+            .and_hms_opt(hour, 0, 0)
+            .unwrap()
+    }
+
+    #[test]
+    fn daily_period_start() {
+        let config = DigestConfig {
+            period: DigestPeriod::Daily,
+            hour: 9,
+            upcoming: false,
+        };
+
+        assert_eq!(config.period_start(at(18, 10)), at(18, 9));
+        assert_eq!(config.period_start(at(18, 9)), at(18, 9));
+        assert_eq!(config.period_start(at(18, 8)), at(17, 9));
+    }
+
+    #[test]
+    fn weekly_period_start() {
+        let config = DigestConfig {
+            period: DigestPeriod::Weekly,
+            hour: 9,
+            upcoming: false,
+        };
+
+        // 2026-10-19 is a monday
+        assert_eq!(config.period_start(at(21, 12)), at(19, 9));
+        assert_eq!(config.period_start(at(19, 8)), at(12, 9));
+    }
+
+    #[test]
+    fn group_by_source_and_day() {
+        let jump = sample_event(ReleaseKind::Released);
+        let mut next_day = sample_event(ReleaseKind::Released);
+        next_day.release_date += Duration::days(1);
+        next_day.chapter_title = "Chapter 2".into();
+        let mut pixiv = sample_event(ReleaseKind::Released);
+        pixiv.source = MangaSource::ComicPixiv;
+        let upcoming = sample_event(ReleaseKind::Upcoming);
+
+        let digest = Digest {
+            period: DigestPeriod::Daily,
+            released: vec![next_day, jump, pixiv],
+            upcoming: vec![upcoming],
+        };
+
+        let headings: Vec<_> = digest.groups().into_iter().map(|(h, _)| h).collect();
+        assert_eq!(
+            headings,
+            vec![
+                "Comic Pixiv, 19-10-2026",
+                "Shounen Jump Plus, 19-10-2026",
+                "Shounen Jump Plus, 20-10-2026",
+                "Upcoming Mon 19-10-2026",
+            ]
+        );
+        assert_eq!(digest.title(), "Daily digest: 3 new chapters");
+        assert!(digest
+            .text()
+            .starts_with("Comic Pixiv, 19-10-2026\n- Kagurabachi: Chapter 1 <Fire & Steel>"));
+    }
+
+    #[test]
+    fn truncate_long_text() {
+        assert_eq!(truncate("abcdef", 4), "abc…");
+        assert_eq!(truncate("abcd", 4), "abcd");
+    }
+}
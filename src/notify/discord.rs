@@ -0,0 +1,394 @@
+use std::time::Duration;
+
+use chrono::{DateTime, FixedOffset};
+use reqwest::{header::RETRY_AFTER, Response, StatusCode, Url};
+use serde::Deserialize;
+use serenity::all::{CreateEmbed, CreateEmbedAuthor, CreateEmbedFooter, Timestamp};
+use strum::IntoEnumIterator;
+
+use super::{
+    check_response,
+    digest::{truncate, Digest},
+    http_client, BatchError, BatchFuture, Notifier, NotifyError, NotifyFuture, ReleaseEvent,
+    ReleaseKind,
+};
+use crate::core::types::MangaSource;
+
+/// discord takes at most 10 embeds per message
+const MAX_EMBEDS: usize = 10;
+
+/// limits of a single embed
+const MAX_FIELDS: usize = 25;
+const MAX_FIELD_VALUE: usize = 1024;
+
+/// a 429 asking to wait longer than this fails the delivery, the outbox retries it later
+const MAX_RETRY_AFTER: Duration = Duration::from_secs(60);
+
+const MAX_RATE_LIMIT_RETRIES: u32 = 3;
+
+const PALETTE: [u32; 10] = [
+    0xe74c3c, 0xe67e22, 0xf1c40f, 0x2ecc71, 0x1abc9c, 0x3498db, 0x9b59b6, 0xe91e63, 0x607d8b,
+    0x795548,
+];
+
+/// how much of the event the embeds show
+#[derive(Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum EmbedLayout {
+    /// cover as a large image and the details as fields
+    #[default]
+    Full,
+    /// cover as a thumbnail and the details folded in the description, for busy channels
+    Compact,
+}
+
+pub struct DiscordNotifier {
+    name: String,
+    client: reqwest::Client,
+    url: String,
+    layout: EmbedLayout,
+}
+
+impl DiscordNotifier {
+    pub fn new(name: String, url: &str, layout: EmbedLayout) -> Self {
+        Self {
+            name,
+            client: http_client(),
+            url: url.to_owned(),
+            layout,
+        }
+    }
+
+    /// post the embeds, waiting out the rate limit when discord answers 429
+    async fn post(&self, embeds: Vec<CreateEmbed>) -> Result<(), NotifyError> {
+        let body = serde_json::json!({ "embeds": embeds });
+        let mut retries = 0;
+
+        loop {
+            let response = self.client.post(&self.url).json(&body).send().await?;
+
+            if response.status() == StatusCode::TOO_MANY_REQUESTS {
+                let retry_after = retry_after(response).await;
+                if retries >= MAX_RATE_LIMIT_RETRIES || retry_after > MAX_RETRY_AFTER {
+                    return Err(NotifyError::RateLimited(retry_after));
+                }
+
+                retries += 1;
+                tokio::time::sleep(retry_after).await;
+                continue;
+            }
+
+            let response = check_response(response).await?;
+
+            // the bucket is empty, wait for it to refill instead of hitting a 429 on the next post
+            if header_value(&response, "x-ratelimit-remaining") == Some(0.0) {
+                if let Some(reset_after) = header_value(&response, "x-ratelimit-reset-after") {
+                    tokio::time::sleep(Duration::from_secs_f64(reset_after)).await;
+                }
+            }
+
+            return Ok(());
+        }
+    }
+}
+
+fn header_value(response: &Response, name: &str) -> Option<f64> {
+    response
+        .headers()
+        .get(name)?
+        .to_str()
+        .ok()?
+        .parse()
+        .ok()
+        .filter(|value: &f64| value.is_finite() && *value >= 0.0)
+}
+
+/// discord puts the delay in seconds in the retry-after header and in the body, with
+/// millisecond precision only in the latter
+async fn retry_after(response: Response) -> Duration {
+    let header = header_value(&response, RETRY_AFTER.as_str());
+    let body = response
+        .json::<serde_json::Value>()
+        .await
+        .ok()
+        .and_then(|body| body["retry_after"].as_f64())
+        .filter(|value| value.is_finite() && *value >= 0.0);
+
+    Duration::from_secs_f64(body.or(header).unwrap_or(1.0))
+}
+
+/// a source keeps the same colour across messages
+pub fn source_colour(source: &MangaSource) -> u32 {
+    let idx = MangaSource::iter()
+        .position(|s| &s == source)
+        .unwrap_or_default();
+
+    PALETTE[idx % PALETTE.len()]
+}
+
+fn source_icon(event: &ReleaseEvent) -> Option<String> {
+    let url = Url::parse(event.series_url.as_deref().unwrap_or(&event.chapter_url)).ok()?;
+
+    Some(format!(
+        "{}/favicon.ico",
+        url.origin().ascii_serialization()
+    ))
+}
+
+/// rendered by the discord client in the reader's timezone
+fn discord_time(date: &DateTime<FixedOffset>, style: char) -> String {
+    format!("<t:{}:{style}>", date.timestamp())
+}
+
+pub fn embed(event: &ReleaseEvent, layout: EmbedLayout) -> CreateEmbed {
+    let when = match event.kind {
+        ReleaseKind::Released => format!("Released {}", discord_time(&event.release_date, 'R')),
+        ReleaseKind::Upcoming => format!(
+            "Releases {} ({})",
+            discord_time(&event.release_date, 'R'),
+            discord_time(&event.release_date, 'f')
+        ),
+    };
+
+    let mut author = CreateEmbedAuthor::new(&event.title);
+    if let Some(series_url) = &event.series_url {
+        author = author.url(series_url);
+    }
+
+    let mut footer = CreateEmbedFooter::new(event.source.to_string());
+    if let Some(icon) = source_icon(event) {
+        footer = footer.icon_url(icon);
+    }
+
+    let mut embed = CreateEmbed::new()
+        .author(author)
+        .title(event.headline())
+        .colour(source_colour(&event.source))
+        .footer(footer);
+
+    if !event.chapter_url.is_empty() {
+        embed = embed.url(&event.chapter_url);
+    }
+
+    if let Ok(timestamp) = Timestamp::from_unix_timestamp(event.release_date.timestamp()) {
+        embed = embed.timestamp(timestamp);
+    }
+
+    match layout {
+        EmbedLayout::Full => embed
+            .description(when)
+            .image(&event.cover_url)
+            .field("author", &event.author, true)
+            .field("source", event.source.to_string(), true),
+        EmbedLayout::Compact => embed
+            .description(format!("by {}, {when}", event.author))
+            .thumbnail(&event.cover_url),
+    }
+}
+
+/// one field per group, the groups past the field limit are dropped
+pub fn digest_embed(digest: &Digest) -> CreateEmbed {
+    let groups = digest.groups();
+    let dropped = groups.len().saturating_sub(MAX_FIELDS);
+
+    let mut embed =
+        CreateEmbed::new()
+            .title(digest.title())
+            .fields(groups.into_iter().take(MAX_FIELDS).map(|(heading, lines)| {
+                (heading, truncate(&lines.join("\n"), MAX_FIELD_VALUE), false)
+            }));
+
+    if dropped > 0 {
+        embed = embed.footer(CreateEmbedFooter::new(format!("and {dropped} more")));
+    }
+
+    embed
+}
+
+impl Notifier for DiscordNotifier {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn send<'a>(&'a self, event: &'a ReleaseEvent) -> NotifyFuture<'a> {
+        Box::pin(self.post(vec![embed(event, self.layout)]))
+    }
+
+    fn send_digest<'a>(&'a self, digest: &'a Digest) -> NotifyFuture<'a> {
+        Box::pin(self.post(vec![digest_embed(digest)]))
+    }
+
+    fn batch_size(&self) -> usize {
+        MAX_EMBEDS
+    }
+
+    fn send_batch<'a>(&'a self, events: &'a [ReleaseEvent]) -> BatchFuture<'a> {
+        Box::pin(async move {
+            for (i, chunk) in events.chunks(MAX_EMBEDS).enumerate() {
+                self.post(
+                    chunk
+                        .iter()
+                        .map(|event| embed(event, self.layout))
+                        .collect(),
+                )
+                .await
+                .map_err(|error| BatchError {
+                    sent: i * MAX_EMBEDS,
+                    error,
+                })?;
+            }
+
+            Ok(())
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    };
+
+    use axum::{http::HeaderMap, Router};
+
+    use super::*;
+    use crate::notify::{
+        digest::DigestPeriod,
+        test_server::{sample_event, TestServer},
+    };
+
+    #[tokio::test]
+    async fn send_embed() {
+        let server = TestServer::start().await;
+        let notifier = DiscordNotifier::new("discord".into(), &server.url, EmbedLayout::Full);
+
+        notifier
+            .send(&sample_event(ReleaseKind::Released))
+            .await
+            .unwrap();
+
+        let requests = server.requests();
+        assert_eq!(requests.len(), 1);
+
+        let embed = &requests[0].json()["embeds"][0];
+        assert_eq!(embed["title"], "[RELEASED] Chapter 1 <Fire & Steel>");
+        assert_eq!(embed["url"], "https://example.com/episode/1");
+        assert_eq!(embed["image"]["url"], "https://example.com/cover.png");
+        assert_eq!(embed["author"]["name"], "Kagurabachi");
+        assert_eq!(embed["author"]["url"], "https://example.com/series/1");
+        assert_eq!(embed["footer"]["text"], "Shounen Jump Plus");
+        assert_eq!(
+            embed["footer"]["icon_url"],
+            "https://example.com/favicon.ico"
+        );
+        assert_eq!(embed["color"], source_colour(&MangaSource::ShounenJumpPlus));
+        assert!(embed["timestamp"]
+            .as_str()
+            .unwrap()
+            .starts_with("2026-10-18T15:00:00"));
+    }
+
+    #[test]
+    fn compact_upcoming_embed() {
+        let event = sample_event(ReleaseKind::Upcoming);
+        let embed = serde_json::to_value(embed(&event, EmbedLayout::Compact)).unwrap();
+
+        let timestamp = event.release_date.timestamp();
+        let description = embed["description"].as_str().unwrap();
+        assert!(description.contains(&format!("<t:{timestamp}:R>")));
+        assert!(description.starts_with("by Takeru Hokazono"));
+        assert_eq!(embed["thumbnail"]["url"], "https://example.com/cover.png");
+        assert!(embed["image"].is_null());
+        assert!(embed["fields"]
+            .as_array()
+            .is_none_or(|fields| fields.is_empty()));
+    }
+
+    #[test]
+    fn digest_field_per_group() {
+        let digest = Digest {
+            period: DigestPeriod::Weekly,
+            released: vec![sample_event(ReleaseKind::Released)],
+            upcoming: vec![sample_event(ReleaseKind::Upcoming)],
+        };
+
+        let embed = serde_json::to_value(digest_embed(&digest)).unwrap();
+
+        assert_eq!(embed["title"], "Weekly digest: 1 new chapters");
+        assert_eq!(embed["fields"].as_array().unwrap().len(), 2);
+        assert_eq!(embed["fields"][0]["name"], "Shounen Jump Plus, 19-10-2026");
+    }
+
+    #[test]
+    fn sources_get_distinct_colours() {
+        assert_ne!(
+            source_colour(&MangaSource::Yanmaga),
+            source_colour(&MangaSource::ShounenJumpPlus)
+        );
+    }
+
+    #[tokio::test]
+    async fn batch_embeds_by_ten() {
+        let server = TestServer::start().await;
+        let notifier = DiscordNotifier::new("discord".into(), &server.url, EmbedLayout::Full);
+        let events = vec![sample_event(ReleaseKind::Released); 12];
+
+        notifier.send_batch(&events).await.unwrap();
+
+        let requests = server.requests();
+        assert_eq!(requests.len(), 2);
+        assert_eq!(requests[0].json()["embeds"].as_array().unwrap().len(), 10);
+        assert_eq!(requests[1].json()["embeds"].as_array().unwrap().len(), 2);
+    }
+
+    /// answers 429 `limited` times before accepting the message
+    async fn rate_limited_server(limited: usize, retry_after: f64) -> (String, Arc<AtomicUsize>) {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counter = calls.clone();
+
+        let app = Router::new().fallback(move || {
+            let counter = counter.clone();
+            async move {
+                let mut headers = HeaderMap::new();
+                if counter.fetch_add(1, Ordering::SeqCst) < limited {
+                    headers.insert(RETRY_AFTER, "1".parse().unwrap());
+                    let body = serde_json::json!({ "retry_after": retry_after }).to_string();
+                    return (StatusCode::TOO_MANY_REQUESTS, headers, body);
+                }
+
+                (StatusCode::NO_CONTENT, headers, String::new())
+            }
+        });
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        (url, calls)
+    }
+
+    #[tokio::test]
+    async fn retry_after_rate_limit() {
+        let (url, calls) = rate_limited_server(1, 0.05).await;
+        let notifier = DiscordNotifier::new("discord".into(), &url, EmbedLayout::Full);
+
+        notifier
+            .send(&sample_event(ReleaseKind::Released))
+            .await
+            .unwrap();
+
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn give_up_on_long_rate_limit() {
+        let (url, calls) = rate_limited_server(1, 3600.0).await;
+        let notifier = DiscordNotifier::new("discord".into(), &url, EmbedLayout::Full);
+
+        let result = notifier.send(&sample_event(ReleaseKind::Released)).await;
+
+        assert!(matches!(result, Err(NotifyError::RateLimited(_))));
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+}
@@ -0,0 +1,207 @@
+use lettre::{
+    message::{header::ContentType, Mailbox},
+    transport::smtp::authentication::Credentials,
+    AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor,
+};
+
+use super::{digest::Digest, Notifier, NotifyError, NotifyFuture, ReleaseEvent, SmtpSecurity};
+
+pub struct SmtpSettings {
+    pub host: String,
+    /// defaults to the port of the security mode
+    pub port: Option<u16>,
+    pub username: Option<String>,
+    pub password: Option<String>,
+    pub security: SmtpSecurity,
+}
+
+pub struct EmailNotifier {
+    name: String,
+    transport: AsyncSmtpTransport<Tokio1Executor>,
+    from: Mailbox,
+    to: Vec<Mailbox>,
+}
+
+impl EmailNotifier {
+    pub fn new(
+        name: String,
+        settings: SmtpSettings,
+        from: &str,
+        to: &[String],
+    ) -> Result<Self, NotifyError> {
+        let mut builder = match settings.security {
+            SmtpSecurity::None => {
+                AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(&settings.host)
+            }
+            SmtpSecurity::Starttls => {
+                AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(&settings.host)
+                    .map_err(|e| NotifyError::Email(e.to_string()))?
+            }
+            SmtpSecurity::Tls => AsyncSmtpTransport::<Tokio1Executor>::relay(&settings.host)
+                .map_err(|e| NotifyError::Email(e.to_string()))?,
+        };
+
+        if let Some(port) = settings.port {
+            builder = builder.port(port);
+        }
+
+        if let (Some(username), Some(password)) = (settings.username, settings.password) {
+            builder = builder.credentials(Credentials::new(username, password));
+        }
+
+        let parse = |address: &str| {
+            address
+                .parse::<Mailbox>()
+                .map_err(|e| NotifyError::Email(format!("invalid address {address}: {e}")))
+        };
+
+        Ok(Self {
+            name,
+            transport: builder.build(),
+            from: parse(from)?,
+            to: to.iter().map(|a| parse(a)).collect::<Result<_, _>>()?,
+        })
+    }
+}
+
+impl EmailNotifier {
+    async fn mail(&self, subject: String, body: String) -> Result<(), NotifyError> {
+        let mut builder = Message::builder()
+            .from(self.from.clone())
+            .subject(subject)
+            .header(ContentType::TEXT_PLAIN);
+        for to in &self.to {
+            builder = builder.to(to.clone());
+        }
+
+        let message = builder
+            .body(body)
+            .map_err(|e| NotifyError::Email(e.to_string()))?;
+
+        self.transport
+            .send(message)
+            .await
+            .map_err(|e| NotifyError::Email(e.to_string()))?;
+
+        Ok(())
+    }
+}
+
+impl Notifier for EmailNotifier {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn send<'a>(&'a self, event: &'a ReleaseEvent) -> NotifyFuture<'a> {
+        Box::pin(self.mail(
+            format!("{} - {}", event.headline(), event.title),
+            format!("{}\n\n{}", event.description(), event.chapter_url),
+        ))
+    }
+
+    fn send_digest<'a>(&'a self, digest: &'a Digest) -> NotifyFuture<'a> {
+        Box::pin(self.mail(digest.title(), digest.text()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use tokio::{
+        io::{AsyncBufReadExt, AsyncWriteExt, BufReader},
+        net::TcpListener,
+    };
+
+    use super::*;
+    use crate::notify::{test_server::sample_event, ReleaseKind};
+
+    /// bare bones smtp server accepting one session, returns the received message
+    async fn start_smtp_server() -> (u16, Arc<Mutex<String>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let received = Arc::new(Mutex::new(String::new()));
+        let data = received.clone();
+
+        tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            let (reader, mut writer) = stream.into_split();
+            let mut lines = BufReader::new(reader).lines();
+            let mut in_data = false;
+
+            writer.write_all(b"220 localhost ESMTP\r\n").await.unwrap();
+            while let Ok(Some(line)) = lines.next_line().await {
+                if in_data {
+                    if line == "." {
+                        in_data = false;
+                        writer.write_all(b"250 OK\r\n").await.unwrap();
+                    } else {
+                        data.lock().unwrap().push_str(&format!("{line}\n"));
+                    }
+                    continue;
+                }
+
+                let reply: &[u8] = match line.split_whitespace().next().unwrap_or_default() {
+                    "EHLO" | "HELO" => b"250 localhost\r\n",
+                    "DATA" => {
+                        in_data = true;
+                        b"354 End data with <CR><LF>.<CR><LF>\r\n"
+                    }
+                    "QUIT" => {
+                        writer.write_all(b"221 Bye\r\n").await.unwrap();
+                        break;
+                    }
+                    _ => b"250 OK\r\n",
+                };
+                writer.write_all(reply).await.unwrap();
+            }
+        });
+
+        (port, received)
+    }
+
+    #[tokio::test]
+    async fn send_plain_text_mail() {
+        let (port, received) = start_smtp_server().await;
+        let notifier = EmailNotifier::new(
+            "email".into(),
+            SmtpSettings {
+                host: "127.0.0.1".into(),
+                port: Some(port),
+                username: None,
+                password: None,
+                security: SmtpSecurity::None,
+            },
+            "Manga Tracker <bot@example.com>",
+            &["reader@example.com".into()],
+        )
+        .unwrap();
+
+        notifier
+            .send(&sample_event(ReleaseKind::Released))
+            .await
+            .unwrap();
+
+        let message = received.lock().unwrap().clone();
+        assert!(message.contains("To: reader@example.com"));
+        assert!(message.contains("https://example.com/episode/1"));
+    }
+
+    #[test]
+    fn reject_invalid_address() {
+        let result = EmailNotifier::new(
+            "email".into(),
+            SmtpSettings {
+                host: "127.0.0.1".into(),
+                port: None,
+                username: None,
+                password: None,
+                security: SmtpSecurity::None,
+            },
+            "not an address",
+            &[],
+        );
+
+        assert!(matches!(result, Err(NotifyError::Email(_))));
+    }
+}
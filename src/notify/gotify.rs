@@ -0,0 +1,92 @@
+use super::{
+    check_response, digest::Digest, http_client, Notifier, NotifyError, NotifyFuture, ReleaseEvent,
+};
+
+/// push notification through a gotify server, `token` is an application token
+pub struct GotifyNotifier {
+    name: String,
+    client: reqwest::Client,
+    endpoint: String,
+    token: String,
+}
+
+impl GotifyNotifier {
+    pub fn new(name: String, url: &str, token: &str) -> Self {
+        Self {
+            name,
+            client: http_client(),
+            endpoint: format!("{}/message", url.trim_end_matches('/')),
+            token: token.to_owned(),
+        }
+    }
+
+    async fn push(&self, body: serde_json::Value) -> Result<(), NotifyError> {
+        let response = self
+            .client
+            .post(&self.endpoint)
+            .header("X-Gotify-Key", &self.token)
+            .json(&body)
+            .send()
+            .await?;
+        check_response(response).await?;
+
+        Ok(())
+    }
+}
+
+impl Notifier for GotifyNotifier {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn send<'a>(&'a self, event: &'a ReleaseEvent) -> NotifyFuture<'a> {
+        Box::pin(async move {
+            let body = serde_json::json!({
+                "title": event.headline(),
+                "message": format!("{}\n{}", event.description(), event.chapter_url),
+                "priority": 5,
+                "extras": {
+                    "client::notification": { "click": { "url": event.chapter_url } },
+                },
+            });
+
+            self.push(body).await
+        })
+    }
+
+    fn send_digest<'a>(&'a self, digest: &'a Digest) -> NotifyFuture<'a> {
+        Box::pin(self.push(serde_json::json!({
+            "title": digest.title(),
+            "message": digest.text(),
+            "priority": 5,
+        })))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::notify::{
+        test_server::{sample_event, TestServer},
+        ReleaseKind,
+    };
+
+    #[tokio::test]
+    async fn post_message() {
+        let server = TestServer::start().await;
+        let notifier = GotifyNotifier::new("gotify".into(), &server.url, "app_token");
+
+        notifier
+            .send(&sample_event(ReleaseKind::Released))
+            .await
+            .unwrap();
+
+        let request = &server.requests()[0];
+        assert_eq!(request.path, "/message");
+        assert_eq!(request.headers["x-gotify-key"], "app_token");
+        assert_eq!(
+            request.json()["extras"]["client::notification"]["click"]["url"],
+            "https://example.com/episode/1"
+        );
+    }
+}
@@ -0,0 +1,551 @@
+use std::{
+    collections::{HashMap, HashSet},
+    env, fs,
+    future::Future,
+    pin::Pin,
+    sync::LazyLock,
+    time::Duration,
+};
+
+use chrono::{DateTime, FixedOffset};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use self::{
+    digest::{is_collected, Digest, DigestConfig},
+    routing::{is_routed, RouteRule},
+};
+use crate::{
+    core::types::MangaSource,
+    db::model::{from_japan_time, MangaRow},
+};
+
+pub mod digest;
+pub mod discord;
+pub mod email;
+pub mod gotify;
+pub mod ntfy;
+pub mod outbox;
+pub mod routing;
+pub mod slack;
+pub mod telegram;
+pub mod webhook;
+
+pub type NotifyFuture<'a> = Pin<Box<dyn Future<Output = Result<(), NotifyError>> + Send + 'a>>;
+pub type BatchFuture<'a> = Pin<Box<dyn Future<Output = Result<(), BatchError>> + Send + 'a>>;
+
+#[derive(Debug, Error)]
+pub enum NotifyError {
+    #[error("request error: {0}")]
+    Request(#[from] reqwest::Error),
+
+    #[error("sink responded {status}: {body}")]
+    Status {
+        status: reqwest::StatusCode,
+        body: String,
+    },
+
+    #[error("email error: {0}")]
+    Email(String),
+
+    #[error("rate limited, retry after {0:?}")]
+    RateLimited(Duration),
+}
+
+/// a batch failing part way, the events before `sent` were delivered
+#[derive(Debug, Error)]
+#[error("{error}")]
+pub struct BatchError {
+    pub sent: usize,
+    #[source]
+    pub error: NotifyError,
+}
+
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// shared by the http sinks, the outbox claim keeps its transaction open while sending so a
+/// sink that never answers can't be waited on forever
+static HTTP_CLIENT: LazyLock<reqwest::Client> = LazyLock::new(|| {
+    reqwest::Client::builder()
+        .connect_timeout(CONNECT_TIMEOUT)
+        .timeout(REQUEST_TIMEOUT)
+        .build()
+        .expect("Error building the sinks http client")
+});
+
+fn http_client() -> reqwest::Client {
+    HTTP_CLIENT.clone()
+}
+
+/// turn non 2xx responses into an error carrying the body, sinks usually explain what's wrong
+/// in there
+async fn check_response(response: reqwest::Response) -> Result<reqwest::Response, NotifyError> {
+    let status = response.status();
+    if status.is_success() {
+        return Ok(response);
+    }
+
+    Err(NotifyError::Status {
+        status,
+        body: response.text().await.unwrap_or_default(),
+    })
+}
+
+#[derive(Deserialize, Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ReleaseKind {
+    Released,
+    Upcoming,
+}
+
+/// a new chapter found by the update job, what every sink gets to format
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq)]
+pub struct ReleaseEvent {
+    pub kind: ReleaseKind,
+    pub source: MangaSource,
+    pub manga_id: String,
+    pub title: String,
+    pub author: String,
+    pub cover_url: String,
+    pub chapter_title: String,
+    pub chapter_url: String,
+    pub release_date: DateTime<FixedOffset>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub series_url: Option<String>,
+}
+
+impl ReleaseEvent {
+    pub fn from_row(kind: ReleaseKind, row: &MangaRow) -> Self {
+        Self {
+            kind,
+            source: row.source.clone(),
+            manga_id: row.manga_id.clone(),
+            title: row.title.clone(),
+            author: row.author.clone(),
+            cover_url: row.cover_url.clone(),
+            chapter_title: row.latest_chapter_title.clone(),
+            chapter_url: row.latest_chapter_url.clone(),
+            release_date: from_japan_time(&row.latest_chapter_release_date),
+            tags: row.tags.clone(),
+            series_url: row
+                .source
+                .fetcher()
+                .and_then(|fetcher| fetcher.series_page_url(&row.manga_id)),
+        }
+    }
+
+    pub fn headline(&self) -> String {
+        match self.kind {
+            ReleaseKind::Released => format!("[RELEASED] {}", self.chapter_title),
+            ReleaseKind::Upcoming => format!("[UPCOMING] {}", self.chapter_title),
+        }
+    }
+
+    /// plain text body for the sinks without rich formatting
+    pub fn description(&self) -> String {
+        let mut description = format!("{} by {} on {}", self.title, self.author, self.source);
+
+        if self.kind == ReleaseKind::Upcoming {
+            description.push_str(&format!(
+                "\nrelease date: {}",
+                self.release_date.format("%d-%m-%Y %H:%M:%S")
+            ));
+        }
+
+        description
+    }
+}
+
+pub trait Notifier: Send + Sync {
+    /// sink name from the config, used in logs
+    fn name(&self) -> &str;
+
+    fn send<'a>(&'a self, event: &'a ReleaseEvent) -> NotifyFuture<'a>;
+
+    /// how many events `send_batch` takes at once
+    fn batch_size(&self) -> usize {
+        1
+    }
+
+    /// send the releases of a whole period as a single message
+    fn send_digest<'a>(&'a self, digest: &'a Digest) -> NotifyFuture<'a>;
+
+    /// send up to `batch_size` events, sinks without batching send them one by one
+    fn send_batch<'a>(&'a self, events: &'a [ReleaseEvent]) -> BatchFuture<'a> {
+        Box::pin(async move {
+            for (sent, event) in events.iter().enumerate() {
+                self.send(event)
+                    .await
+                    .map_err(|error| BatchError { sent, error })?;
+            }
+
+            Ok(())
+        })
+    }
+}
+
+#[derive(Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum SmtpSecurity {
+    None,
+    #[default]
+    Starttls,
+    Tls,
+}
+
+fn default_telegram_api() -> String {
+    "https://api.telegram.org".into()
+}
+
+#[derive(Deserialize, Clone, Debug, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum SinkKind {
+    Discord {
+        url: String,
+        #[serde(default)]
+        layout: discord::EmbedLayout,
+    },
+    /// POST every [`ReleaseEvent`] as json
+    Webhook {
+        url: String,
+        #[serde(default)]
+        headers: HashMap<String, String>,
+    },
+    Slack {
+        url: String,
+    },
+    Telegram {
+        bot_token: String,
+        chat_id: String,
+        #[serde(default = "default_telegram_api")]
+        api_url: String,
+    },
+    Ntfy {
+        url: String,
+        topic: String,
+        token: Option<String>,
+    },
+    Gotify {
+        url: String,
+        token: String,
+    },
+    Email {
+        host: String,
+        port: Option<u16>,
+        username: Option<String>,
+        password: Option<String>,
+        #[serde(default)]
+        security: SmtpSecurity,
+        from: String,
+        to: Vec<String>,
+    },
+}
+
+#[derive(Deserialize, Clone, Debug)]
+pub struct SinkConfig {
+    pub name: String,
+    #[serde(flatten)]
+    pub kind: SinkKind,
+    /// events this sink gets, all of them when empty
+    #[serde(default)]
+    pub routes: Vec<RouteRule>,
+    /// send a digest per period instead of a message per event
+    #[serde(default)]
+    pub digest: Option<DigestConfig>,
+}
+
+#[derive(Debug, Error)]
+pub enum SinkConfigError {
+    #[error("can't read sinks file: {0}")]
+    File(#[from] std::io::Error),
+
+    #[error("invalid sinks config: {0}")]
+    Json(#[from] serde_json::Error),
+
+    #[error("sink name {0} is used more than once")]
+    DuplicateName(String),
+}
+
+impl SinkConfig {
+    /// sinks are read as a json list from NOTIFY_SINKS or the file at NOTIFY_SINKS_FILE, a lone
+    /// WEBHOOK_URL is still understood as a discord sink
+    pub fn from_env() -> Result<Vec<Self>, SinkConfigError> {
+        if let Ok(sinks) = env::var("NOTIFY_SINKS") {
+            return Self::parse(&sinks);
+        }
+
+        if let Ok(path) = env::var("NOTIFY_SINKS_FILE") {
+            return Self::parse(&fs::read_to_string(path)?);
+        }
+
+        Ok(env::var("WEBHOOK_URL")
+            .map(|url| {
+                vec![Self {
+                    name: "discord".into(),
+                    kind: SinkKind::Discord {
+                        url,
+                        layout: Default::default(),
+                    },
+                    routes: Vec::new(),
+                    digest: None,
+                }]
+            })
+            .unwrap_or_default())
+    }
+
+    /// names identify the sinks in the notification outbox, they have to be unique
+    pub fn parse(json: &str) -> Result<Vec<Self>, SinkConfigError> {
+        let sinks: Vec<Self> = serde_json::from_str(json)?;
+
+        let mut names = HashSet::new();
+        if let Some(sink) = sinks.iter().find(|sink| !names.insert(&sink.name)) {
+            return Err(SinkConfigError::DuplicateName(sink.name.clone()));
+        }
+
+        Ok(sinks)
+    }
+
+    pub fn build(&self) -> Result<Box<dyn Notifier>, NotifyError> {
+        let name = self.name.clone();
+
+        let notifier: Box<dyn Notifier> = match &self.kind {
+            SinkKind::Discord { url, layout } => {
+                Box::new(discord::DiscordNotifier::new(name, url, *layout))
+            }
+            SinkKind::Webhook { url, headers } => {
+                Box::new(webhook::WebhookNotifier::new(name, url, headers.clone()))
+            }
+            SinkKind::Slack { url } => Box::new(slack::SlackNotifier::new(name, url)),
+            SinkKind::Telegram {
+                bot_token,
+                chat_id,
+                api_url,
+            } => Box::new(telegram::TelegramNotifier::new(
+                name, api_url, bot_token, chat_id,
+            )),
+            SinkKind::Ntfy { url, topic, token } => {
+                Box::new(ntfy::NtfyNotifier::new(name, url, topic, token.clone()))
+            }
+            SinkKind::Gotify { url, token } => {
+                Box::new(gotify::GotifyNotifier::new(name, url, token))
+            }
+            SinkKind::Email {
+                host,
+                port,
+                username,
+                password,
+                security,
+                from,
+                to,
+            } => Box::new(email::EmailNotifier::new(
+                name,
+                email::SmtpSettings {
+                    host: host.clone(),
+                    port: *port,
+                    username: username.clone(),
+                    password: password.clone(),
+                    security: security.clone(),
+                },
+                from,
+                to,
+            )?),
+        };
+
+        Ok(notifier)
+    }
+}
+
+/// a configured destination, the notifier along its routing rules
+pub struct Sink {
+    pub notifier: Box<dyn Notifier>,
+    pub routes: Vec<RouteRule>,
+    pub digest: Option<DigestConfig>,
+}
+
+impl Sink {
+    pub fn accepts(&self, event: &ReleaseEvent) -> bool {
+        let collected = self.digest.is_none() || is_collected(event);
+
+        collected && is_routed(&self.routes, event)
+    }
+}
+
+pub fn build_sinks(sinks: &[SinkConfig]) -> Result<Vec<Sink>, NotifyError> {
+    sinks
+        .iter()
+        .map(|sink| {
+            Ok(Sink {
+                notifier: sink.build()?,
+                routes: sink.routes.clone(),
+                digest: sink.digest.clone(),
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+pub(crate) mod test_server {
+    use std::sync::{Arc, Mutex};
+
+    use axum::{
+        body::Bytes,
+        http::{HeaderMap, StatusCode, Uri},
+        Router,
+    };
+    use chrono::{FixedOffset, TimeZone};
+
+    use super::{ReleaseEvent, ReleaseKind};
+    use crate::core::types::MangaSource;
+
+    #[derive(Debug, Clone)]
+    pub struct CapturedRequest {
+        pub path: String,
+        pub query: Option<String>,
+        pub headers: HeaderMap,
+        pub body: Bytes,
+    }
+
+    impl CapturedRequest {
+        pub fn json(&self) -> serde_json::Value {
+            serde_json::from_slice(&self.body).unwrap()
+        }
+    }
+
+    /// local stand-in for the sink apis, records every request and answers with `status`
+    pub struct TestServer {
+        pub url: String,
+        requests: Arc<Mutex<Vec<CapturedRequest>>>,
+    }
+
+    impl TestServer {
+        pub async fn start() -> Self {
+            Self::start_with_status(StatusCode::OK).await
+        }
+
+        pub async fn start_with_status(status: StatusCode) -> Self {
+            let requests = Arc::new(Mutex::new(Vec::new()));
+            let captured = requests.clone();
+
+            let app = Router::new().fallback(move |uri: Uri, headers: HeaderMap, body: Bytes| {
+                let captured = captured.clone();
+                async move {
+                    captured.lock().unwrap().push(CapturedRequest {
+                        path: uri.path().to_owned(),
+                        query: uri.query().map(|q| q.to_owned()),
+                        headers,
+                        body,
+                    });
+                    (status, "{}")
+                }
+            });
+
+            let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+            let url = format!("http://{}", listener.local_addr().unwrap());
+            tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+            Self { url, requests }
+        }
+
+        pub fn requests(&self) -> Vec<CapturedRequest> {
+            self.requests.lock().unwrap().clone()
+        }
+    }
+
+    pub fn sample_event(kind: ReleaseKind) -> ReleaseEvent {
+        ReleaseEvent {
+            kind,
+            source: MangaSource::ShounenJumpPlus,
+            manga_id: "10834108156641784251".into(),
+            title: "Kagurabachi".into(),
+            author: "Takeru Hokazono".into(),
+            cover_url: "https://example.com/cover.png".into(),
+            chapter_title: "Chapter 1 <Fire & Steel>".into(),
+            chapter_url: "https://example.com/episode/1".into(),
+            release_date: FixedOffset::east_opt(9 * 3600)
+                .unwrap()
+                .with_ymd_and_hms(2026, 10, 19, 0, 0, 0)
+                .unwrap(),
+            tags: Vec::new(),
+            series_url: Some("https://example.com/series/1".into()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::test_server::{sample_event, TestServer};
+    use super::*;
+
+    #[test]
+    fn parse_sinks_config() {
+        let sinks = SinkConfig::parse(
+            r#"[
+                {"name": "team", "type": "discord", "url": "https://discord.com/api/webhooks/1/a"},
+                {"name": "phone", "type": "ntfy", "url": "https://ntfy.sh", "topic": "manga"},
+                {"name": "mail", "type": "email", "host": "smtp.example.com", "from": "bot@example.com", "to": ["me@example.com"]}
+            ]"#,
+        )
+        .unwrap();
+
+        assert_eq!(sinks.len(), 3);
+        assert_eq!(sinks[1].name, "phone");
+        assert_eq!(
+            sinks[1].kind,
+            SinkKind::Ntfy {
+                url: "https://ntfy.sh".into(),
+                topic: "manga".into(),
+                token: None
+            }
+        );
+        assert!(matches!(
+            &sinks[2].kind,
+            SinkKind::Email {
+                security: SmtpSecurity::Starttls,
+                port: None,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn reject_duplicate_sink_names() {
+        let result = SinkConfig::parse(
+            r#"[
+                {"name": "team", "type": "discord", "url": "https://discord.com/api/webhooks/1/a"},
+                {"name": "team", "type": "slack", "url": "https://hooks.slack.com/services/1"}
+            ]"#,
+        );
+
+        assert!(matches!(result, Err(SinkConfigError::DuplicateName(name)) if name == "team"));
+    }
+
+    #[tokio::test]
+    async fn default_batch_sends_one_by_one() {
+        let server = TestServer::start().await;
+        let notifier = SinkConfig {
+            name: "webhook".into(),
+            kind: SinkKind::Webhook {
+                url: server.url.clone(),
+                headers: HashMap::new(),
+            },
+            routes: Vec::new(),
+            digest: None,
+        }
+        .build()
+        .unwrap();
+
+        notifier
+            .send_batch(&[
+                sample_event(ReleaseKind::Released),
+                sample_event(ReleaseKind::Upcoming),
+            ])
+            .await
+            .unwrap();
+
+        assert_eq!(notifier.batch_size(), 1);
+        assert_eq!(server.requests().len(), 2);
+    }
+}
@@ -0,0 +1,102 @@
+use super::{
+    check_response, digest::Digest, http_client, Notifier, NotifyError, NotifyFuture, ReleaseEvent,
+    ReleaseKind,
+};
+
+/// push notification through an ntfy server, published as json so titles aren't limited to
+/// ascii headers
+pub struct NtfyNotifier {
+    name: String,
+    client: reqwest::Client,
+    url: String,
+    topic: String,
+    token: Option<String>,
+}
+
+impl NtfyNotifier {
+    pub fn new(name: String, url: &str, topic: &str, token: Option<String>) -> Self {
+        Self {
+            name,
+            client: http_client(),
+            url: url.trim_end_matches('/').to_owned(),
+            topic: topic.to_owned(),
+            token,
+        }
+    }
+
+    async fn publish(&self, body: serde_json::Value) -> Result<(), NotifyError> {
+        let mut request = self.client.post(&self.url).json(&body);
+        if let Some(token) = &self.token {
+            request = request.bearer_auth(token);
+        }
+
+        check_response(request.send().await?).await?;
+
+        Ok(())
+    }
+}
+
+impl Notifier for NtfyNotifier {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn send<'a>(&'a self, event: &'a ReleaseEvent) -> NotifyFuture<'a> {
+        Box::pin(async move {
+            let tag = match event.kind {
+                ReleaseKind::Released => "books",
+                ReleaseKind::Upcoming => "hourglass",
+            };
+            let body = serde_json::json!({
+                "topic": self.topic,
+                "title": event.headline(),
+                "message": event.description(),
+                "click": event.chapter_url,
+                "attach": event.cover_url,
+                "tags": [tag],
+            });
+
+            self.publish(body).await
+        })
+    }
+
+    fn send_digest<'a>(&'a self, digest: &'a Digest) -> NotifyFuture<'a> {
+        Box::pin(self.publish(serde_json::json!({
+            "topic": self.topic,
+            "title": digest.title(),
+            "message": digest.text(),
+            "tags": ["newspaper"],
+        })))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::notify::test_server::{sample_event, TestServer};
+
+    #[tokio::test]
+    async fn publish_json_message() {
+        let server = TestServer::start().await;
+        let notifier = NtfyNotifier::new(
+            "ntfy".into(),
+            &format!("{}/", server.url),
+            "manga",
+            Some("tk_secret".into()),
+        );
+
+        notifier
+            .send(&sample_event(ReleaseKind::Upcoming))
+            .await
+            .unwrap();
+
+        let request = &server.requests()[0];
+        assert_eq!(request.path, "/");
+        assert_eq!(request.headers["authorization"], "Bearer tk_secret");
+
+        let body = request.json();
+        assert_eq!(body["topic"], "manga");
+        assert_eq!(body["title"], "[UPCOMING] Chapter 1 <Fire & Steel>");
+        assert_eq!(body["click"], "https://example.com/episode/1");
+    }
+}
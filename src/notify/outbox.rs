@@ -0,0 +1,399 @@
+use chrono::{Duration, Local};
+use sqlx::PgPool;
+
+use super::{
+    digest::{Digest, DigestConfig},
+    routing::is_routed,
+    ReleaseEvent, ReleaseKind, Sink,
+};
+use crate::db::{
+    inquiry::{claim_pending_outbox, get_pending_outbox, get_upcoming_series},
+    insert::claim_scheduled_tick,
+    model::japan_now,
+    update::{drop_unconfigured_outbox, mark_outbox_delivered, mark_outbox_failed},
+};
+
+/// pair every event with the name of each sink it is routed to, the outbox entries written
+/// along the series update
+pub fn routed_events<'a>(
+    sinks: &'a [Sink],
+    events: &'a [ReleaseEvent],
+) -> Vec<(&'a str, &'a ReleaseEvent)> {
+    sinks
+        .iter()
+        .flat_map(|sink| {
+            events
+                .iter()
+                .filter(move |event| sink.accepts(event))
+                .map(move |event| (sink.notifier.name(), event))
+        })
+        .collect()
+}
+
+/// deliver the pending events of every sink, digest sinks only once per period. Events left
+/// for sinks that aren't configured anymore are dropped. Returns the number of events which
+/// couldn't be delivered
+pub async fn dispatch(sinks: &[Sink], pool: &PgPool) -> Result<usize, sqlx::Error> {
+    let names: Vec<&str> = sinks.iter().map(|sink| sink.notifier.name()).collect();
+    let dropped = drop_unconfigured_outbox(&names, pool).await?;
+    if dropped > 0 {
+        println!("dropped {dropped} pending notifications of sinks not configured anymore");
+    }
+
+    let mut undelivered = 0;
+
+    for sink in sinks {
+        undelivered += match &sink.digest {
+            Some(config) => dispatch_digest(sink, config, pool).await?,
+            None => dispatch_events(sink, pool).await?,
+        };
+    }
+
+    Ok(undelivered)
+}
+
+/// deliver the pending events of the sink in batches, oldest first. Each batch is claimed in
+/// its own transaction and marked delivered before it commits, a dispatcher running at the same
+/// time skips it. The sink stops at its first failed batch so the order is kept, the events sent
+/// before the failure are marked delivered and the rest gets retried on the next dispatch
+async fn dispatch_events(sink: &Sink, pool: &PgPool) -> Result<usize, sqlx::Error> {
+    let name = sink.notifier.name();
+    let batch_size = sink.notifier.batch_size().max(1) as i64;
+
+    loop {
+        let mut trx = pool.begin().await?;
+
+        let batch = claim_pending_outbox(name, batch_size, &mut *trx).await?;
+        if batch.is_empty() {
+            return Ok(0);
+        }
+
+        let ids: Vec<i64> = batch.iter().map(|row| row.id).collect();
+        let events: Vec<ReleaseEvent> = batch.into_iter().map(|row| row.event.0).collect();
+
+        match sink.notifier.send_batch(&events).await {
+            Ok(_) => {
+                mark_outbox_delivered(&ids, &mut *trx).await?;
+                trx.commit().await?;
+            }
+            Err(e) => {
+                println!("Error notifying {name}: {e}");
+                let (sent, failed) = ids.split_at(e.sent.min(ids.len()));
+                mark_outbox_delivered(sent, &mut *trx).await?;
+                mark_outbox_failed(failed, &e.to_string(), &mut *trx).await?;
+                trx.commit().await?;
+
+                return Ok(get_pending_outbox(name, pool).await?.len());
+            }
+        }
+    }
+}
+
+/// send the pending releases of a digest sink once per period, along the chapters expected in
+/// the next 7 days when enabled. The period is claimed in the same transaction as the events, a
+/// failed delivery rolls the claim back so the next dispatch tries again
+async fn dispatch_digest(
+    sink: &Sink,
+    config: &DigestConfig,
+    pool: &PgPool,
+) -> Result<usize, sqlx::Error> {
+    let name = sink.notifier.name();
+    let now = Local::now().naive_local();
+    let mut trx = pool.begin().await?;
+
+    let period_start = config.period_start(now);
+    let release_now = japan_now();
+    if !claim_scheduled_tick(&format!("digest:{name}"), period_start, &mut *trx).await? {
+        return Ok(0);
+    }
+
+    let pending = claim_pending_outbox(name, i64::MAX, &mut *trx).await?;
+    let ids: Vec<i64> = pending.iter().map(|row| row.id).collect();
+
+    let upcoming = match config.upcoming {
+        true => get_upcoming_series(release_now, release_now + Duration::days(7), pool)
+            .await?
+            .iter()
+            .map(|row| ReleaseEvent::from_row(ReleaseKind::Upcoming, row))
+            .filter(|event| is_routed(&sink.routes, event))
+            .collect(),
+        false => Vec::new(),
+    };
+
+    let digest = Digest {
+        period: config.period,
+        released: pending.into_iter().map(|row| row.event.0).collect(),
+        upcoming,
+    };
+
+    if digest.is_empty() {
+        trx.commit().await?;
+        return Ok(0);
+    }
+
+    match sink.notifier.send_digest(&digest).await {
+        Ok(_) => {
+            mark_outbox_delivered(&ids, &mut *trx).await?;
+            trx.commit().await?;
+
+            Ok(0)
+        }
+        Err(e) => {
+            println!("Error sending digest to {name}: {e}");
+            trx.rollback().await?;
+            mark_outbox_failed(&ids, &e.to_string(), &mut *pool.acquire().await?).await?;
+
+            Ok(ids.len().max(1))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        collections::HashMap,
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc,
+        },
+    };
+
+    use axum::http::StatusCode;
+
+    use super::*;
+    use crate::{
+        db::insert::insert_outbox,
+        notify::{
+            build_sinks,
+            digest::DigestPeriod,
+            test_server::{sample_event, TestServer},
+            Notifier, NotifyError, NotifyFuture, ReleaseKind, SinkConfig, SinkKind,
+        },
+        testcontainer::postgres_container::get_test_db,
+    };
+
+    async fn enqueue(sinks: &[Sink], events: &[ReleaseEvent], pool: &PgPool) {
+        let mut conn = pool.acquire().await.unwrap();
+        insert_outbox(routed_events(sinks, events), false, &mut conn)
+            .await
+            .unwrap();
+    }
+
+    fn webhook_sink(name: &str, url: &str) -> SinkConfig {
+        SinkConfig {
+            name: name.into(),
+            kind: SinkKind::Webhook {
+                url: url.to_owned(),
+                headers: HashMap::new(),
+            },
+            routes: Vec::new(),
+            digest: None,
+        }
+    }
+
+    async fn pending_count(sink: &str, pool: &PgPool) -> usize {
+        get_pending_outbox(sink, pool).await.unwrap().len()
+    }
+
+    #[tokio::test]
+    async fn failed_deliveries_stay_pending() {
+        let db = get_test_db("outbox_failed_deliveries").await.unwrap();
+        let ok_server = TestServer::start().await;
+        let failing_server = TestServer::start_with_status(StatusCode::BAD_GATEWAY).await;
+        let sinks = build_sinks(&[
+            webhook_sink("ok", &ok_server.url),
+            webhook_sink("failing", &failing_server.url),
+        ])
+        .unwrap();
+        let events = vec![
+            sample_event(ReleaseKind::Released),
+            sample_event(ReleaseKind::Upcoming),
+        ];
+
+        enqueue(&sinks, &events, &db.0).await;
+        let undelivered = dispatch(&sinks, &db.0).await.unwrap();
+
+        assert_eq!(undelivered, 2);
+        assert_eq!(ok_server.requests().len(), 2);
+        // the failing sink stops at its first error
+        assert_eq!(failing_server.requests().len(), 1);
+        assert_eq!(pending_count("ok", &db.0).await, 0);
+
+        let pending = get_pending_outbox("failing", &db.0).await.unwrap();
+        assert_eq!(pending.len(), 2);
+        assert_eq!(pending[0].attempts, 1);
+        assert!(pending[0].last_error.is_some());
+        assert_eq!(pending[1].attempts, 0);
+
+        // delivered events aren't sent again
+        dispatch(&sinks, &db.0).await.unwrap();
+        assert_eq!(ok_server.requests().len(), 2);
+    }
+
+    /// takes a few events per batch but sends them one by one, only the first send succeeds
+    struct FlakyNotifier {
+        sends: Arc<AtomicUsize>,
+    }
+
+    impl Notifier for FlakyNotifier {
+        fn name(&self) -> &str {
+            "flaky"
+        }
+
+        fn send<'a>(&'a self, _: &'a ReleaseEvent) -> NotifyFuture<'a> {
+            Box::pin(async move {
+                match self.sends.fetch_add(1, Ordering::SeqCst) {
+                    0 => Ok(()),
+                    _ => Err(NotifyError::Email("refused".into())),
+                }
+            })
+        }
+
+        fn batch_size(&self) -> usize {
+            3
+        }
+
+        fn send_digest<'a>(&'a self, _: &'a Digest) -> NotifyFuture<'a> {
+            Box::pin(async { Ok(()) })
+        }
+    }
+
+    #[tokio::test]
+    async fn partly_sent_batches_keep_delivered_events() {
+        let db = get_test_db("outbox_partly_sent").await.unwrap();
+        let sends = Arc::new(AtomicUsize::new(0));
+        let sinks = vec![Sink {
+            notifier: Box::new(FlakyNotifier {
+                sends: sends.clone(),
+            }),
+            routes: Vec::new(),
+            digest: None,
+        }];
+        let events = vec![
+            sample_event(ReleaseKind::Released),
+            sample_event(ReleaseKind::Released),
+            sample_event(ReleaseKind::Upcoming),
+        ];
+
+        enqueue(&sinks, &events, &db.0).await;
+        let undelivered = dispatch(&sinks, &db.0).await.unwrap();
+
+        // the first event went out before the second one failed
+        assert_eq!(undelivered, 2);
+        assert_eq!(sends.load(Ordering::SeqCst), 2);
+        let pending = get_pending_outbox("flaky", &db.0).await.unwrap();
+        assert_eq!(pending.len(), 2);
+        assert_eq!(pending[0].attempts, 1);
+
+        // the retry starts from the failed event, the delivered one isn't sent again
+        dispatch(&sinks, &db.0).await.unwrap();
+        assert_eq!(sends.load(Ordering::SeqCst), 3);
+        assert_eq!(pending_count("flaky", &db.0).await, 2);
+    }
+
+    #[tokio::test]
+    async fn claimed_events_are_skipped() {
+        let db = get_test_db("outbox_claimed_events").await.unwrap();
+        let server = TestServer::start().await;
+        let sinks = build_sinks(&[webhook_sink("ok", &server.url)]).unwrap();
+
+        enqueue(&sinks, &[sample_event(ReleaseKind::Released)], &db.0).await;
+
+        // another dispatcher holds the event while delivering it
+        let mut trx = db.0.begin().await.unwrap();
+        let claimed = claim_pending_outbox("ok", 10, &mut *trx).await.unwrap();
+        assert_eq!(claimed.len(), 1);
+
+        dispatch(&sinks, &db.0).await.unwrap();
+        assert!(server.requests().is_empty());
+
+        mark_outbox_delivered(&[claimed[0].id], &mut *trx)
+            .await
+            .unwrap();
+        trx.commit().await.unwrap();
+
+        dispatch(&sinks, &db.0).await.unwrap();
+        assert!(server.requests().is_empty());
+        assert_eq!(pending_count("ok", &db.0).await, 0);
+    }
+
+    #[tokio::test]
+    async fn digest_sent_once_per_period() {
+        let db = get_test_db("outbox_digest").await.unwrap();
+        let server = TestServer::start().await;
+        let sinks = build_sinks(&[SinkConfig {
+            digest: Some(DigestConfig {
+                period: DigestPeriod::Daily,
+                hour: 0,
+                upcoming: false,
+            }),
+            ..webhook_sink("digest", &server.url)
+        }])
+        .unwrap();
+        let events = vec![
+            sample_event(ReleaseKind::Released),
+            sample_event(ReleaseKind::Released),
+            sample_event(ReleaseKind::Upcoming),
+        ];
+
+        enqueue(&sinks, &events, &db.0).await;
+        let undelivered = dispatch(&sinks, &db.0).await.unwrap();
+
+        assert_eq!(undelivered, 0);
+        let requests = server.requests();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].json()["released"].as_array().unwrap().len(), 2);
+        assert_eq!(pending_count("digest", &db.0).await, 0);
+
+        // the next releases wait for the next period
+        enqueue(&sinks, &events, &db.0).await;
+        dispatch(&sinks, &db.0).await.unwrap();
+        assert_eq!(server.requests().len(), 1);
+        assert_eq!(pending_count("digest", &db.0).await, 2);
+    }
+
+    #[tokio::test]
+    async fn removed_sinks_are_dropped() {
+        let db = get_test_db("outbox_removed_sinks").await.unwrap();
+        let server = TestServer::start().await;
+        let removed = build_sinks(&[webhook_sink("removed", &server.url)]).unwrap();
+        let sinks = build_sinks(&[webhook_sink("ok", &server.url)]).unwrap();
+
+        enqueue(&removed, &[sample_event(ReleaseKind::Released)], &db.0).await;
+        let undelivered = dispatch(&sinks, &db.0).await.unwrap();
+
+        assert_eq!(undelivered, 0);
+        assert!(server.requests().is_empty());
+        assert_eq!(pending_count("removed", &db.0).await, 0);
+    }
+
+    #[tokio::test]
+    async fn enqueue_follows_routes() {
+        let db = get_test_db("outbox_follows_routes").await.unwrap();
+        let pixiv_server = TestServer::start().await;
+        let jump_server = TestServer::start().await;
+        let sinks: Vec<SinkConfig> = serde_json::from_value(serde_json::json!([
+            {
+                "name": "pixiv",
+                "type": "webhook",
+                "url": pixiv_server.url,
+                "routes": [{"sources": ["ComicPixiv"]}],
+            },
+            {
+                "name": "jump",
+                "type": "webhook",
+                "url": jump_server.url,
+                "routes": [{"sources": ["ShounenJumpPlus"]}],
+            },
+        ]))
+        .unwrap();
+        let sinks = build_sinks(&sinks).unwrap();
+
+        enqueue(&sinks, &[sample_event(ReleaseKind::Released)], &db.0).await;
+        let undelivered = dispatch(&sinks, &db.0).await.unwrap();
+
+        assert_eq!(undelivered, 0);
+        assert!(pixiv_server.requests().is_empty());
+        assert_eq!(jump_server.requests().len(), 1);
+    }
+}
@@ -0,0 +1,120 @@
+use regex::Regex;
+use serde::{Deserialize, Deserializer};
+
+use super::ReleaseEvent;
+use crate::core::types::MangaSource;
+
+/// decides whether a sink gets an event, every criteria given has to match. An empty list of
+/// sources, authors or tags matches anything
+#[derive(Deserialize, Clone, Debug, Default)]
+pub struct RouteRule {
+    #[serde(default)]
+    pub sources: Vec<MangaSource>,
+    /// compared case insensitively against the whole author name
+    #[serde(default)]
+    pub authors: Vec<String>,
+    #[serde(default, deserialize_with = "deserialize_pattern")]
+    pub title_pattern: Option<Regex>,
+    /// the series must have at least one of these tags
+    #[serde(default)]
+    pub tags: Vec<String>,
+}
+
+fn deserialize_pattern<'de, D>(deserializer: D) -> Result<Option<Regex>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    Option::<String>::deserialize(deserializer)?
+        .map(|pattern| Regex::new(&pattern).map_err(serde::de::Error::custom))
+        .transpose()
+}
+
+impl RouteRule {
+    pub fn matches(&self, event: &ReleaseEvent) -> bool {
+        let source_match = self.sources.is_empty() || self.sources.contains(&event.source);
+
+        let author_match = self.authors.is_empty()
+            || self
+                .authors
+                .iter()
+                .any(|author| author.to_lowercase() == event.author.to_lowercase());
+
+        let title_match = self
+            .title_pattern
+            .as_ref()
+            .is_none_or(|pattern| pattern.is_match(&event.title));
+
+        let tag_match =
+            self.tags.is_empty() || self.tags.iter().any(|tag| event.tags.contains(tag));
+
+        source_match && author_match && title_match && tag_match
+    }
+}
+
+/// a sink without rules gets everything, otherwise any matching rule lets the event through
+pub fn is_routed(rules: &[RouteRule], event: &ReleaseEvent) -> bool {
+    rules.is_empty() || rules.iter().any(|rule| rule.matches(event))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::notify::{test_server::sample_event, ReleaseKind};
+
+    fn rules(json: &str) -> Vec<RouteRule> {
+        serde_json::from_str(json).unwrap()
+    }
+
+    #[test]
+    fn no_rule_routes_everything() {
+        assert!(is_routed(&[], &sample_event(ReleaseKind::Released)));
+    }
+
+    #[test]
+    fn route_by_source() {
+        let event = sample_event(ReleaseKind::Released);
+
+        assert!(is_routed(
+            &rules(r#"[{"sources": ["ComicPixiv", "ShounenJumpPlus"]}]"#),
+            &event
+        ));
+        assert!(!is_routed(
+            &rules(r#"[{"sources": ["ComicPixiv"]}]"#),
+            &event
+        ));
+    }
+
+    #[test]
+    fn criteria_of_a_rule_are_combined() {
+        let event = sample_event(ReleaseKind::Released);
+
+        assert!(is_routed(
+            &rules(r#"[{"sources": ["ShounenJumpPlus"], "authors": ["takeru hokazono"]}]"#),
+            &event
+        ));
+        assert!(!is_routed(
+            &rules(r#"[{"sources": ["ShounenJumpPlus"], "title_pattern": "^One"}]"#),
+            &event
+        ));
+        assert!(is_routed(
+            &rules(r#"[{"title_pattern": "^One"}, {"title_pattern": "(?i)bachi$"}]"#),
+            &event
+        ));
+    }
+
+    #[test]
+    fn route_by_tag() {
+        let mut event = sample_event(ReleaseKind::Released);
+        let rules = rules(r#"[{"tags": ["action", "favorite"]}]"#);
+
+        assert!(!is_routed(&rules, &event));
+
+        event.tags = vec!["favorite".into()];
+        assert!(is_routed(&rules, &event));
+    }
+
+    #[test]
+    fn reject_invalid_pattern() {
+        assert!(serde_json::from_str::<Vec<RouteRule>>(r#"[{"title_pattern": "("}]"#).is_err());
+    }
+}
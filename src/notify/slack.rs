@@ -0,0 +1,107 @@
+use super::{
+    check_response, digest::Digest, http_client, Notifier, NotifyError, NotifyFuture, ReleaseEvent,
+};
+
+/// slack incoming webhook
+pub struct SlackNotifier {
+    name: String,
+    client: reqwest::Client,
+    url: String,
+}
+
+impl SlackNotifier {
+    pub fn new(name: String, url: &str) -> Self {
+        Self {
+            name,
+            client: http_client(),
+            url: url.to_owned(),
+        }
+    }
+}
+
+/// slack mrkdwn only needs these three escaped
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+pub fn payload(event: &ReleaseEvent) -> serde_json::Value {
+    let text = format!(
+        "*<{}|{}>*\n{}",
+        event.chapter_url,
+        escape(&event.headline()),
+        escape(&event.description())
+    );
+
+    serde_json::json!({
+        "text": event.headline(),
+        "blocks": [{
+            "type": "section",
+            "text": { "type": "mrkdwn", "text": text },
+            "accessory": {
+                "type": "image",
+                "image_url": event.cover_url,
+                "alt_text": event.title,
+            },
+        }],
+    })
+}
+
+pub fn digest_payload(digest: &Digest) -> serde_json::Value {
+    serde_json::json!({
+        "text": format!("*{}*\n{}", escape(&digest.title()), escape(&digest.text())),
+    })
+}
+
+impl SlackNotifier {
+    async fn post(&self, body: &serde_json::Value) -> Result<(), NotifyError> {
+        let response = self.client.post(&self.url).json(body).send().await?;
+        check_response(response).await?;
+
+        Ok(())
+    }
+}
+
+impl Notifier for SlackNotifier {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn send<'a>(&'a self, event: &'a ReleaseEvent) -> NotifyFuture<'a> {
+        Box::pin(async move { self.post(&payload(event)).await })
+    }
+
+    fn send_digest<'a>(&'a self, digest: &'a Digest) -> NotifyFuture<'a> {
+        Box::pin(async move { self.post(&digest_payload(digest)).await })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::notify::{
+        test_server::{sample_event, TestServer},
+        ReleaseKind,
+    };
+
+    #[tokio::test]
+    async fn send_block_message() {
+        let server = TestServer::start().await;
+        let notifier = SlackNotifier::new("slack".into(), &server.url);
+
+        notifier
+            .send(&sample_event(ReleaseKind::Released))
+            .await
+            .unwrap();
+
+        let body = server.requests()[0].json();
+        assert_eq!(body["text"], "[RELEASED] Chapter 1 <Fire & Steel>");
+        assert!(body["blocks"][0]["text"]["text"]
+            .as_str()
+            .unwrap()
+            .starts_with(
+                "*<https://example.com/episode/1|[RELEASED] Chapter 1 &lt;Fire &amp; Steel&gt;>*"
+            ));
+    }
+}
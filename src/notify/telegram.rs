@@ -0,0 +1,112 @@
+use super::{
+    check_response,
+    digest::{truncate, Digest},
+    http_client, Notifier, NotifyError, NotifyFuture, ReleaseEvent,
+};
+
+/// telegram rejects longer messages, with some room left for the markup
+const MAX_TEXT: usize = 4000;
+
+/// message sent by a bot through the telegram bot api
+pub struct TelegramNotifier {
+    name: String,
+    client: reqwest::Client,
+    endpoint: String,
+    chat_id: String,
+}
+
+impl TelegramNotifier {
+    pub fn new(name: String, api_url: &str, bot_token: &str, chat_id: &str) -> Self {
+        Self {
+            name,
+            client: http_client(),
+            endpoint: format!(
+                "{}/bot{bot_token}/sendMessage",
+                api_url.trim_end_matches('/')
+            ),
+            chat_id: chat_id.to_owned(),
+        }
+    }
+
+    async fn send_html(&self, text: String) -> Result<(), NotifyError> {
+        let body = serde_json::json!({
+            "chat_id": self.chat_id,
+            "text": text,
+            "parse_mode": "HTML",
+        });
+
+        let response = self.client.post(&self.endpoint).json(&body).send().await?;
+        check_response(response).await?;
+
+        Ok(())
+    }
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+impl Notifier for TelegramNotifier {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn send<'a>(&'a self, event: &'a ReleaseEvent) -> NotifyFuture<'a> {
+        Box::pin(async move {
+            let text = format!(
+                "<b><a href=\"{}\">{}</a></b>\n{}",
+                escape_html(&event.chapter_url),
+                escape_html(&event.headline()),
+                escape_html(&event.description())
+            );
+
+            self.send_html(text).await
+        })
+    }
+
+    fn send_digest<'a>(&'a self, digest: &'a Digest) -> NotifyFuture<'a> {
+        Box::pin(async move {
+            // truncated before escaping so no entity gets cut in half
+            let text = format!(
+                "<b>{}</b>\n{}",
+                escape_html(&digest.title()),
+                escape_html(&truncate(&digest.text(), MAX_TEXT))
+            );
+
+            self.send_html(text).await
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::notify::{
+        test_server::{sample_event, TestServer},
+        ReleaseKind,
+    };
+
+    #[tokio::test]
+    async fn send_html_message() {
+        let server = TestServer::start().await;
+        let notifier = TelegramNotifier::new("telegram".into(), &server.url, "123:abc", "-100");
+
+        notifier
+            .send(&sample_event(ReleaseKind::Released))
+            .await
+            .unwrap();
+
+        let request = &server.requests()[0];
+        assert_eq!(request.path, "/bot123:abc/sendMessage");
+
+        let body = request.json();
+        assert_eq!(body["chat_id"], "-100");
+        assert_eq!(body["parse_mode"], "HTML");
+        assert!(body["text"]
+            .as_str()
+            .unwrap()
+            .contains("[RELEASED] Chapter 1 &lt;Fire &amp; Steel&gt;"));
+    }
+}
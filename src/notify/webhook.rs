@@ -0,0 +1,81 @@
+use std::collections::HashMap;
+
+use serde::Serialize;
+
+use super::{
+    check_response, digest::Digest, http_client, Notifier, NotifyError, NotifyFuture, ReleaseEvent,
+};
+
+/// POST the event as json, for home made integrations
+pub struct WebhookNotifier {
+    name: String,
+    client: reqwest::Client,
+    url: String,
+    headers: HashMap<String, String>,
+}
+
+impl WebhookNotifier {
+    pub fn new(name: String, url: &str, headers: HashMap<String, String>) -> Self {
+        Self {
+            name,
+            client: http_client(),
+            url: url.to_owned(),
+            headers,
+        }
+    }
+
+    async fn post<T: Serialize + ?Sized>(&self, body: &T) -> Result<(), NotifyError> {
+        let mut request = self.client.post(&self.url).json(body);
+        for (key, value) in &self.headers {
+            request = request.header(key, value);
+        }
+
+        check_response(request.send().await?).await?;
+
+        Ok(())
+    }
+}
+
+impl Notifier for WebhookNotifier {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn send<'a>(&'a self, event: &'a ReleaseEvent) -> NotifyFuture<'a> {
+        Box::pin(self.post(event))
+    }
+
+    /// digests are told apart from events by their `released` list
+    fn send_digest<'a>(&'a self, digest: &'a Digest) -> NotifyFuture<'a> {
+        Box::pin(self.post(digest))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::notify::{
+        test_server::{sample_event, TestServer},
+        ReleaseKind,
+    };
+
+    #[tokio::test]
+    async fn send_event_as_json() {
+        let server = TestServer::start().await;
+        let notifier = WebhookNotifier::new(
+            "webhook".into(),
+            &format!("{}/hooks/manga", server.url),
+            HashMap::from([("Authorization".into(), "Bearer secret".into())]),
+        );
+        let event = sample_event(ReleaseKind::Upcoming);
+
+        notifier.send(&event).await.unwrap();
+
+        let requests = server.requests();
+        assert_eq!(requests[0].path, "/hooks/manga");
+        assert_eq!(requests[0].headers["authorization"], "Bearer secret");
+
+        let sent: ReleaseEvent = serde_json::from_slice(&requests[0].body).unwrap();
+        assert_eq!(sent, event);
+    }
+}
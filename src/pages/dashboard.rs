@@ -1,29 +1,79 @@
 use std::{collections::HashSet, str::FromStr};
 
-use crate::core::types::{MangaQuery, MangaSource};
+use crate::core::types::{
+    FetchErrorKind, Manga, MangaQuery, MangaSource, ReadState, SeriesHealth, TrackedManga,
+};
 use icondata::AiCaretDownOutlined;
 use leptos::{prelude::*, task::spawn_local};
 use leptos_meta::Title;
 use leptos_use::signal_debounced;
 use strum::IntoEnumIterator;
 use thaw::{
-    Button, ButtonAppearance, Combobox, ComboboxOption, Dialog, DialogActions, DialogBody,
-    DialogContent, DialogSurface, DialogTitle, Field, Flex, FlexAlign, FlexGap, FlexJustify, Icon,
-    Input, Menu, MenuItem, MenuPosition, MenuTrigger, Pagination, Spinner, SpinnerSize, Table,
-    TableBody, TableCell, TableCellLayout, TableHeader, TableHeaderCell, TableRow, Toast,
-    ToastBody, ToastIntent, ToastOptions, ToastTitle, ToasterInjection,
+    Badge, BadgeAppearance, BadgeColor, Button, ButtonAppearance, ButtonSize, Checkbox, Combobox,
+    ComboboxOption, Dialog, DialogActions, DialogBody, DialogContent, DialogSurface, DialogTitle,
+    Field, Flex, FlexAlign, FlexGap, FlexJustify, Icon, Input, Menu, MenuItem, MenuPosition,
+    MenuTrigger, Pagination, Spinner, SpinnerSize, Table, TableBody, TableCell, TableCellLayout,
+    TableHeader, TableHeaderCell, TableRow, Toast, ToastBody, ToastIntent, ToastOptions,
+    ToastTitle, ToasterInjection,
 };
 
 #[component]
 pub fn Dashboard() -> impl IntoView {
+    use crate::server::resume_manga;
+
     let show_add_dialog = RwSignal::new(false);
     let show_delete_dialog = RwSignal::new(false);
+    let show_refresh_dialog = RwSignal::new(false);
+    let show_tag_dialog = RwSignal::new(false);
     let page: RwSignal<usize> = RwSignal::new(1);
     let page_count: RwSignal<usize> = RwSignal::new(1);
     let refetch_counter: RwSignal<usize> = RwSignal::new(0);
 
     let selected_rows = RwSignal::new(HashSet::<(MangaSource, String)>::new());
     let is_select_empty = Signal::derive(move || selected_rows.get().is_empty());
+    let is_resuming = RwSignal::new(false);
+
+    let toaster = ToasterInjection::expect_context();
+    let handle_resume = move |_| {
+        spawn_local(async move {
+            is_resuming.set(true);
+            let values = selected_rows
+                .get_untracked()
+                .into_iter()
+                .collect::<Vec<_>>();
+
+            match resume_manga(values).await {
+                Ok(num_rows) => {
+                    toaster.dispatch_toast(
+                        move || {
+                            view! {
+                                <Toast>
+                                    <ToastTitle>"Resume Success"</ToastTitle>
+                                    <ToastBody>{format!("{num_rows} manga resumed")}</ToastBody>
+                                </Toast>
+                            }
+                        },
+                        ToastOptions::default().with_intent(ToastIntent::Success),
+                    );
+                    selected_rows.update(|values| values.clear());
+                    refetch_counter.update(|value| *value += 1);
+                }
+                Err(e) => toaster.dispatch_toast(
+                    move || {
+                        view! {
+                            <Toast attr:id="toast-resume-error">
+                                <ToastTitle>"Error"</ToastTitle>
+                                <ToastBody>{e.to_string()}</ToastBody>
+                            </Toast>
+                        }
+                    },
+                    ToastOptions::default().with_intent(ToastIntent::Error),
+                ),
+            }
+
+            is_resuming.set(false);
+        })
+    };
 
     view! {
         <Flex vertical=true gap=FlexGap::Large>
@@ -51,6 +101,30 @@ pub fn Dashboard() -> impl IntoView {
                     >
                         "Delete"
                     </Button>
+                    <Button
+                        attr:id="trigger-refresh-dialog-btn"
+                        appearance=ButtonAppearance::Primary
+                        on_click=move |_| show_refresh_dialog.set(true)
+                        disabled=is_select_empty
+                    >
+                        "Refresh"
+                    </Button>
+                    <Button
+                        attr:id="trigger-tag-dialog-btn"
+                        appearance=ButtonAppearance::Secondary
+                        on_click=move |_| show_tag_dialog.set(true)
+                        disabled=is_select_empty
+                    >
+                        "Tags"
+                    </Button>
+                    <Button
+                        attr:id="resume-btn"
+                        appearance=ButtonAppearance::Secondary
+                        on_click=handle_resume
+                        disabled=Signal::derive(move || is_select_empty.get() || is_resuming.get())
+                    >
+                        "Resume"
+                    </Button>
 
                 </Flex>
                 <Transition fallback=move || {
@@ -70,6 +144,30 @@ pub fn Dashboard() -> impl IntoView {
             }
         />
 
+        <RefreshMangaDialog
+            id="refresh-dialog"
+            open=show_refresh_dialog
+            selected_rows=selected_rows.read_only()
+            on_refresh=move || {
+                refetch_counter
+                    .update(|value| {
+                        *value += 1;
+                    });
+            }
+        />
+
+        <TagMangaDialog
+            id="tag-dialog"
+            open=show_tag_dialog
+            selected_rows=selected_rows.read_only()
+            on_tag=move || {
+                refetch_counter
+                    .update(|value| {
+                        *value += 1;
+                    });
+            }
+        />
+
         <DeleteMangaDialog
             id="delete-dialog"
             open=show_delete_dialog
@@ -128,10 +226,11 @@ fn MangaTable(
     refetch_counter: RwSignal<usize>,
     total_page: RwSignal<usize>,
 ) -> impl IntoView {
-    use crate::server::retrieve_manga;
+    use crate::server::{mark_read, retrieve_manga};
 
     // filter
     let source_filter = RwSignal::new(None::<String>);
+    let unread_filter = RwSignal::new(false);
     let title_filter = RwSignal::new("".to_string());
     let author_filter = RwSignal::new("".to_string());
     let chapter_filter = RwSignal::new("".to_string());
@@ -151,10 +250,11 @@ fn MangaTable(
                 title_filter_debounce.get(),
                 author_filter_debounce.get(),
                 chapter_filter_debounce.get(),
+                unread_filter.get(),
                 refetch_counter.get(),
             )
         },
-        move |(current_page, source, title, author, chapter_title, _counter)| async move {
+        move |(current_page, source, title, author, chapter_title, unread_only, _counter)| async move {
             let title = match title.as_str() {
                 "" => None,
                 _ => Some(title),
@@ -179,6 +279,8 @@ fn MangaTable(
                     author,
                     chapter_title,
                     day: None,
+                    unread_only,
+                    ..Default::default()
                 },
             )
             .await
@@ -192,12 +294,33 @@ fn MangaTable(
         total_page.set(current_total);
     });
 
-    // also reset current page when source filter change
+    // also reset current page when source or unread filter change
     Effect::new(move |_| {
         let _ = source_filter.get();
+        let _ = unread_filter.get();
         current_page.set(1);
     });
 
+    let toaster = ToasterInjection::expect_context();
+    let handle_mark_read = Callback::new(move |key: (MangaSource, String)| {
+        spawn_local(async move {
+            match mark_read(vec![key]).await {
+                Ok(_) => refetch_counter.update(|value| *value += 1),
+                Err(e) => toaster.dispatch_toast(
+                    move || {
+                        view! {
+                            <Toast attr:id="toast-mark-read-error">
+                                <ToastTitle>"Error"</ToastTitle>
+                                <ToastBody>{e.to_string()}</ToastBody>
+                            </Toast>
+                        }
+                    },
+                    ToastOptions::default().with_intent(ToastIntent::Error),
+                ),
+            }
+        })
+    });
+
     view! {
         <Table>
             <TableHeader>
@@ -258,6 +381,15 @@ fn MangaTable(
                         id="chapter-filter"
                         on_change=on_filter_change
                     />
+                    <TableHeaderCell>
+                        <Checkbox
+                            checked=unread_filter
+                            label="Unread only"
+                            attr:id="unread-filter"
+                        />
+                    </TableHeaderCell>
+                    <TableHeaderCell>"Tags"</TableHeaderCell>
+                    <TableHeaderCell>"Health"</TableHeaderCell>
                 </TableRow>
             </TableHeader>
             <TableBody>
@@ -273,7 +405,19 @@ fn MangaTable(
                             .await
                             .data
                             .into_iter()
-                            .map(|(source, manga_id, manga)| {
+                            .map(|
+                                TrackedManga {
+                                    source,
+                                    manga_id,
+                                    manga,
+                                    tags,
+                                    health,
+                                    last_read,
+                                    unread,
+                                }|
+                            {
+                                let read_key = (source.clone(), manga_id.clone());
+                                let read_btn_id = format!("mark-read-{}-{}", &source, &manga_id);
                                 let src = source.clone();
                                 let src_check = source.clone();
                                 let id_check = manga_id.clone();
@@ -323,6 +467,48 @@ fn MangaTable(
                                                 {manga.latest_chapter_title}
                                             </TableCellLayout>
                                         </TableCell>
+                                        <TableCell>
+                                            <TableCellLayout>
+                                                {match unread {
+                                                    true => {
+                                                        view! {
+                                                            <Button
+                                                                attr:id=read_btn_id
+                                                                size=ButtonSize::Small
+                                                                on_click=move |_| handle_mark_read.run(read_key.clone())
+                                                            >
+                                                                "Mark read"
+                                                            </Button>
+                                                        }
+                                                            .into_any()
+                                                    }
+                                                    false => {
+                                                        view! { <ReadBadge last_read /> }.into_any()
+                                                    }
+                                                }}
+                                            </TableCellLayout>
+                                        </TableCell>
+                                        <TableCell>
+                                            <TableCellLayout>
+                                                <Flex gap=FlexGap::Small>
+                                                    {tags
+                                                        .into_iter()
+                                                        .map(|tag| {
+                                                            view! {
+                                                                <Badge appearance=BadgeAppearance::Outline>
+                                                                    {tag}
+                                                                </Badge>
+                                                            }
+                                                        })
+                                                        .collect_view()}
+                                                </Flex>
+                                            </TableCellLayout>
+                                        </TableCell>
+                                        <TableCell>
+                                            <TableCellLayout>
+                                                <HealthBadge health />
+                                            </TableCellLayout>
+                                        </TableCell>
                                     </TableRow>
                                 }
                             })
@@ -334,26 +520,155 @@ fn MangaTable(
     }
 }
 
+#[component]
+fn ReadBadge(last_read: Option<ReadState>) -> impl IntoView {
+    // not unread without a read state means the latest chapter isn't out yet
+    let (color, label, detail) = match &last_read {
+        None => (BadgeColor::Subtle, "Upcoming", None),
+        Some(r) => (
+            BadgeColor::Success,
+            "Read",
+            Some(format!(
+                "{} read {}",
+                r.chapter_title,
+                r.read_at.format("%d-%m-%Y %H:%M")
+            )),
+        ),
+    };
+
+    view! {
+        <Badge appearance=BadgeAppearance::Tint color attr:title=detail>
+            {label}
+        </Badge>
+    }
+}
+
+#[component]
+fn HealthBadge(health: Option<SeriesHealth>) -> impl IntoView {
+    let (color, label, detail) = match &health {
+        None => (BadgeColor::Subtle, "Unchecked".to_string(), None),
+        Some(h) if h.suspended => (
+            BadgeColor::Danger,
+            format!("Suspended ({})", h.consecutive_failures),
+            Some(format!(
+                "{}\nnext probe {}",
+                h.last_error.clone().unwrap_or_default(),
+                h.next_probe_at.map_or("on next run".to_string(), |dt| dt
+                    .format("%d-%m-%Y %H:%M")
+                    .to_string())
+            )),
+        ),
+        Some(h) if !h.is_failing() => (
+            BadgeColor::Success,
+            "OK".to_string(),
+            h.last_success
+                .map(|dt| format!("last success {}", dt.format("%d-%m-%Y %H:%M"))),
+        ),
+        Some(h) => {
+            // a failing transient error usually goes away by itself, the rest needs a look
+            let color = match h.last_error_kind {
+                Some(kind) if kind.is_transient() => BadgeColor::Warning,
+                Some(FetchErrorKind::Blocked) => BadgeColor::Severe,
+                _ => BadgeColor::Danger,
+            };
+            let label = match h.last_error_kind {
+                Some(kind) => format!("{kind} ({})", h.consecutive_failures),
+                None => format!("Failing ({})", h.consecutive_failures),
+            };
+            let detail = h.last_error.clone().map(|e| match h.last_success {
+                Some(dt) => format!("{e}\nlast success {}", dt.format("%d-%m-%Y %H:%M")),
+                None => e,
+            });
+
+            (color, label, detail)
+        }
+    };
+
+    view! {
+        <Badge appearance=BadgeAppearance::Tint color attr:title=detail>
+            {label}
+        </Badge>
+    }
+}
+
 #[component]
 fn AddMangaDialog(
     #[prop(into, optional)] id: MaybeProp<String>,
     open: RwSignal<bool>,
     #[prop(into)] on_add: Callback<()>,
 ) -> impl IntoView {
-    use crate::server::add_manga;
+    use crate::server::{add_manga, preview_manga, resolve_manga_url};
 
     // state
     let manga_id = RwSignal::new("".to_owned());
     let selected_source = RwSignal::new(None::<String>);
     let is_submitting = RwSignal::new(false);
 
+    // a pasted url fills the source and id
+    let url = RwSignal::new("".to_owned());
+    let url_debounce: Signal<String> = signal_debounced(url.read_only(), 500.0);
+    let resolved = Resource::new(
+        move || url_debounce.get(),
+        move |url| async move {
+            match url.trim() {
+                "" => None,
+                url => Some(resolve_manga_url(url.to_owned()).await),
+            }
+        },
+    );
+    Effect::new(move |_| {
+        if let Some(Some(Ok((source, id)))) = resolved.get() {
+            selected_source.set(Some(source.to_string()));
+            manga_id.set(id);
+        }
+    });
+
+    // fetched manga waiting for confirmation, dropped when the id or source changes
+    let preview = RwSignal::new(None::<Manga>);
+    Effect::new(move |_| {
+        manga_id.track();
+        selected_source.track();
+        preview.set(None);
+    });
+
     let toaster = ToasterInjection::expect_context();
-    let handle_add = move |_| {
-        let id = manga_id.get();
+    let show_error = move |e: ServerFnError| {
+        toaster.dispatch_toast(
+            move || {
+                view! {
+                    <Toast attr:id="toast-add-error">
+                        <ToastTitle>"Error"</ToastTitle>
+                        <ToastBody>{e.to_string()}</ToastBody>
+                    </Toast>
+                }
+            },
+            ToastOptions::default().with_intent(ToastIntent::Error),
+        )
+    };
+    let selected = move || {
         let source = selected_source
-            .get()
+            .get_untracked()
             .map(|s| MangaSource::from_str(&s).unwrap());
 
+        (manga_id.get_untracked(), source)
+    };
+
+    let handle_preview = Callback::new(move |_: ()| {
+        let (id, source) = selected();
+
+        spawn_local(async move {
+            is_submitting.set(true);
+            match preview_manga(id, source).await {
+                Ok(manga) => preview.set(Some(manga)),
+                Err(e) => show_error(e),
+            }
+            is_submitting.set(false);
+        })
+    });
+
+    let handle_add = Callback::new(move |_: ()| {
+        let (id, source) = selected();
+
         spawn_local(async move {
             is_submitting.set(true);
             let result = add_manga(id, source).await;
@@ -372,25 +687,17 @@ fn AddMangaDialog(
 
                     manga_id.set("".into());
                     selected_source.set(None);
+                    url.set("".into());
+                    preview.set(None);
                     open.set(false);
                     on_add.run(());
                 }
-                Err(e) => toaster.dispatch_toast(
-                    move || {
-                        view! {
-                            <Toast attr:id="toast-add-error">
-                                <ToastTitle>"Error"</ToastTitle>
-                                <ToastBody>{e.to_string()}</ToastBody>
-                            </Toast>
-                        }
-                    },
-                    ToastOptions::default().with_intent(ToastIntent::Error),
-                ),
+                Err(e) => show_error(e),
             }
 
             is_submitting.set(false);
         })
-    };
+    });
 
     view! {
         <Dialog open>
@@ -399,6 +706,39 @@ fn AddMangaDialog(
                     <DialogTitle>"Add new Manga"</DialogTitle>
                     <DialogContent>
                         <Flex vertical=true gap=FlexGap::Large style="margin-bottom: 10px">
+                            <Field label="URL">
+                                <Input
+                                    value=url
+                                    placeholder="Paste a series or episode page"
+                                    attr:id=id.get().map(|v| format!("{v}-url"))
+                                />
+                            </Field>
+                            <Transition>
+                                {move || {
+                                    resolved
+                                        .get()
+                                        .flatten()
+                                        .map(|result| match result {
+                                            Ok((source, manga_id)) => {
+                                                view! {
+                                                    <p id=id.get().map(|v| format!("{v}-resolved"))>
+                                                        {format!("{source} series {manga_id}")}
+                                                    </p>
+                                                }
+                                                    .into_any()
+                                            }
+                                            Err(e) => {
+                                                view! {
+                                                    <p id=id.get().map(|v| format!("{v}-resolve-error"))>
+                                                        {e.to_string()}
+                                                    </p>
+                                                }
+                                                    .into_any()
+                                            }
+                                        })
+                                }}
+                            </Transition>
+
                             <Field label="Manga ID">
                                 <Input
                                     value=manga_id
@@ -437,23 +777,83 @@ fn AddMangaDialog(
                                     </Combobox>
                                 </Show>
                             </Field>
+
+                            {move || {
+                                preview
+                                    .get()
+                                    .map(|manga| {
+                                        view! {
+                                            <Flex
+                                                gap=FlexGap::Large
+                                                attr:id=id.get().map(|v| format!("{v}-preview"))
+                                            >
+                                                <img
+                                                    src=manga.cover_url
+                                                    alt=manga.title.clone()
+                                                    style="width: 96px"
+                                                />
+                                                <Flex vertical=true>
+                                                    <b>{manga.title}</b>
+                                                    <span>{manga.author}</span>
+                                                    <a href=manga.latest_chapter_url target="_blank">
+                                                        {manga.latest_chapter_title}
+                                                    </a>
+                                                    <span>
+                                                        {manga
+                                                            .latest_chapter_release_date
+                                                            .format("%d-%m-%Y %H:%M")
+                                                            .to_string()}
+                                                    </span>
+                                                </Flex>
+                                            </Flex>
+                                        }
+                                    })
+                            }}
                         </Flex>
                     </DialogContent>
 
                     <DialogActions>
-                        <Button
-                            attr:id=id.get().map(|v| format!("{v}-add-btn"))
-                            appearance=ButtonAppearance::Primary
-                            on_click=handle_add
-                            disabled=is_submitting
+                        <Show
+                            when=move || preview.get().is_some()
+                            fallback=move || {
+                                view! {
+                                    <Button
+                                        attr:id=id.get().map(|v| format!("{v}-preview-btn"))
+                                        appearance=ButtonAppearance::Primary
+                                        on_click=move |_| handle_preview.run(())
+                                        disabled=is_submitting
+                                    >
+                                        {move || {
+                                            is_submitting
+                                                .get()
+                                                .then(|| view! { <Spinner size=SpinnerSize::Tiny /> })
+                                        }}
+                                        "Preview"
+                                    </Button>
+                                }
+                            }
                         >
-                            {move || {
-                                is_submitting
-                                    .get()
-                                    .then(|| view! { <Spinner size=SpinnerSize::Tiny /> })
-                            }}
-                            "Add"
-                        </Button>
+                            <Button
+                                attr:id=id.get().map(|v| format!("{v}-back-btn"))
+                                on_click=move |_| preview.set(None)
+                                disabled=is_submitting
+                            >
+                                "Back"
+                            </Button>
+                            <Button
+                                attr:id=id.get().map(|v| format!("{v}-add-btn"))
+                                appearance=ButtonAppearance::Primary
+                                on_click=move |_| handle_add.run(())
+                                disabled=is_submitting
+                            >
+                                {move || {
+                                    is_submitting
+                                        .get()
+                                        .then(|| view! { <Spinner size=SpinnerSize::Tiny /> })
+                                }}
+                                "Add"
+                            </Button>
+                        </Show>
                     </DialogActions>
                 </DialogBody>
             </DialogSurface>
@@ -552,3 +952,229 @@ fn DeleteMangaDialog(
         </Dialog>
     }
 }
+
+#[component]
+fn RefreshMangaDialog(
+    #[prop(into, optional)] id: MaybeProp<String>,
+    open: RwSignal<bool>,
+    selected_rows: ReadSignal<HashSet<(MangaSource, String)>>,
+    #[prop(into)] on_refresh: Callback<()>,
+) -> impl IntoView {
+    use crate::server::refresh_manga;
+
+    // state
+    let broadcast = RwSignal::new(false);
+    let is_submitting = RwSignal::new(false);
+
+    let toaster = ToasterInjection::expect_context();
+    let handle_refresh = move |_| {
+        spawn_local(async move {
+            is_submitting.set(true);
+            let values = selected_rows
+                .get_untracked()
+                .into_iter()
+                .collect::<Vec<_>>();
+            let result = refresh_manga(values, broadcast.get_untracked()).await;
+
+            match result {
+                Ok(result) => {
+                    let intent = match result.failed.is_empty() {
+                        true => ToastIntent::Success,
+                        false => ToastIntent::Warning,
+                    };
+                    toaster.dispatch_toast(
+                        move || {
+                            view! {
+                                <Toast>
+                                    <ToastTitle>"Refresh Done"</ToastTitle>
+                                    <ToastBody>
+                                        {format!(
+                                            "{} released, {} upcoming, {} unchanged",
+                                            result.released,
+                                            result.upcoming,
+                                            result.no_change,
+                                        )}
+                                        {result
+                                            .failed
+                                            .into_iter()
+                                            .map(|(source, manga_id, error)| {
+                                                view! { <p>{format!("{source} {manga_id}: {error}")}</p> }
+                                            })
+                                            .collect_view()}
+                                    </ToastBody>
+                                </Toast>
+                            }
+                        },
+                        ToastOptions::default().with_intent(intent),
+                    );
+                    on_refresh.run(());
+                }
+                Err(e) => toaster.dispatch_toast(
+                    move || {
+                        view! {
+                            <Toast attr:id="toast-refresh-error">
+                                <ToastTitle>"Error"</ToastTitle>
+                                <ToastBody>{e.to_string()}</ToastBody>
+                            </Toast>
+                        }
+                    },
+                    ToastOptions::default().with_intent(ToastIntent::Error),
+                ),
+            }
+
+            is_submitting.set(false);
+            open.set(false);
+        })
+    };
+
+    view! {
+        <Dialog open>
+            <DialogSurface>
+                <DialogBody attr:id=id.get().map(|v| format!("{v}-body"))>
+                    <DialogTitle>"Refresh Manga"</DialogTitle>
+                    <DialogContent>
+                        <Flex vertical=true gap=FlexGap::Large style="margin-bottom: 10px">
+                            <p>"Check the selected manga for new chapters now ?"</p>
+                            <Checkbox
+                                checked=broadcast
+                                label="Broadcast new chapters to the notification sinks"
+                                attr:id=id.get().map(|v| format!("{v}-broadcast"))
+                            />
+                        </Flex>
+                    </DialogContent>
+
+                    <DialogActions>
+                        <Button
+                            attr:id=id.get().map(|v| format!("{v}-refresh-btn"))
+                            appearance=ButtonAppearance::Primary
+                            on_click=handle_refresh
+                            disabled=is_submitting
+                        >
+                            {move || {
+                                is_submitting
+                                    .get()
+                                    .then(|| view! { <Spinner size=SpinnerSize::Tiny /> })
+                            }}
+                            "Refresh"
+                        </Button>
+                        <Button
+                            attr:id=id.get().map(|v| format!("{v}-cancel-btn"))
+                            appearance=ButtonAppearance::Primary
+                            on_click=move |_| open.set(false)
+                        >
+                            "Cancel"
+                        </Button>
+                    </DialogActions>
+                </DialogBody>
+            </DialogSurface>
+        </Dialog>
+    }
+}
+
+#[component]
+fn TagMangaDialog(
+    #[prop(into, optional)] id: MaybeProp<String>,
+    open: RwSignal<bool>,
+    selected_rows: ReadSignal<HashSet<(MangaSource, String)>>,
+    #[prop(into)] on_tag: Callback<()>,
+) -> impl IntoView {
+    use crate::server::tag_manga;
+
+    // state
+    let tags = RwSignal::new("".to_owned());
+    let is_submitting = RwSignal::new(false);
+
+    let toaster = ToasterInjection::expect_context();
+    let handle_tag = move |_| {
+        spawn_local(async move {
+            is_submitting.set(true);
+            let values = selected_rows
+                .get_untracked()
+                .into_iter()
+                .collect::<Vec<_>>();
+            let tag_list = tags
+                .get_untracked()
+                .split(',')
+                .map(|tag| tag.to_string())
+                .collect::<Vec<_>>();
+            let result = tag_manga(values, tag_list).await;
+
+            match result {
+                Ok(num_rows) => {
+                    toaster.dispatch_toast(
+                        move || {
+                            view! {
+                                <Toast>
+                                    <ToastTitle>"Tags Updated"</ToastTitle>
+                                    <ToastBody>{format!("{num_rows} manga tagged")}</ToastBody>
+                                </Toast>
+                            }
+                        },
+                        ToastOptions::default().with_intent(ToastIntent::Success),
+                    );
+                    tags.set("".to_owned());
+                    on_tag.run(());
+                }
+                Err(e) => toaster.dispatch_toast(
+                    move || {
+                        view! {
+                            <Toast attr:id="toast-tag-error">
+                                <ToastTitle>"Error"</ToastTitle>
+                                <ToastBody>{e.to_string()}</ToastBody>
+                            </Toast>
+                        }
+                    },
+                    ToastOptions::default().with_intent(ToastIntent::Error),
+                ),
+            }
+
+            is_submitting.set(false);
+            open.set(false);
+        })
+    };
+
+    view! {
+        <Dialog open>
+            <DialogSurface>
+                <DialogBody attr:id=id.get().map(|v| format!("{v}-body"))>
+                    <DialogTitle>"Tag Manga"</DialogTitle>
+                    <DialogContent>
+                        <Flex vertical=true gap=FlexGap::Large style="margin-bottom: 10px">
+                            <p>"Replace the tags of the selected manga, leave empty to clear them"</p>
+                            <Field label="Tags (comma separated)">
+                                <Input
+                                    value=tags
+                                    placeholder="action, favorite"
+                                    attr:id=id.get().map(|v| format!("{v}-input"))
+                                />
+                            </Field>
+                        </Flex>
+                    </DialogContent>
+
+                    <DialogActions>
+                        <Button
+                            attr:id=id.get().map(|v| format!("{v}-save-btn"))
+                            appearance=ButtonAppearance::Primary
+                            on_click=handle_tag
+                            disabled=is_submitting
+                        >
+                            {move || {
+                                is_submitting
+                                    .get()
+                                    .then(|| view! { <Spinner size=SpinnerSize::Tiny /> })
+                            }}
+                            "Save"
+                        </Button>
+                        <Button
+                            attr:id=id.get().map(|v| format!("{v}-cancel-btn"))
+                            appearance=ButtonAppearance::Primary
+                            on_click=move |_| open.set(false)
+                        >
+                            "Cancel"
+                        </Button>
+                    </DialogActions>
+                </DialogBody>
+            </DialogSurface>
+        </Dialog>
+    }
+}
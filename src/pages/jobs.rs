@@ -0,0 +1,244 @@
+use chrono::{DateTime, FixedOffset};
+use leptos::prelude::*;
+use leptos_meta::Title;
+use leptos_router::hooks::use_params_map;
+use thaw::{
+    Badge, BadgeAppearance, BadgeColor, Flex, FlexGap, FlexJustify, Link, Pagination, Table,
+    TableBody, TableCell, TableCellLayout, TableHeader, TableHeaderCell, TableRow,
+};
+
+use crate::core::types::JobRun;
+
+fn format_dt(dt: DateTime<FixedOffset>) -> String {
+    dt.format("%d-%m-%Y %H:%M:%S").to_string()
+}
+
+#[component]
+fn RunStatus(run: JobRun) -> impl IntoView {
+    let (color, label) = match (&run.error, run.finished_at) {
+        (Some(_), _) => (BadgeColor::Danger, "Aborted"),
+        (None, None) => (BadgeColor::Informative, "Running"),
+        (None, Some(_)) if run.failed > 0 || run.notification_failures > 0 => {
+            (BadgeColor::Warning, "Partial")
+        }
+        (None, Some(_)) => (BadgeColor::Success, "Success"),
+    };
+
+    view! {
+        <Badge appearance=BadgeAppearance::Tint color attr:title=run.error>
+            {label}
+        </Badge>
+    }
+}
+
+#[component]
+pub fn JobRuns() -> impl IntoView {
+    use crate::server::retrieve_job_runs;
+
+    let page: RwSignal<usize> = RwSignal::new(1);
+    let page_count: RwSignal<usize> = RwSignal::new(1);
+
+    let data_source = Resource::new(
+        move || page.get(),
+        move |current_page| async move { retrieve_job_runs(current_page as i64, 20).await.unwrap() },
+    );
+
+    Effect::new(move |_| {
+        let current_total = data_source.get().map_or(1, |d| d.total_page as usize);
+        page_count.set(current_total.max(1));
+    });
+
+    view! {
+        <Flex vertical=true gap=FlexGap::Large>
+            <Title text="Jobs" />
+            <Table>
+                <TableHeader>
+                    <TableRow>
+                        <TableHeaderCell>"Run"</TableHeaderCell>
+                        <TableHeaderCell>"Started"</TableHeaderCell>
+                        <TableHeaderCell>"Finished"</TableHeaderCell>
+                        <TableHeaderCell>"Status"</TableHeaderCell>
+                        <TableHeaderCell>"Checked"</TableHeaderCell>
+                        <TableHeaderCell>"Released"</TableHeaderCell>
+                        <TableHeaderCell>"Upcoming"</TableHeaderCell>
+                        <TableHeaderCell>"No change"</TableHeaderCell>
+                        <TableHeaderCell>"Failed"</TableHeaderCell>
+                    </TableRow>
+                </TableHeader>
+                <TableBody>
+                    <Transition fallback=move || {
+                        view! {
+                            <TableRow>
+                                <p>"Loading..."</p>
+                            </TableRow>
+                        }
+                    }>
+                        {move || Suspend::new(async move {
+                            data_source
+                                .await
+                                .data
+                                .into_iter()
+                                .map(|run| {
+                                    view! {
+                                        <TableRow attr:id=format!("run-{}", run.id)>
+                                            <TableCell>
+                                                <TableCellLayout>
+                                                    <Link href=format!("/jobs/{}", run.id)>
+                                                        {format!("#{}", run.id)}
+                                                    </Link>
+                                                </TableCellLayout>
+                                            </TableCell>
+                                            <TableCell>
+                                                <TableCellLayout>
+                                                    {format_dt(run.started_at)}
+                                                </TableCellLayout>
+                                            </TableCell>
+                                            <TableCell>
+                                                <TableCellLayout>
+                                                    {run.finished_at.map(format_dt)}
+                                                </TableCellLayout>
+                                            </TableCell>
+                                            <TableCell>
+                                                <TableCellLayout>
+                                                    <RunStatus run=run.clone() />
+                                                </TableCellLayout>
+                                            </TableCell>
+                                            <TableCell>
+                                                <TableCellLayout>
+                                                    {format!("{} ({} skipped)", run.checked, run.skipped)}
+                                                </TableCellLayout>
+                                            </TableCell>
+                                            <TableCell>
+                                                <TableCellLayout>{run.released}</TableCellLayout>
+                                            </TableCell>
+                                            <TableCell>
+                                                <TableCellLayout>{run.upcoming}</TableCellLayout>
+                                            </TableCell>
+                                            <TableCell>
+                                                <TableCellLayout>{run.no_change}</TableCellLayout>
+                                            </TableCell>
+                                            <TableCell>
+                                                <TableCellLayout>{run.failed}</TableCellLayout>
+                                            </TableCell>
+                                        </TableRow>
+                                    }
+                                })
+                                .collect_view()
+                        })}
+                    </Transition>
+                </TableBody>
+            </Table>
+            <Flex justify=FlexJustify::End>
+                <Pagination page page_count />
+            </Flex>
+        </Flex>
+    }
+}
+
+#[component]
+pub fn JobRunPage() -> impl IntoView {
+    use crate::server::retrieve_job_run;
+
+    let params = use_params_map();
+    let run_id = move || {
+        params
+            .read()
+            .get("id")
+            .and_then(|id| id.parse::<i64>().ok())
+    };
+
+    let data_source = Resource::new(run_id, move |run_id| async move {
+        match run_id {
+            Some(run_id) => retrieve_job_run(run_id).await.map_err(|e| e.to_string()),
+            None => Err("invalid job run id".to_string()),
+        }
+    });
+
+    view! {
+        <Flex vertical=true gap=FlexGap::Large>
+            <Title text="Job Run" />
+            <Link href="/jobs">"Back to jobs"</Link>
+            <Transition fallback=move || {
+                view! { <p>"Loading..."</p> }
+            }>
+                {move || Suspend::new(async move {
+                    match data_source.await {
+                        Err(e) => view! { <p attr:id="job-run-error">{e}</p> }.into_any(),
+                        Ok(detail) => {
+                            let run = detail.run;
+                            view! {
+                                <Flex vertical=true gap=FlexGap::Medium>
+                                    <Flex gap=FlexGap::Large>
+                                        <h2>{format!("Run #{}", run.id)}</h2>
+                                        <RunStatus run=run.clone() />
+                                    </Flex>
+                                    <p>
+                                        {format!(
+                                            "Started {}, finished {}",
+                                            format_dt(run.started_at),
+                                            run.finished_at.map_or("-".to_string(), format_dt),
+                                        )}
+                                    </p>
+                                    <p>
+                                        {format!(
+                                            "{} checked, {} skipped, {} released, {} upcoming, {} unchanged, {} failed, {} notification failures",
+                                            run.checked,
+                                            run.skipped,
+                                            run.released,
+                                            run.upcoming,
+                                            run.no_change,
+                                            run.failed,
+                                            run.notification_failures,
+                                        )}
+                                    </p>
+                                    {run.error.map(|e| view! { <p>{format!("Aborted: {e}")}</p> })}
+                                </Flex>
+                                <Table>
+                                    <TableHeader>
+                                        <TableRow>
+                                            <TableHeaderCell>"Source"</TableHeaderCell>
+                                            <TableHeaderCell>"Manga ID"</TableHeaderCell>
+                                            <TableHeaderCell>"Kind"</TableHeaderCell>
+                                            <TableHeaderCell>"Error"</TableHeaderCell>
+                                        </TableRow>
+                                    </TableHeader>
+                                    <TableBody>
+                                        {detail
+                                            .errors
+                                            .into_iter()
+                                            .map(|error| {
+                                                view! {
+                                                    <TableRow>
+                                                        <TableCell>
+                                                            <TableCellLayout>
+                                                                {error.source.to_string()}
+                                                            </TableCellLayout>
+                                                        </TableCell>
+                                                        <TableCell>
+                                                            <TableCellLayout>{error.manga_id}</TableCellLayout>
+                                                        </TableCell>
+                                                        <TableCell>
+                                                            <TableCellLayout>
+                                                                {error
+                                                                    .error_kind
+                                                                    .map_or("Panicked".to_string(), |k| k.to_string())}
+                                                            </TableCellLayout>
+                                                        </TableCell>
+                                                        <TableCell>
+                                                            <TableCellLayout>{error.error}</TableCellLayout>
+                                                        </TableCell>
+                                                    </TableRow>
+                                                }
+                                            })
+                                            .collect_view()}
+                                    </TableBody>
+                                </Table>
+                            }
+                                .into_any()
+                        }
+                    }
+                })}
+            </Transition>
+        </Flex>
+    }
+}
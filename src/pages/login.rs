@@ -0,0 +1,82 @@
+use leptos::{prelude::*, task::spawn_local};
+use leptos_meta::Title;
+use thaw::{
+    Button, ButtonAppearance, Field, Flex, FlexGap, Input, InputType, Spinner, SpinnerSize, Toast,
+    ToastBody, ToastIntent, ToastOptions, ToastTitle, ToasterInjection,
+};
+
+use crate::core::types::User;
+
+#[component]
+pub fn Login() -> impl IntoView {
+    use crate::server::{login, register};
+
+    let username = RwSignal::new("".to_owned());
+    let password = RwSignal::new("".to_owned());
+    let is_submitting = RwSignal::new(false);
+
+    let toaster = ToasterInjection::expect_context();
+    let submit = Callback::new(move |signup: bool| {
+        spawn_local(async move {
+            is_submitting.set(true);
+            let (name, pass) = (username.get_untracked(), password.get_untracked());
+            let result: Result<User, _> = match signup {
+                true => register(name, pass).await,
+                false => login(name, pass).await,
+            };
+
+            match result {
+                // reload so every page picks up the session
+                Ok(_) => {
+                    let _ = window().location().set_href("/dashboard");
+                }
+                Err(e) => toaster.dispatch_toast(
+                    move || {
+                        view! {
+                            <Toast attr:id="toast-login-error">
+                                <ToastTitle>"Error"</ToastTitle>
+                                <ToastBody>{e.to_string()}</ToastBody>
+                            </Toast>
+                        }
+                    },
+                    ToastOptions::default().with_intent(ToastIntent::Error),
+                ),
+            }
+
+            is_submitting.set(false);
+        })
+    });
+
+    view! {
+        <Title text="Login" />
+        <Flex vertical=true gap=FlexGap::Large style="width: 320px">
+            <h2>"Login"</h2>
+            <Field label="Username">
+                <Input value=username attr:id="login-username" />
+            </Field>
+            <Field label="Password">
+                <Input value=password input_type=InputType::Password attr:id="login-password" />
+            </Field>
+            <Flex>
+                <Button
+                    attr:id="login-btn"
+                    appearance=ButtonAppearance::Primary
+                    on_click=move |_| submit.run(false)
+                    disabled=is_submitting
+                >
+                    {move || {
+                        is_submitting.get().then(|| view! { <Spinner size=SpinnerSize::Tiny /> })
+                    }}
+                    "Login"
+                </Button>
+                <Button
+                    attr:id="signup-btn"
+                    on_click=move |_| submit.run(true)
+                    disabled=is_submitting
+                >
+                    "Sign up"
+                </Button>
+            </Flex>
+        </Flex>
+    }
+}
@@ -1,2 +1,5 @@
 pub mod dashboard;
 pub mod home;
+pub mod jobs;
+pub mod login;
+pub mod tokens;
@@ -0,0 +1,161 @@
+use chrono::{DateTime, FixedOffset};
+use leptos::{prelude::*, task::spawn_local};
+use leptos_meta::Title;
+use thaw::{
+    Button, ButtonAppearance, ButtonSize, Field, Flex, FlexAlign, FlexGap, Input, Table, TableBody,
+    TableCell, TableCellLayout, TableHeader, TableHeaderCell, TableRow, Toast, ToastBody,
+    ToastIntent, ToastOptions, ToastTitle, ToasterInjection,
+};
+
+fn format_dt(dt: DateTime<FixedOffset>) -> String {
+    dt.format("%d-%m-%Y %H:%M:%S").to_string()
+}
+
+/// api tokens of the logged in user, sent as `Authorization: Bearer <token>`
+#[component]
+pub fn ApiTokens() -> impl IntoView {
+    use crate::server::{create_api_token, retrieve_api_tokens, revoke_api_token};
+
+    let name = RwSignal::new("".to_owned());
+    let created_token = RwSignal::new(None::<String>);
+    let refetch_counter: RwSignal<usize> = RwSignal::new(0);
+
+    let data_source = Resource::new(
+        move || refetch_counter.get(),
+        move |_| async move { retrieve_api_tokens().await },
+    );
+
+    let toaster = ToasterInjection::expect_context();
+    let show_error = move |e: ServerFnError| {
+        toaster.dispatch_toast(
+            move || {
+                view! {
+                    <Toast attr:id="toast-token-error">
+                        <ToastTitle>"Error"</ToastTitle>
+                        <ToastBody>{e.to_string()}</ToastBody>
+                    </Toast>
+                }
+            },
+            ToastOptions::default().with_intent(ToastIntent::Error),
+        )
+    };
+
+    let handle_create = move |_| {
+        spawn_local(async move {
+            match create_api_token(name.get_untracked()).await {
+                Ok(token) => {
+                    created_token.set(Some(token));
+                    name.set("".to_owned());
+                    refetch_counter.update(|value| *value += 1);
+                }
+                Err(e) => show_error(e),
+            }
+        })
+    };
+
+    let handle_revoke = Callback::new(move |id: i64| {
+        spawn_local(async move {
+            match revoke_api_token(id).await {
+                Ok(_) => refetch_counter.update(|value| *value += 1),
+                Err(e) => show_error(e),
+            }
+        })
+    });
+
+    view! {
+        <Flex vertical=true gap=FlexGap::Large>
+            <Title text="API Tokens" />
+            <Flex align=FlexAlign::End>
+                <Field label="Token name">
+                    <Input value=name placeholder="ci" attr:id="token-name-input" />
+                </Field>
+                <Button
+                    attr:id="create-token-btn"
+                    appearance=ButtonAppearance::Primary
+                    on_click=handle_create
+                >
+                    "Create"
+                </Button>
+            </Flex>
+            {move || {
+                created_token
+                    .get()
+                    .map(|token| {
+                        view! {
+                            <p>
+                                "Copy the token now, it won't be shown again: "
+                                <code id="created-token">{token}</code>
+                            </p>
+                        }
+                    })
+            }}
+            <Table>
+                <TableHeader>
+                    <TableRow>
+                        <TableHeaderCell>"Name"</TableHeaderCell>
+                        <TableHeaderCell>"Created"</TableHeaderCell>
+                        <TableHeaderCell>"Last used"</TableHeaderCell>
+                        <TableHeaderCell>"Action"</TableHeaderCell>
+                    </TableRow>
+                </TableHeader>
+                <TableBody>
+                    <Transition fallback=move || {
+                        view! {
+                            <TableRow>
+                                <p>"Loading..."</p>
+                            </TableRow>
+                        }
+                    }>
+                        {move || Suspend::new(async move {
+                            match data_source.await {
+                                Ok(tokens) => {
+                                    tokens
+                                        .into_iter()
+                                        .map(|token| {
+                                            view! {
+                                                <TableRow attr:id=format!("token-{}", token.id)>
+                                                    <TableCell>
+                                                        <TableCellLayout>{token.name}</TableCellLayout>
+                                                    </TableCell>
+                                                    <TableCell>
+                                                        <TableCellLayout>
+                                                            {format_dt(token.created_at)}
+                                                        </TableCellLayout>
+                                                    </TableCell>
+                                                    <TableCell>
+                                                        <TableCellLayout>
+                                                            {token.last_used_at.map(format_dt)}
+                                                        </TableCellLayout>
+                                                    </TableCell>
+                                                    <TableCell>
+                                                        <TableCellLayout>
+                                                            <Button
+                                                                size=ButtonSize::Small
+                                                                on_click=move |_| handle_revoke.run(token.id)
+                                                            >
+                                                                "Revoke"
+                                                            </Button>
+                                                        </TableCellLayout>
+                                                    </TableCell>
+                                                </TableRow>
+                                            }
+                                        })
+                                        .collect_view()
+                                        .into_any()
+                                }
+                                Err(e) => {
+                                    view! {
+                                        <TableRow>
+                                            <p>{e.to_string()}</p>
+                                        </TableRow>
+                                    }
+                                        .into_any()
+                                }
+                            }
+                        })}
+                    </Transition>
+                </TableBody>
+            </Table>
+        </Flex>
+    }
+}
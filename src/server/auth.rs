@@ -0,0 +1,268 @@
+use std::env;
+
+use argon2::{
+    password_hash::{rand_core::OsRng, rand_core::RngCore, PasswordHash, SaltString},
+    Argon2, PasswordHasher, PasswordVerifier,
+};
+use http::{
+    header::{AUTHORIZATION, COOKIE},
+    HeaderMap,
+};
+use sha2::{Digest, Sha256};
+
+use crate::core::types::User;
+
+pub const SESSION_COOKIE: &str = "session";
+pub const SESSION_DAYS: i64 = 30;
+
+#[derive(Clone)]
+pub struct AuthConfig {
+    /// anonymous visitors can't see or change anything
+    pub require_login: bool,
+    /// anyone reaching the login page can create an account, off by default once writes are
+    /// protected since any account passes the write guard
+    pub allow_signup: bool,
+    /// bearer token allowed to call every server function
+    pub admin_token: Option<String>,
+    /// server functions changing data need an api token or a logged in user, always the case
+    /// when login is required
+    pub protect_writes: bool,
+    /// the session cookie is only sent over https
+    pub secure_cookie: bool,
+}
+
+impl Default for AuthConfig {
+    fn default() -> Self {
+        Self {
+            require_login: false,
+            allow_signup: true,
+            admin_token: None,
+            protect_writes: false,
+            secure_cookie: false,
+        }
+    }
+}
+
+impl std::fmt::Debug for AuthConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AuthConfig")
+            .field("require_login", &self.require_login)
+            .field("allow_signup", &self.allow_signup)
+            .field(
+                "admin_token",
+                &self.admin_token.as_ref().map(|_| "<redacted>"),
+            )
+            .field("protect_writes", &self.protect_writes)
+            .field("secure_cookie", &self.secure_cookie)
+            .finish()
+    }
+}
+
+impl AuthConfig {
+    pub fn from_env() -> Self {
+        let default = Self::default();
+        let flag = |key: &str, default: bool| {
+            env::var(key)
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(default)
+        };
+
+        let admin_token = env::var("ADMIN_API_TOKEN").ok().filter(|t| !t.is_empty());
+        // setting an admin token is only useful when writes are protected
+        let protect_writes = flag("PROTECT_WRITES", admin_token.is_some());
+        let signup_default = default.allow_signup && !protect_writes && admin_token.is_none();
+
+        Self {
+            require_login: flag("REQUIRE_LOGIN", default.require_login),
+            allow_signup: flag("ALLOW_SIGNUP", signup_default),
+            protect_writes,
+            secure_cookie: flag("SECURE_COOKIE", default.secure_cookie),
+            admin_token,
+        }
+    }
+
+    /// writes are protected on their own or because login is required
+    pub fn protects_writes(&self) -> bool {
+        self.protect_writes || self.require_login
+    }
+
+    /// without required login nor an admin token nobody else can manage the shared series,
+    /// anonymous callers act as the admin
+    pub fn anonymous_is_admin(&self) -> bool {
+        !self.require_login && self.admin_token.is_none()
+    }
+
+    pub fn is_admin_token(&self, token: &str) -> bool {
+        // compare digests so the comparison time doesn't depend on the matching prefix
+        self.admin_token
+            .as_ref()
+            .is_some_and(|admin| hash_token(admin) == hash_token(token))
+    }
+}
+
+/// who a request to the api was authenticated as
+#[derive(Debug, Clone)]
+pub enum Caller {
+    Admin,
+    User(User),
+}
+
+pub fn hash_password(password: &str) -> Result<String, argon2::password_hash::Error> {
+    let salt = SaltString::generate(&mut OsRng);
+    let hash = Argon2::default().hash_password(password.as_bytes(), &salt)?;
+
+    Ok(hash.to_string())
+}
+
+pub fn verify_password(password: &str, password_hash: &str) -> bool {
+    PasswordHash::new(password_hash).is_ok_and(|hash| {
+        Argon2::default()
+            .verify_password(password.as_bytes(), &hash)
+            .is_ok()
+    })
+}
+
+/// a random session token, only its hash is stored so a leaked db can't be used to log in
+pub fn new_session_token() -> String {
+    let mut bytes = [0u8; 32];
+    OsRng.fill_bytes(&mut bytes);
+
+    hex(&bytes)
+}
+
+pub fn hash_token(token: &str) -> String {
+    hex(&Sha256::digest(token.as_bytes()))
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+pub fn session_cookie(token: &str, secure: bool) -> String {
+    format!(
+        "{SESSION_COOKIE}={token}; HttpOnly; SameSite=Lax; Path=/; Max-Age={}{}",
+        SESSION_DAYS * 24 * 60 * 60,
+        secure_attribute(secure)
+    )
+}
+
+pub fn expired_session_cookie(secure: bool) -> String {
+    format!(
+        "{SESSION_COOKIE}=; HttpOnly; SameSite=Lax; Path=/; Max-Age=0{}",
+        secure_attribute(secure)
+    )
+}
+
+fn secure_attribute(secure: bool) -> &'static str {
+    if secure {
+        "; Secure"
+    } else {
+        ""
+    }
+}
+
+/// the session token sent by the browser, if any
+pub fn session_token(headers: &HeaderMap) -> Option<String> {
+    headers
+        .get_all(COOKIE)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(';'))
+        .filter_map(|cookie| cookie.trim().split_once('='))
+        .find(|(name, value)| *name == SESSION_COOKIE && !value.is_empty())
+        .map(|(_, value)| value.to_string())
+}
+
+/// the token of an `Authorization: Bearer` header, if any
+pub fn bearer_token(headers: &HeaderMap) -> Option<String> {
+    headers
+        .get(AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .map(|token| token.trim().to_string())
+        .filter(|token| !token.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use http::HeaderValue;
+
+    use super::*;
+
+    #[test]
+    fn verify_hashed_password() {
+        let hash = hash_password("hunter2").unwrap();
+
+        assert_ne!(hash, "hunter2");
+        assert!(verify_password("hunter2", &hash));
+        assert!(!verify_password("hunter3", &hash));
+        assert!(!verify_password("hunter2", "not a hash"));
+    }
+
+    #[test]
+    fn read_session_token_from_cookies() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(session_token(&headers), None);
+
+        headers.append(COOKIE, HeaderValue::from_static("theme=dark; session=abc"));
+        assert_eq!(session_token(&headers), Some("abc".into()));
+
+        let mut headers = HeaderMap::new();
+        headers.append(COOKIE, HeaderValue::from_static("session="));
+        assert_eq!(session_token(&headers), None);
+    }
+
+    #[test]
+    fn secure_session_cookie() {
+        assert!(!session_cookie("abc", false).contains("Secure"));
+        assert!(session_cookie("abc", true).ends_with("; Secure"));
+        assert!(expired_session_cookie(true).ends_with("; Secure"));
+    }
+
+    #[test]
+    fn tokens_are_unique() {
+        let token = new_session_token();
+
+        assert_eq!(token.len(), 64);
+        assert_ne!(token, new_session_token());
+        assert_eq!(hash_token(&token), hash_token(&token));
+        assert_ne!(hash_token(&token), token);
+    }
+
+    #[test]
+    fn read_bearer_token() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(bearer_token(&headers), None);
+
+        headers.insert(AUTHORIZATION, HeaderValue::from_static("Basic abc"));
+        assert_eq!(bearer_token(&headers), None);
+
+        headers.insert(AUTHORIZATION, HeaderValue::from_static("Bearer abc"));
+        assert_eq!(bearer_token(&headers), Some("abc".into()));
+    }
+
+    #[test]
+    fn match_admin_token() {
+        let config = AuthConfig {
+            admin_token: Some("secret".into()),
+            ..Default::default()
+        };
+
+        assert!(config.is_admin_token("secret"));
+        assert!(!config.is_admin_token("secre"));
+        assert!(!AuthConfig::default().is_admin_token(""));
+        assert!(!format!("{config:?}").contains("secret"));
+    }
+
+    #[test]
+    fn required_login_protects_writes() {
+        assert!(!AuthConfig::default().protects_writes());
+
+        let config = AuthConfig {
+            require_login: true,
+            ..Default::default()
+        };
+        assert!(config.protects_writes());
+    }
+}
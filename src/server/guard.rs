@@ -0,0 +1,139 @@
+use axum::{
+    extract::{Request, State},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use http::{HeaderMap, Method, StatusCode};
+use leptos::server_fn::ServerFn;
+
+use super::{
+    auth::{bearer_token, session_token, AuthConfig, Caller},
+    service::{api_token_user_service, session_user_service},
+    CurrentUser, Login, Logout, Register, RetrieveApiTokens, RetrieveChapters, RetrieveJobRun,
+    RetrieveJobRuns, RetrieveManga,
+};
+use crate::{api, state::AppState};
+
+/// server functions open to anonymous callers even when writes are protected: the read-only
+/// ones and the ones needed to log in, every other server function is guarded by default
+const OPEN_SERVER_FNS: [&str; 9] = [
+    RetrieveManga::PATH,
+    RetrieveChapters::PATH,
+    RetrieveJobRuns::PATH,
+    RetrieveJobRun::PATH,
+    RetrieveApiTokens::PATH,
+    CurrentUser::PATH,
+    Register::PATH,
+    Login::PATH,
+    Logout::PATH,
+];
+
+/// server functions and rest routes changing data or fetching from the sources, they need a
+/// caller when writes are protected
+fn is_mutating(method: &Method, path: &str) -> bool {
+    if path.starts_with(api::PREFIX) {
+        return !matches!(*method, Method::GET | Method::HEAD);
+    }
+
+    !OPEN_SERVER_FNS.contains(&path)
+}
+
+/// anonymous requests refused under `auth`, signing up is refused as well when it's turned off
+/// since the new account would get past the guard
+fn is_guarded(auth: &AuthConfig, method: &Method, path: &str) -> bool {
+    let signup_closed = path == Register::PATH && !auth.allow_signup;
+
+    auth.protects_writes() && (is_mutating(method, path) || signup_closed)
+}
+
+/// authenticate api calls from a bearer token or the session cookie, the caller is handed to
+/// server functions through the request extensions
+pub async fn require_api_token(
+    State(state): State<AppState>,
+    mut request: Request,
+    next: Next,
+) -> Response {
+    let caller = match authenticate(&state, request.headers()).await {
+        Ok(caller) => caller,
+        Err(response) => return response,
+    };
+
+    match caller {
+        Some(caller) => {
+            request.extensions_mut().insert(caller);
+        }
+        None if is_guarded(&state.auth, request.method(), request.uri().path()) => {
+            return (StatusCode::UNAUTHORIZED, "api token required").into_response();
+        }
+        None => (),
+    }
+
+    next.run(request).await
+}
+
+async fn authenticate(state: &AppState, headers: &HeaderMap) -> Result<Option<Caller>, Response> {
+    if let Some(token) = bearer_token(headers) {
+        if state.auth.is_admin_token(&token) {
+            return Ok(Some(Caller::Admin));
+        }
+
+        // a wrong token is refused rather than treated as anonymous so typos don't go unnoticed
+        return match api_token_user_service(token, state.pool.clone()).await {
+            Ok(Some(user)) => Ok(Some(Caller::User(user))),
+            Ok(None) => Err((StatusCode::UNAUTHORIZED, "invalid api token").into_response()),
+            Err(e) => Err((StatusCode::INTERNAL_SERVER_ERROR, e).into_response()),
+        };
+    }
+
+    match session_token(headers) {
+        Some(token) => session_user_service(token, state.pool.clone())
+            .await
+            .map(|user| user.map(Caller::User))
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e).into_response()),
+        None => Ok(None),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{AddManga, DeleteManga, PreviewManga, ResolveMangaUrl};
+    use super::*;
+
+    #[test]
+    fn only_writes_are_mutating() {
+        assert!(is_mutating(&Method::POST, AddManga::PATH));
+        assert!(is_mutating(&Method::POST, DeleteManga::PATH));
+        assert!(is_mutating(&Method::POST, PreviewManga::PATH));
+        assert!(is_mutating(&Method::POST, ResolveMangaUrl::PATH));
+        assert!(is_mutating(&Method::POST, "/api/not_yet_written"));
+        assert!(!is_mutating(&Method::POST, RetrieveManga::PATH));
+        assert!(!is_mutating(&Method::POST, Login::PATH));
+
+        assert!(is_mutating(&Method::POST, "/api/v1/series"));
+        assert!(is_mutating(&Method::DELETE, "/api/v1/series/GammaPlus/1"));
+        assert!(!is_mutating(&Method::GET, "/api/v1/series"));
+    }
+
+    #[test]
+    fn signup_refused_when_writes_are_protected() {
+        let auth = AuthConfig {
+            protect_writes: true,
+            allow_signup: false,
+            ..Default::default()
+        };
+        assert!(is_guarded(&auth, &Method::POST, Register::PATH));
+        assert!(!is_guarded(&auth, &Method::POST, Login::PATH));
+
+        let auth = AuthConfig {
+            allow_signup: true,
+            ..auth
+        };
+        assert!(!is_guarded(&auth, &Method::POST, Register::PATH));
+        assert!(is_guarded(&auth, &Method::POST, AddManga::PATH));
+        assert!(!is_guarded(
+            &AuthConfig::default(),
+            &Method::POST,
+            AddManga::PATH
+        ));
+    }
+}
@@ -1,14 +1,29 @@
 use crate::core::types::Paginated;
-use crate::core::types::{Manga, MangaQuery, MangaSource};
+use crate::core::types::{
+    ApiToken, ChapterRecord, JobRun, JobRunDetail, Manga, MangaQuery, MangaSource, RefreshResult,
+    TrackedManga, User,
+};
 use leptos::server;
 use leptos::server_fn::ServerFnError;
 
+#[cfg(feature = "ssr")]
+pub mod auth;
+#[cfg(feature = "ssr")]
+pub mod guard;
 #[cfg(feature = "ssr")]
 pub mod service;
 
 #[cfg(feature = "ssr")]
 use {
-    service::{add_manga_service, delete_manga_service, retrieve_manga_service},
+    auth::{expired_session_cookie, session_cookie, session_token, AuthConfig, Caller},
+    service::{
+        add_manga_service, check_followed_service, create_api_token_service, delete_manga_service,
+        follow_manga_service, login_service, logout_service, mark_read_service,
+        preview_manga_service, refresh_manga_service, register_service, resolve_manga_url_service,
+        resume_manga_service, retrieve_api_tokens_service, retrieve_chapters_service,
+        retrieve_job_run_service, retrieve_job_runs_service, retrieve_manga_service,
+        revoke_api_token_service, session_user_service, tag_manga_service, unfollow_manga_service,
+    },
     sqlx::Pool,
     sqlx::Postgres,
 };
@@ -37,15 +52,150 @@ fn get_webdriver_url() -> Result<String, ServerFnError> {
     Ok(url)
 }
 
+#[cfg(feature = "ssr")]
+fn get_sinks() -> Result<Vec<crate::notify::SinkConfig>, ServerFnError> {
+    use crate::state::AppState;
+    use leptos::prelude::use_context;
+
+    let sinks = use_context::<AppState>()
+        .ok_or(ServerFnError::new("AppState not found from context"))?
+        .sinks;
+
+    Ok(sinks)
+}
+
+#[cfg(feature = "ssr")]
+fn get_auth() -> Result<AuthConfig, ServerFnError> {
+    use crate::state::AppState;
+    use leptos::prelude::use_context;
+
+    let auth = use_context::<AppState>()
+        .ok_or(ServerFnError::new("AppState not found from context"))?
+        .auth;
+
+    Ok(auth)
+}
+
+/// the session token from the request cookies
+#[cfg(feature = "ssr")]
+fn get_session_token() -> Option<String> {
+    use leptos::prelude::use_context;
+
+    use_context::<http::request::Parts>().and_then(|parts| session_token(&parts.headers))
+}
+
+#[cfg(feature = "ssr")]
+fn set_cookie(cookie: &str) -> Result<(), ServerFnError> {
+    use leptos::prelude::use_context;
+
+    let response = use_context::<leptos_axum::ResponseOptions>()
+        .ok_or(ServerFnError::new("ResponseOptions not found from context"))?;
+    let value = http::HeaderValue::from_str(cookie).map_err(ServerFnError::new)?;
+    response.append_header(http::header::SET_COOKIE, value);
+
+    Ok(())
+}
+
+/// the caller authenticated by the api guard, or the user of the session cookie
+#[cfg(feature = "ssr")]
+async fn get_caller() -> Result<Option<Caller>, ServerFnError> {
+    use leptos::prelude::use_context;
+
+    let guarded = use_context::<http::request::Parts>()
+        .and_then(|parts| parts.extensions.get::<Caller>().cloned());
+    if guarded.is_some() {
+        return Ok(guarded);
+    }
+
+    let Some(token) = get_session_token() else {
+        return Ok(None);
+    };
+
+    session_user_service(token, get_db()?)
+        .await
+        .map(|user| user.map(Caller::User))
+        .map_err(ServerFnError::new)
+}
+
+#[cfg(feature = "ssr")]
+async fn get_user() -> Result<Option<User>, ServerFnError> {
+    match get_caller().await? {
+        Some(Caller::User(user)) => Ok(Some(user)),
+        _ => Ok(None),
+    }
+}
+
+/// the logged in user, anonymous access is refused when login is required
+#[cfg(feature = "ssr")]
+async fn authorize() -> Result<Option<User>, ServerFnError> {
+    let caller = get_caller().await?;
+
+    match caller {
+        Some(Caller::User(user)) => Ok(Some(user)),
+        Some(Caller::Admin) => Ok(None),
+        None if get_auth()?.require_login => Err(ServerFnError::new("login required")),
+        None => Ok(None),
+    }
+}
+
+/// only the admin changes every series, see `AuthConfig::anonymous_is_admin`
+#[cfg(feature = "ssr")]
+fn require_admin(caller: &Option<Caller>) -> Result<(), ServerFnError> {
+    match caller {
+        Some(Caller::Admin) => Ok(()),
+        None if get_auth()?.anonymous_is_admin() => Ok(()),
+        _ => Err(ServerFnError::new("admin only")),
+    }
+}
+
+/// users may only change the series they follow, the admin every series
+#[cfg(feature = "ssr")]
+async fn authorize_series(manga_list: &[(MangaSource, String)]) -> Result<(), ServerFnError> {
+    match get_caller().await? {
+        Some(Caller::User(user)) => check_followed_service(user.id, manga_list, get_db()?)
+            .await
+            .map_err(ServerFnError::new),
+        caller => require_admin(&caller),
+    }
+}
+
 #[server]
 pub async fn add_manga(
     manga_id: String,
     source: Option<MangaSource>,
 ) -> Result<Manga, ServerFnError> {
+    let user = authorize().await?;
     let db = get_db()?;
     let webdriver_url = get_webdriver_url()?;
 
-    add_manga_service(manga_id, source, webdriver_url, db)
+    match user {
+        Some(user) => follow_manga_service(user.id, manga_id, source, webdriver_url, db).await,
+        None => add_manga_service(manga_id, source, webdriver_url, db).await,
+    }
+    .map_err(ServerFnError::new)
+}
+
+/// the manga as it would be added, to check it before inserting
+#[server]
+pub async fn preview_manga(
+    manga_id: String,
+    source: Option<MangaSource>,
+) -> Result<Manga, ServerFnError> {
+    authorize().await?;
+    let webdriver_url = get_webdriver_url()?;
+
+    preview_manga_service(manga_id, source, webdriver_url)
+        .await
+        .map_err(ServerFnError::new)
+}
+
+/// the source and id of the series a site url points to
+#[server]
+pub async fn resolve_manga_url(url: String) -> Result<(MangaSource, String), ServerFnError> {
+    authorize().await?;
+    let webdriver_url = get_webdriver_url()?;
+
+    resolve_manga_url_service(url, webdriver_url)
         .await
         .map_err(ServerFnError::new)
 }
@@ -55,21 +205,200 @@ pub async fn retrieve_manga(
     page_number: i64,
     page_size: i64,
     #[server(default)] query_option: MangaQuery,
-) -> Result<Paginated<Vec<(MangaSource, String, Manga)>>, ServerFnError> {
+) -> Result<Paginated<Vec<TrackedManga>>, ServerFnError> {
+    let user = authorize().await?;
     let db = get_db()?;
+    let query_option = MangaQuery {
+        subscriber: user.map(|u| u.id),
+        ..query_option
+    };
 
     retrieve_manga_service(page_number, page_size, query_option, db)
         .await
         .map_err(ServerFnError::new)
 }
 
+#[server]
+pub async fn retrieve_chapters(
+    source: MangaSource,
+    manga_id: String,
+) -> Result<Vec<ChapterRecord>, ServerFnError> {
+    authorize().await?;
+    let db = get_db()?;
+
+    retrieve_chapters_service(source, manga_id, db)
+        .await
+        .map_err(ServerFnError::new)
+}
+
 #[server]
 pub async fn delete_manga(
     #[server(default)] manga_list: Vec<(MangaSource, String)>,
 ) -> Result<u64, ServerFnError> {
+    let caller = get_caller().await?;
+    let db = get_db()?;
+
+    // users only unfollow, deleting a series for everyone is up to the admin
+    match caller {
+        Some(Caller::User(user)) => unfollow_manga_service(user.id, manga_list, db).await,
+        caller => {
+            require_admin(&caller)?;
+            delete_manga_service(manga_list, db).await
+        }
+    }
+    .map_err(ServerFnError::new)
+}
+
+#[server]
+pub async fn refresh_manga(
+    #[server(default)] manga_list: Vec<(MangaSource, String)>,
+    broadcast: bool,
+) -> Result<RefreshResult, ServerFnError> {
+    authorize_series(&manga_list).await?;
+    let db = get_db()?;
+    let webdriver_url = get_webdriver_url()?;
+    let sinks = get_sinks()?;
+
+    refresh_manga_service(manga_list, broadcast, sinks, webdriver_url, db)
+        .await
+        .map_err(ServerFnError::new)
+}
+
+#[server]
+pub async fn resume_manga(
+    #[server(default)] manga_list: Vec<(MangaSource, String)>,
+) -> Result<u64, ServerFnError> {
+    authorize_series(&manga_list).await?;
+    let db = get_db()?;
+
+    resume_manga_service(manga_list, db)
+        .await
+        .map_err(ServerFnError::new)
+}
+
+#[server]
+pub async fn tag_manga(
+    #[server(default)] manga_list: Vec<(MangaSource, String)>,
+    #[server(default)] tags: Vec<String>,
+) -> Result<u64, ServerFnError> {
+    authorize_series(&manga_list).await?;
+    let db = get_db()?;
+
+    tag_manga_service(manga_list, tags, db)
+        .await
+        .map_err(ServerFnError::new)
+}
+
+#[server]
+pub async fn mark_read(
+    #[server(default)] manga_list: Vec<(MangaSource, String)>,
+) -> Result<u64, ServerFnError> {
+    let user = authorize().await?;
+    let db = get_db()?;
+
+    mark_read_service(user.map(|u| u.id), manga_list, db)
+        .await
+        .map_err(ServerFnError::new)
+}
+
+#[server]
+pub async fn retrieve_job_runs(
+    page_number: i64,
+    page_size: i64,
+) -> Result<Paginated<Vec<JobRun>>, ServerFnError> {
+    authorize().await?;
+    let db = get_db()?;
+
+    retrieve_job_runs_service(page_number, page_size, db)
+        .await
+        .map_err(ServerFnError::new)
+}
+
+#[server]
+pub async fn retrieve_job_run(run_id: i64) -> Result<JobRunDetail, ServerFnError> {
+    authorize().await?;
+    let db = get_db()?;
+
+    retrieve_job_run_service(run_id, db)
+        .await
+        .map_err(ServerFnError::new)
+}
+
+#[server]
+pub async fn register(username: String, password: String) -> Result<User, ServerFnError> {
+    if !get_auth()?.allow_signup {
+        return Err(ServerFnError::new("sign up is disabled"));
+    }
+    let db = get_db()?;
+
+    register_service(username.clone(), password.clone(), db)
+        .await
+        .map_err(ServerFnError::new)?;
+
+    login(username, password).await
+}
+
+#[server]
+pub async fn login(username: String, password: String) -> Result<User, ServerFnError> {
+    let db = get_db()?;
+
+    let (user, token) = login_service(username, password, db)
+        .await
+        .map_err(ServerFnError::new)?;
+    set_cookie(&session_cookie(&token, get_auth()?.secure_cookie))?;
+
+    Ok(user)
+}
+
+#[server]
+pub async fn logout() -> Result<(), ServerFnError> {
+    if let Some(token) = get_session_token() {
+        logout_service(token, get_db()?)
+            .await
+            .map_err(ServerFnError::new)?;
+    }
+
+    set_cookie(&expired_session_cookie(get_auth()?.secure_cookie))
+}
+
+#[server]
+pub async fn current_user() -> Result<Option<User>, ServerFnError> {
+    get_user().await
+}
+
+/// returns the token, it can't be retrieved afterward
+#[server]
+pub async fn create_api_token(name: String) -> Result<String, ServerFnError> {
+    let user = get_user()
+        .await?
+        .ok_or(ServerFnError::new("login required"))?;
+    let db = get_db()?;
+
+    create_api_token_service(user.id, name, db)
+        .await
+        .map_err(ServerFnError::new)
+}
+
+#[server]
+pub async fn retrieve_api_tokens() -> Result<Vec<ApiToken>, ServerFnError> {
+    let user = get_user()
+        .await?
+        .ok_or(ServerFnError::new("login required"))?;
+    let db = get_db()?;
+
+    retrieve_api_tokens_service(user.id, db)
+        .await
+        .map_err(ServerFnError::new)
+}
+
+#[server]
+pub async fn revoke_api_token(id: i64) -> Result<u64, ServerFnError> {
+    let user = get_user()
+        .await?
+        .ok_or(ServerFnError::new("login required"))?;
     let db = get_db()?;
 
-    delete_manga_service(manga_list, db)
+    revoke_api_token_service(user.id, id, db)
         .await
         .map_err(ServerFnError::new)
 }
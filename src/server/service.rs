@@ -1,81 +1,215 @@
+use std::collections::HashMap;
+use thiserror::Error;
+
 use crate::{
     core::{
         fetch::FetchError,
-        types::{Manga, MangaQuery, MangaSource, Paginated},
+        resolve::resolve_url,
+        source::FetchContext,
+        types::{
+            ApiToken, ChapterRecord, FetchErrorKind, JobRun, JobRunDetail, Manga, MangaQuery,
+            MangaSource, Paginated, RefreshResult, TrackedManga, User,
+        },
     },
     db::{
-        delete::delete_manga_bulk,
-        inquiry::{get_manga, get_manga_paginated},
-        insert::insert_manga,
+        delete::{
+            delete_api_token, delete_manga_bulk, delete_session, delete_subscriptions,
+            delete_unfollowed_series,
+        },
+        inquiry::{
+            get_api_tokens, get_chapters, get_followed_series, get_job_run, get_job_run_errors,
+            get_job_runs_paginated, get_manga, get_manga_paginated, get_read_states,
+            get_series_health, get_session_user, get_user_by_name,
+        },
+        insert::{
+            insert_api_token, insert_manga, insert_session, insert_subscriptions, insert_user,
+        },
+        update::{
+            mark_added_by_follow, mark_read_bulk, resume_series_bulk, set_series_tags_bulk,
+            use_api_token,
+        },
+    },
+    job::{
+        scheduler::{try_lock, UPDATE_JOB_LOCK_KEY},
+        series::{check_series, SuspendPolicy},
     },
+    notify::{build_sinks, SinkConfig},
 };
 
+use super::auth::{hash_password, hash_token, new_session_token, verify_password, SESSION_DAYS};
+
+/// why adding or removing series failed, the rest api picks its status code from it
+#[derive(Debug, Error, Clone, PartialEq)]
+pub enum SeriesError {
+    /// the request itself is wrong, like an empty id or an id unknown to the source
+    #[error("{0}")]
+    Invalid(String),
+
+    #[error("{0}")]
+    AlreadyTracked(String),
+
+    #[error("{0}")]
+    NotTracked(String),
+
+    /// the source, or the webdriver reading it, couldn't be fetched
+    #[error("{0}")]
+    Upstream(String),
+
+    /// the db failed, or the server can't handle the source
+    #[error("{0}")]
+    Internal(String),
+}
+
 pub async fn add_manga_service(
     manga_id: String,
     source: Option<MangaSource>,
     web_driver_url: String,
     pool: sqlx::PgPool,
-) -> Result<Manga, String> {
+) -> Result<Manga, SeriesError> {
     if source.is_none() {
-        return Err("Source cannot be empty".into());
+        return Err(SeriesError::Invalid("Source cannot be empty".into()));
     }
 
     // check if manga exist or not
     let check_exist = get_manga(source.as_ref().unwrap(), &manga_id, &pool).await;
 
     match check_exist {
-        Ok(_) => return Err("manga already exist in db".into()),
+        Ok(_) => {
+            return Err(SeriesError::AlreadyTracked(
+                "manga already exist in db".into(),
+            ))
+        }
         Err(sqlx::Error::RowNotFound) => (),
-        _ => return Err("error checking manga in db".into()),
+        _ => return Err(SeriesError::Internal("error checking manga in db".into())),
     };
 
-    let manga = source
-        .as_ref()
-        .unwrap()
-        .fetch(&web_driver_url, &manga_id)
-        .await
-        .map_err(|e| {
-            println!("Fetch error: {e:?}");
-
-            match e {
-                FetchError::ReqwestError(err) => err.to_string(),
-                FetchError::JsonDeserializeError(err) => err.to_string(),
-                FetchError::XmlDeserializeError(err) => {
-                    err.unwrap_or("Error on deserializing xml".to_string())
-                }
-                FetchError::ChapterNotFound(err) => err.unwrap_or("Chapter Not Found".to_string()),
-                FetchError::PageNotFound(err) => err.unwrap_or("Page Not Found".to_string()),
-                FetchError::WebDriverSessionError(err) => err.to_string(),
-                FetchError::WebDriverCmdError(err) => err.to_string(),
-            }
-        })?;
+    let manga = preview_manga_service(manga_id.clone(), source.clone(), web_driver_url).await?;
 
     //insert to db
     insert_manga(source.unwrap(), manga_id, manga.clone(), &pool)
         .await
         .map_err(|e| {
             dbg!(&e);
-            "Error inserting manga to db".to_string()
+            SeriesError::Internal("Error inserting manga to db".into())
         })?;
 
     Ok(manga)
 }
 
+/// the manga as fetched from its source, without touching the db
+pub async fn preview_manga_service(
+    manga_id: String,
+    source: Option<MangaSource>,
+    web_driver_url: String,
+) -> Result<Manga, SeriesError> {
+    let Some(source) = source else {
+        return Err(SeriesError::Invalid("Source cannot be empty".into()));
+    };
+    if manga_id.trim().is_empty() {
+        return Err(SeriesError::Invalid("manga id cannot be empty".into()));
+    }
+
+    source.fetch(&web_driver_url, &manga_id).await.map_err(|e| {
+        println!("Fetch error: {e:?}");
+        let message = fetch_error_message(&e);
+        match e.kind {
+            FetchErrorKind::NotFound => SeriesError::Invalid(message),
+            FetchErrorKind::Unsupported => SeriesError::Internal(message),
+            _ => SeriesError::Upstream(message),
+        }
+    })
+}
+
+pub async fn resolve_manga_url_service(
+    url: String,
+    web_driver_url: String,
+) -> Result<(MangaSource, String), String> {
+    if url.trim().is_empty() {
+        return Err("url cannot be empty".into());
+    }
+
+    resolve_url(&FetchContext::new(&web_driver_url), &url)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+fn fetch_error_message(e: &FetchError) -> String {
+    let hint = match e.kind {
+        FetchErrorKind::NotFound => format!(
+            "{} is not found on {}, check the manga id",
+            e.manga_id, e.manga_source
+        ),
+        FetchErrorKind::Blocked => format!("{} is refusing our requests", e.manga_source),
+        FetchErrorKind::Transient => {
+            format!("{} can't be reached, try again later", e.manga_source)
+        }
+        FetchErrorKind::LayoutChanged => format!(
+            "{} page can't be read, the site layout might have changed",
+            e.manga_source
+        ),
+        FetchErrorKind::WebDriverUnavailable => {
+            format!("{} requires webdriver which is unavailable", e.manga_source)
+        }
+        FetchErrorKind::Unsupported => {
+            format!("{} can't be fetched by this server", e.manga_source)
+        }
+    };
+
+    format!("{hint} ({})", e.cause)
+}
+
 pub async fn retrieve_manga_service(
     page_number: i64,
     page_size: i64,
     query_option: MangaQuery,
     pool: sqlx::PgPool,
-) -> Result<Paginated<Vec<(MangaSource, String, Manga)>>, String> {
+) -> Result<Paginated<Vec<TrackedManga>>, String> {
+    let reader = query_option.subscriber;
     let paginated_result = get_manga_paginated(page_number, page_size, query_option, &pool)
         .await
         .map_err(|_| "Error at querying manga")?;
 
+    let keys: Vec<_> = paginated_result
+        .data
+        .iter()
+        .map(|d| (d.source.clone(), d.manga_id.clone()))
+        .collect();
+    let mut health: HashMap<_, _> = get_series_health(&keys, &pool)
+        .await
+        .map_err(|_| "Error at querying series health")?
+        .into_iter()
+        .map(|h| ((h.source.clone(), h.manga_id.clone()), h.into_health()))
+        .collect();
+    let mut read_states: HashMap<_, _> = get_read_states(reader, &keys, &pool)
+        .await
+        .map_err(|_| "Error at querying read states")?
+        .into_iter()
+        .map(|r| ((r.source.clone(), r.manga_id.clone()), r))
+        .collect();
+
     let result = Paginated {
         data: paginated_result
             .data
             .into_iter()
-            .map(|d| (d.source.clone(), d.manga_id.clone(), d.into_manga()))
+            .map(|d| {
+                let key = (d.source.clone(), d.manga_id.clone());
+                let read_state = read_states.remove(&key);
+                // chapters are identified by their title, the url can move with the site
+                let unread = d.latest_chapter_released
+                    && read_state
+                        .as_ref()
+                        .is_none_or(|r| r.chapter_title != d.latest_chapter_title);
+
+                TrackedManga {
+                    health: health.remove(&key),
+                    last_read: read_state.map(|r| r.into_read_state()),
+                    unread,
+                    source: d.source.clone(),
+                    manga_id: d.manga_id.clone(),
+                    tags: d.tags.clone(),
+                    manga: d.into_manga(),
+                }
+            })
             .collect(),
         total_page: paginated_result.total_page,
     };
@@ -83,22 +217,435 @@ pub async fn retrieve_manga_service(
     Ok(result)
 }
 
+pub async fn retrieve_chapters_service(
+    source: MangaSource,
+    manga_id: String,
+    pool: sqlx::PgPool,
+) -> Result<Vec<ChapterRecord>, String> {
+    let rows = get_chapters(&source, &manga_id, &pool)
+        .await
+        .map_err(|_| "Error at querying chapters")?;
+
+    Ok(rows.into_iter().map(|row| row.into_record()).collect())
+}
+
+pub async fn retrieve_job_runs_service(
+    page_number: i64,
+    page_size: i64,
+    pool: sqlx::PgPool,
+) -> Result<Paginated<Vec<JobRun>>, String> {
+    let paginated_result = get_job_runs_paginated(page_number, page_size, &pool)
+        .await
+        .map_err(|_| "Error at querying job runs")?;
+
+    Ok(Paginated {
+        data: paginated_result
+            .data
+            .into_iter()
+            .map(|row| row.into_job_run())
+            .collect(),
+        total_page: paginated_result.total_page,
+    })
+}
+
+pub async fn retrieve_job_run_service(
+    run_id: i64,
+    pool: sqlx::PgPool,
+) -> Result<JobRunDetail, String> {
+    let run = match get_job_run(run_id, &pool).await {
+        Ok(row) => row.into_job_run(),
+        Err(sqlx::Error::RowNotFound) => return Err(format!("job run {run_id} not found")),
+        Err(_) => return Err("Error at querying job run".into()),
+    };
+
+    let errors = get_job_run_errors(run_id, &pool)
+        .await
+        .map_err(|_| "Error at querying job run errors")?
+        .into_iter()
+        .map(|row| row.into_job_run_error())
+        .collect();
+
+    Ok(JobRunDetail { run, errors })
+}
+
 pub async fn delete_manga_service(
     manga_list: Vec<(MangaSource, String)>,
     pool: sqlx::PgPool,
+) -> Result<u64, SeriesError> {
+    if manga_list.is_empty() {
+        return Err(SeriesError::Invalid("manga list cannot be empty".into()));
+    }
+
+    let num_rows = delete_manga_bulk(manga_list, &pool)
+        .await
+        .map_err(|_| SeriesError::Internal("Error at deleting manga".into()))?;
+
+    if num_rows == 0 {
+        return Err(SeriesError::NotTracked("no manga deleted".into()));
+    }
+
+    Ok(num_rows)
+}
+
+/// check the given series right away. Without broadcast the releases found are stored and their
+/// announcements recorded as suppressed, so neither this refresh nor a later run sends them
+pub async fn refresh_manga_service(
+    manga_list: Vec<(MangaSource, String)>,
+    broadcast: bool,
+    sinks: Vec<SinkConfig>,
+    web_driver_url: String,
+    pool: sqlx::PgPool,
+) -> Result<RefreshResult, String> {
+    if manga_list.is_empty() {
+        return Err("manga list cannot be empty".into());
+    }
+
+    if broadcast && sinks.is_empty() {
+        return Err("no notification sink is configured".into());
+    }
+
+    let sinks = build_sinks(&sinks).map_err(|e| format!("invalid notification sink: {e}"))?;
+
+    let mut series = Vec::with_capacity(manga_list.len());
+    for (source, manga_id) in &manga_list {
+        match get_manga(source, manga_id, &pool).await {
+            Ok(row) => series.push(row),
+            Err(sqlx::Error::RowNotFound) => {
+                return Err(format!("{manga_id} from {source} is not tracked"))
+            }
+            Err(_) => return Err("error checking manga in db".into()),
+        }
+    }
+
+    let health = get_series_health(&manga_list, &pool)
+        .await
+        .map_err(|_| "Error at querying series health")?
+        .into_iter()
+        .map(|h| ((h.source.clone(), h.manga_id.clone()), h))
+        .collect();
+
+    // racing the update job or the release reminders would announce the same release twice, a
+    // scheduled tick firing during the refresh waits for it rather than being skipped
+    let mut trx = pool
+        .begin()
+        .await
+        .map_err(|_| "Error at checking running updates")?;
+    let locked = try_lock(UPDATE_JOB_LOCK_KEY, &mut *trx)
+        .await
+        .map_err(|_| "Error at checking running updates")?;
+    if !locked {
+        return Err("an update is running, refresh again once it's done".into());
+    }
+
+    // a refresh is explicitly asked for, suspended series are checked as well
+    let summary = check_series(
+        series,
+        health,
+        &sinks,
+        broadcast,
+        &web_driver_url,
+        &SuspendPolicy::from_env(),
+        &pool,
+    )
+    .await
+    .map_err(|e| {
+        println!("Refresh error: {e}");
+        "Error updating manga in db".to_string()
+    })?;
+
+    trx.commit()
+        .await
+        .map_err(|_| "Error at releasing the update lock")?;
+
+    Ok(RefreshResult {
+        released: summary.released,
+        upcoming: summary.upcoming,
+        no_change: summary.no_change,
+        failed: summary
+            .failed
+            .into_iter()
+            .map(|f| (f.source, f.manga_id, f.error))
+            .collect(),
+        notification_failures: summary.notification_failures,
+    })
+}
+
+pub async fn resume_manga_service(
+    manga_list: Vec<(MangaSource, String)>,
+    pool: sqlx::PgPool,
 ) -> Result<u64, String> {
     if manga_list.is_empty() {
         return Err("manga list cannot be empty".into());
     }
 
-    let num_rows = delete_manga_bulk(manga_list, &pool)
+    let num_rows = resume_series_bulk(manga_list, &pool)
+        .await
+        .map_err(|_| "Error at resuming manga")?;
+
+    if num_rows == 0 {
+        return Err("no suspended manga resumed".into());
+    }
+
+    Ok(num_rows)
+}
+
+/// tags are trimmed, lowercased and deduplicated so routing rules match them reliably
+pub async fn tag_manga_service(
+    manga_list: Vec<(MangaSource, String)>,
+    tags: Vec<String>,
+    pool: sqlx::PgPool,
+) -> Result<u64, String> {
+    if manga_list.is_empty() {
+        return Err("manga list cannot be empty".into());
+    }
+
+    let mut tags: Vec<String> = tags
+        .iter()
+        .map(|tag| tag.trim().to_lowercase())
+        .filter(|tag| !tag.is_empty())
+        .collect();
+    tags.sort();
+    tags.dedup();
+
+    set_series_tags_bulk(manga_list, &tags, &pool)
+        .await
+        .map_err(|_| "Error at tagging manga".into())
+}
+
+/// mark the latest chapter of the series as read, `user_id` is None for anonymous visitors
+pub async fn mark_read_service(
+    user_id: Option<i64>,
+    manga_list: Vec<(MangaSource, String)>,
+    pool: sqlx::PgPool,
+) -> Result<u64, String> {
+    if manga_list.is_empty() {
+        return Err("manga list cannot be empty".into());
+    }
+
+    let read_at = chrono::Local::now().naive_local();
+    let num_rows = mark_read_bulk(user_id, manga_list, read_at, &pool)
+        .await
+        .map_err(|_| "Error at marking manga read")?;
+
+    if num_rows == 0 {
+        return Err("no manga marked read".into());
+    }
+
+    Ok(num_rows)
+}
+
+pub async fn register_service(
+    username: String,
+    password: String,
+    pool: sqlx::PgPool,
+) -> Result<User, String> {
+    let username = username.trim().to_string();
+    if username.is_empty() {
+        return Err("username cannot be empty".into());
+    }
+    if password.len() < 8 {
+        return Err("password must be at least 8 characters".into());
+    }
+
+    let password_hash = hash_password(&password).map_err(|_| "Error at hashing password")?;
+
+    match insert_user(&username, &password_hash, &pool).await {
+        Ok(id) => Ok(User { id, username }),
+        Err(sqlx::Error::Database(e)) if e.is_unique_violation() => {
+            Err(format!("username {username} is taken"))
+        }
+        Err(_) => Err("Error at creating user".into()),
+    }
+}
+
+/// returns the user and the token of its new session
+pub async fn login_service(
+    username: String,
+    password: String,
+    pool: sqlx::PgPool,
+) -> Result<(User, String), String> {
+    let user = match get_user_by_name(username.trim(), &pool).await {
+        Ok(row) if verify_password(&password, &row.password_hash) => row.into_user(),
+        Ok(_) | Err(sqlx::Error::RowNotFound) => return Err("wrong username or password".into()),
+        Err(_) => return Err("Error at querying user".into()),
+    };
+
+    let token = new_session_token();
+    let expires_at = chrono::Local::now().naive_local() + chrono::Duration::days(SESSION_DAYS);
+    insert_session(&hash_token(&token), user.id, expires_at, &pool)
+        .await
+        .map_err(|_| "Error at creating session")?;
+
+    Ok((user, token))
+}
+
+pub async fn logout_service(token: String, pool: sqlx::PgPool) -> Result<(), String> {
+    delete_session(&hash_token(&token), &pool)
+        .await
+        .map_err(|_| "Error at deleting session".into())
+}
+
+pub async fn session_user_service(
+    token: String,
+    pool: sqlx::PgPool,
+) -> Result<Option<User>, String> {
+    let now = chrono::Local::now().naive_local();
+
+    match get_session_user(&hash_token(&token), now, &pool).await {
+        Ok(row) => Ok(Some(row.into_user())),
+        Err(sqlx::Error::RowNotFound) => Ok(None),
+        Err(_) => Err("Error at querying session".into()),
+    }
+}
+
+/// returns the new token, only its hash is kept
+pub async fn create_api_token_service(
+    user_id: i64,
+    name: String,
+    pool: sqlx::PgPool,
+) -> Result<String, String> {
+    let name = name.trim().to_string();
+    if name.is_empty() {
+        return Err("token name cannot be empty".into());
+    }
+
+    let token = new_session_token();
+    match insert_api_token(user_id, &name, &hash_token(&token), &pool).await {
+        Ok(_) => Ok(token),
+        Err(sqlx::Error::Database(e)) if e.is_unique_violation() => {
+            Err(format!("token {name} already exists"))
+        }
+        Err(_) => Err("Error at creating api token".into()),
+    }
+}
+
+pub async fn retrieve_api_tokens_service(
+    user_id: i64,
+    pool: sqlx::PgPool,
+) -> Result<Vec<ApiToken>, String> {
+    let rows = get_api_tokens(user_id, &pool)
+        .await
+        .map_err(|_| "Error at querying api tokens")?;
+
+    Ok(rows.into_iter().map(|row| row.into_api_token()).collect())
+}
+
+pub async fn revoke_api_token_service(
+    user_id: i64,
+    id: i64,
+    pool: sqlx::PgPool,
+) -> Result<u64, String> {
+    let num_rows = delete_api_token(user_id, id, &pool)
+        .await
+        .map_err(|_| "Error at revoking api token")?;
+
+    if num_rows == 0 {
+        return Err("no api token revoked".into());
+    }
+
+    Ok(num_rows)
+}
+
+pub async fn api_token_user_service(
+    token: String,
+    pool: sqlx::PgPool,
+) -> Result<Option<User>, String> {
+    let now = chrono::Local::now().naive_local();
+
+    match use_api_token(&hash_token(&token), now, &pool).await {
+        Ok(row) => Ok(Some(row.into_user())),
+        Err(sqlx::Error::RowNotFound) => Ok(None),
+        Err(_) => Err("Error at querying api token".into()),
+    }
+}
+
+/// follow a series, it is only fetched when nobody tracks it yet
+pub async fn follow_manga_service(
+    user_id: i64,
+    manga_id: String,
+    source: Option<MangaSource>,
+    web_driver_url: String,
+    pool: sqlx::PgPool,
+) -> Result<Manga, SeriesError> {
+    let follow_error = |_: sqlx::Error| SeriesError::Internal("Error at following manga".into());
+    let Some(source) = source else {
+        return Err(SeriesError::Invalid("Source cannot be empty".into()));
+    };
+
+    let manga = match get_manga(&source, &manga_id, &pool).await {
+        Ok(row) => row.into_manga(),
+        Err(sqlx::Error::RowNotFound) => {
+            let manga = add_manga_service(
+                manga_id.clone(),
+                Some(source.clone()),
+                web_driver_url,
+                pool.clone(),
+            )
+            .await?;
+            mark_added_by_follow(&source, &manga_id, &pool)
+                .await
+                .map_err(follow_error)?;
+            manga
+        }
+        Err(_) => return Err(SeriesError::Internal("error checking manga in db".into())),
+    };
+
+    let num_rows = insert_subscriptions(user_id, [(source, manga_id)], &pool)
+        .await
+        .map_err(follow_error)?;
+
+    if num_rows == 0 {
+        return Err(SeriesError::AlreadyTracked("manga already followed".into()));
+    }
+
+    Ok(manga)
+}
+
+/// refuse changing series the user doesn't follow, they are shared with the other users
+pub async fn check_followed_service(
+    user_id: i64,
+    manga_list: &[(MangaSource, String)],
+    pool: sqlx::PgPool,
+) -> Result<(), String> {
+    let followed = get_followed_series(user_id, manga_list, &pool)
+        .await
+        .map_err(|_| "Error at checking followed manga")?;
+
+    if manga_list.iter().any(|manga| !followed.contains(manga)) {
+        return Err("only followed manga can be changed".into());
+    }
+
+    Ok(())
+}
+
+/// unfollow series, the ones created by a follow are deleted once nobody follows them anymore
+pub async fn unfollow_manga_service(
+    user_id: i64,
+    manga_list: Vec<(MangaSource, String)>,
+    pool: sqlx::PgPool,
+) -> Result<u64, SeriesError> {
+    let unfollow_error =
+        |_: sqlx::Error| SeriesError::Internal("Error at unfollowing manga".into());
+    if manga_list.is_empty() {
+        return Err(SeriesError::Invalid("manga list cannot be empty".into()));
+    }
+
+    let mut trx = pool.begin().await.map_err(unfollow_error)?;
+
+    let num_rows = delete_subscriptions(user_id, manga_list.clone(), &mut trx)
         .await
-        .map_err(|_| "Error at deleting manga")?;
+        .map_err(unfollow_error)?;
 
     if num_rows == 0 {
-        return Err("no manga deleted".into());
+        return Err(SeriesError::NotTracked("no manga unfollowed".into()));
     }
 
+    delete_unfollowed_series(manga_list, &mut trx)
+        .await
+        .map_err(|_| SeriesError::Internal("Error at deleting manga".into()))?;
+
+    trx.commit().await.map_err(unfollow_error)?;
+
     Ok(num_rows)
 }
 
@@ -267,27 +814,11 @@ mod tests {
     }
 
     #[tokio::test]
-    async fn delete_manga_error_not_found() {
-        let db = get_test_db("delete_manga_not_found").await.unwrap();
-        match delete_manga_service(
-            vec![(MangaSource::TonariYoungJump, "1234".to_string())],
-            db.0,
-        )
-        .await
-        {
-            Ok(_) => panic!("server fn should error"),
-            Err(err) => {
-                assert_eq!(err.to_string(), "no manga deleted")
-            }
-        }
-    }
-
-    #[tokio::test]
-    async fn delete_manga_success() {
+    async fn retrieve_chapters_after_add() {
         let id = "10834108156641784251";
-        let db = get_test_db("delete_manga").await.unwrap();
+        let db = get_test_db("retrieve_chapters").await.unwrap();
 
-        let _ = add_manga_service(
+        let manga = add_manga_service(
             id.to_string(),
             Some(MangaSource::ShounenJumpPlus),
             "".into(),
@@ -296,8 +827,646 @@ mod tests {
         .await
         .unwrap();
 
-        delete_manga_service(vec![(MangaSource::ShounenJumpPlus, id.to_string())], db.0)
+        let chapters =
+            retrieve_chapters_service(MangaSource::ShounenJumpPlus, id.to_string(), db.0)
+                .await
+                .unwrap();
+
+        assert_eq!(chapters.len(), 1);
+        assert_eq!(chapters[0].title, manga.latest_chapter_title);
+    }
+
+    #[tokio::test]
+    async fn refetch_keeps_recorded_release_date() {
+        use crate::{
+            core::types::Chapter,
+            db::{insert::insert_chapters, model::ChapterRow},
+        };
+
+        let db = get_test_db("refetch_release_date").await.unwrap();
+        let released_at = chrono::Utc::now().fixed_offset() - chrono::Duration::days(7);
+        let chapter = Chapter::new(
+            "chapter 1".into(),
+            "https://example.com/1".into(),
+            "".into(),
+            released_at,
+        );
+        let manga =
+            Manga::from_chapters("title".into(), None, "author".into(), vec![chapter.clone()])
+                .unwrap();
+        insert_manga(MangaSource::GammaPlus, "1".into(), manga, &db.0)
+            .await
+            .unwrap();
+
+        // the same chapter seen again later with another date and url
+        let refetched = Chapter {
+            url: "https://example.com/1?v=2".into(),
+            ..Chapter::new(
+                chapter.title.clone(),
+                chapter.url.clone(),
+                "".into(),
+                chrono::Utc::now().fixed_offset(),
+            )
+        };
+        let row = ChapterRow::from_chapter(MangaSource::GammaPlus, "1".into(), &refetched);
+        let mut conn = db.0.acquire().await.unwrap();
+        insert_chapters([&row], &mut conn).await.unwrap();
+
+        let chapters = retrieve_chapters_service(MangaSource::GammaPlus, "1".into(), db.0.clone())
+            .await
+            .unwrap();
+        assert_eq!(chapters.len(), 1);
+        assert_eq!(chapters[0].url, refetched.url);
+        assert_eq!(
+            chapters[0].release_date.map(|dt| dt.timestamp()),
+            Some(released_at.timestamp())
+        );
+    }
+
+    #[tokio::test]
+    async fn retrieve_manga_with_health() {
+        let id = "10834108156641784251";
+        let db = get_test_db("retrieve_manga_health").await.unwrap();
+
+        add_manga_service(
+            id.to_string(),
+            Some(MangaSource::ShounenJumpPlus),
+            "".into(),
+            db.0.clone(),
+        )
+        .await
+        .unwrap();
+
+        let result = retrieve_manga_service(1, 10, MangaQuery::default(), db.0)
+            .await
+            .unwrap();
+
+        let health = result.data[0].health.as_ref().unwrap();
+        assert!(health.last_success.is_some());
+        assert!(!health.is_failing());
+    }
+
+    #[tokio::test]
+    async fn refresh_manga_success() {
+        let id = "10834108156641784251";
+        let db = get_test_db("refresh_manga").await.unwrap();
+
+        add_manga_service(
+            id.to_string(),
+            Some(MangaSource::ShounenJumpPlus),
+            "".into(),
+            db.0.clone(),
+        )
+        .await
+        .unwrap();
+
+        let result = refresh_manga_service(
+            vec![(MangaSource::ShounenJumpPlus, id.to_string())],
+            false,
+            Vec::new(),
+            "".into(),
+            db.0,
+        )
+        .await
+        .unwrap();
+
+        assert!(result.failed.is_empty());
+        assert_eq!(result.notification_failures, 0);
+    }
+
+    #[tokio::test]
+    async fn refresh_manga_error_not_tracked() {
+        let db = get_test_db("refresh_manga_not_tracked").await.unwrap();
+
+        match refresh_manga_service(
+            vec![(MangaSource::TonariYoungJump, "1234".to_string())],
+            false,
+            Vec::new(),
+            "".into(),
+            db.0,
+        )
+        .await
+        {
+            Ok(_) => panic!("server fn should error"),
+            Err(err) => {
+                assert_eq!(err, "1234 from Tonari Young Jump is not tracked")
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn refresh_manga_refused_while_update_runs() {
+        let db = get_test_db("refresh_manga_locked").await.unwrap();
+        let manga = Manga::from_chapters(
+            "title".into(),
+            None,
+            "author".into(),
+            vec![crate::core::types::Chapter::new(
+                "chapter 1".into(),
+                "https://example.com/1".into(),
+                "".into(),
+                chrono::Utc::now().fixed_offset(),
+            )],
+        )
+        .unwrap();
+        insert_manga(MangaSource::GammaPlus, "1".into(), manga, &db.0)
+            .await
+            .unwrap();
+
+        let mut running = db.0.begin().await.unwrap();
+        assert!(try_lock(UPDATE_JOB_LOCK_KEY, &mut *running).await.unwrap());
+
+        let result = refresh_manga_service(
+            vec![(MangaSource::GammaPlus, "1".into())],
+            false,
+            Vec::new(),
+            "".into(),
+            db.0.clone(),
+        )
+        .await;
+        assert_eq!(
+            result.unwrap_err(),
+            "an update is running, refresh again once it's done"
+        );
+    }
+
+    #[tokio::test]
+    async fn resume_manga_error_not_suspended() {
+        let id = "10834108156641784251";
+        let db = get_test_db("resume_manga_not_suspended").await.unwrap();
+
+        add_manga_service(
+            id.to_string(),
+            Some(MangaSource::ShounenJumpPlus),
+            "".into(),
+            db.0.clone(),
+        )
+        .await
+        .unwrap();
+
+        match resume_manga_service(vec![(MangaSource::ShounenJumpPlus, id.to_string())], db.0).await
+        {
+            Ok(_) => panic!("server fn should error"),
+            Err(err) => {
+                assert_eq!(err.to_string(), "no suspended manga resumed")
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn tag_manga_success() {
+        let id = "10834108156641784251";
+        let db = get_test_db("tag_manga").await.unwrap();
+
+        add_manga_service(
+            id.to_string(),
+            Some(MangaSource::ShounenJumpPlus),
+            "".into(),
+            db.0.clone(),
+        )
+        .await
+        .unwrap();
+
+        let num_rows = tag_manga_service(
+            vec![(MangaSource::ShounenJumpPlus, id.to_string())],
+            vec![
+                " Action".into(),
+                "favorite".into(),
+                "action".into(),
+                "".into(),
+            ],
+            db.0.clone(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(num_rows, 1);
+
+        let series = retrieve_manga_service(1, 10, MangaQuery::default(), db.0)
+            .await
+            .unwrap();
+        assert_eq!(series.data[0].tags, vec!["action", "favorite"]);
+    }
+
+    #[tokio::test]
+    async fn retrieve_job_run_records_errors() {
+        use crate::db::{
+            insert::insert_job_run,
+            model::{JobRunErrorRow, JobRunRow},
+            update::finish_job_run,
+        };
+
+        let db = get_test_db("retrieve_job_run").await.unwrap();
+        let started_at = chrono::Local::now().naive_local();
+
+        let run_id = insert_job_run(started_at, &db.0).await.unwrap();
+        let run = JobRunRow {
+            id: run_id,
+            started_at,
+            finished_at: Some(started_at),
+            checked: 2,
+            failed: 1,
+            ..Default::default()
+        };
+        let errors = [JobRunErrorRow {
+            run_id,
+            source: MangaSource::GammaPlus,
+            manga_id: "1".into(),
+            error_kind: Some(FetchErrorKind::Blocked),
+            error: "403".into(),
+        }];
+        finish_job_run(&run, &errors, &db.0).await.unwrap();
+
+        let runs = retrieve_job_runs_service(1, 10, db.0.clone())
+            .await
+            .unwrap();
+        assert_eq!(runs.data.len(), 1);
+        assert_eq!(runs.total_page, 1);
+
+        let detail = retrieve_job_run_service(run_id, db.0).await.unwrap();
+        assert_eq!(detail.run.checked, 2);
+        assert_eq!(detail.errors.len(), 1);
+        assert_eq!(detail.errors[0].error_kind, Some(FetchErrorKind::Blocked));
+    }
+
+    #[tokio::test]
+    async fn delete_manga_error_not_found() {
+        let db = get_test_db("delete_manga_not_found").await.unwrap();
+        match delete_manga_service(
+            vec![(MangaSource::TonariYoungJump, "1234".to_string())],
+            db.0,
+        )
+        .await
+        {
+            Ok(_) => panic!("server fn should error"),
+            Err(err) => {
+                assert_eq!(err.to_string(), "no manga deleted")
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn delete_manga_success() {
+        let id = "10834108156641784251";
+        let db = get_test_db("delete_manga").await.unwrap();
+
+        let _ = add_manga_service(
+            id.to_string(),
+            Some(MangaSource::ShounenJumpPlus),
+            "".into(),
+            db.0.clone(),
+        )
+        .await
+        .unwrap();
+
+        delete_manga_service(vec![(MangaSource::ShounenJumpPlus, id.to_string())], db.0)
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn login_after_register() {
+        let db = get_test_db("login").await.unwrap();
+
+        let user = register_service("alice".into(), "correct horse".into(), db.0.clone())
+            .await
+            .unwrap();
+
+        match register_service("alice".into(), "another password".into(), db.0.clone()).await {
+            Ok(_) => panic!("username should be taken"),
+            Err(err) => assert_eq!(err, "username alice is taken"),
+        }
+
+        match login_service("alice".into(), "wrong password".into(), db.0.clone()).await {
+            Ok(_) => panic!("login should fail"),
+            Err(err) => assert_eq!(err, "wrong username or password"),
+        }
+
+        let (logged_in, token) =
+            login_service("alice".into(), "correct horse".into(), db.0.clone())
+                .await
+                .unwrap();
+        assert_eq!(logged_in, user);
+
+        let session_user = session_user_service(token.clone(), db.0.clone())
+            .await
+            .unwrap();
+        assert_eq!(session_user, Some(user));
+
+        logout_service(token.clone(), db.0.clone()).await.unwrap();
+        let session_user = session_user_service(token, db.0).await.unwrap();
+        assert_eq!(session_user, None);
+    }
+
+    #[tokio::test]
+    async fn follow_manga_per_user() {
+        let id = "10834108156641784251";
+        let key = (MangaSource::ShounenJumpPlus, id.to_string());
+        let db = get_test_db("follow_manga").await.unwrap();
+
+        let alice = register_service("alice".into(), "correct horse".into(), db.0.clone())
+            .await
+            .unwrap();
+        let bob = register_service("bob".into(), "battery staple".into(), db.0.clone())
+            .await
+            .unwrap();
+
+        for user in [&alice, &bob] {
+            follow_manga_service(
+                user.id,
+                id.to_string(),
+                Some(MangaSource::ShounenJumpPlus),
+                "".into(),
+                db.0.clone(),
+            )
+            .await
+            .unwrap();
+        }
+
+        // the series is tracked once for both users
+        let all = retrieve_manga_service(1, 10, MangaQuery::default(), db.0.clone())
+            .await
+            .unwrap();
+        assert_eq!(all.data.len(), 1);
+
+        unfollow_manga_service(alice.id, vec![key.clone()], db.0.clone())
+            .await
+            .unwrap();
+
+        let followed = |user: &User| MangaQuery {
+            subscriber: Some(user.id),
+            ..Default::default()
+        };
+        let alice_series = retrieve_manga_service(1, 10, followed(&alice), db.0.clone())
+            .await
+            .unwrap();
+        assert!(alice_series.data.is_empty());
+        let bob_series = retrieve_manga_service(1, 10, followed(&bob), db.0.clone())
+            .await
+            .unwrap();
+        assert_eq!(bob_series.data.len(), 1);
+
+        unfollow_manga_service(bob.id, vec![key], db.0.clone())
+            .await
+            .unwrap();
+        let all = retrieve_manga_service(1, 10, MangaQuery::default(), db.0)
+            .await
+            .unwrap();
+        assert!(all.data.is_empty());
+    }
+
+    #[tokio::test]
+    async fn unfollow_keeps_shared_series() {
+        use crate::{core::types::Chapter, db::insert::insert_manga};
+
+        let db = get_test_db("unfollow_shared").await.unwrap();
+        let key = (MangaSource::ShounenJumpPlus, "1".to_string());
+        let manga = Manga::from_chapters(
+            "title".into(),
+            Some("cover".into()),
+            "author".into(),
+            vec![Chapter::new(
+                "chapter 1".into(),
+                "https://example.com/1".into(),
+                "".into(),
+                chrono::Utc::now().fixed_offset(),
+            )],
+        )
+        .unwrap();
+        // added without an account, nobody follows it
+        insert_manga(key.0.clone(), key.1.clone(), manga, &db.0)
+            .await
+            .unwrap();
+
+        let alice = register_service("alice".into(), "correct horse".into(), db.0.clone())
+            .await
+            .unwrap();
+        follow_manga_service(
+            alice.id,
+            key.1.clone(),
+            Some(key.0.clone()),
+            "".into(),
+            db.0.clone(),
+        )
+        .await
+        .unwrap();
+        unfollow_manga_service(alice.id, vec![key], db.0.clone())
+            .await
+            .unwrap();
+
+        let all = retrieve_manga_service(1, 10, MangaQuery::default(), db.0)
+            .await
+            .unwrap();
+        assert_eq!(all.data.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn change_only_followed_series() {
+        use crate::{core::types::Chapter, db::insert::insert_manga};
+
+        let db = get_test_db("check_followed").await.unwrap();
+        let followed = (MangaSource::ShounenJumpPlus, "1".to_string());
+        let other = (MangaSource::ShounenJumpPlus, "2".to_string());
+        for (source, id) in [&followed, &other] {
+            let manga = Manga::from_chapters(
+                "title".into(),
+                Some("cover".into()),
+                "author".into(),
+                vec![Chapter::new(
+                    "chapter 1".into(),
+                    "https://example.com/1".into(),
+                    "".into(),
+                    chrono::Utc::now().fixed_offset(),
+                )],
+            )
+            .unwrap();
+            insert_manga(source.clone(), id.clone(), manga, &db.0)
+                .await
+                .unwrap();
+        }
+
+        let alice = register_service("alice".into(), "correct horse".into(), db.0.clone())
+            .await
+            .unwrap();
+        insert_subscriptions(alice.id, [followed.clone()], &db.0)
+            .await
+            .unwrap();
+
+        check_followed_service(alice.id, &[followed.clone()], db.0.clone())
+            .await
+            .unwrap();
+        assert_eq!(
+            check_followed_service(alice.id, &[followed, other], db.0).await,
+            Err("only followed manga can be changed".into())
+        );
+    }
+
+    #[tokio::test]
+    async fn mark_read_per_reader() {
+        use crate::{core::types::Chapter, db::insert::insert_manga};
+
+        let db = get_test_db("mark_read").await.unwrap();
+        let key = (MangaSource::ShounenJumpPlus, "1".to_string());
+        let released = chrono::Utc::now().fixed_offset() - chrono::Duration::days(1);
+        let manga = Manga::from_chapters(
+            "title".into(),
+            Some("cover".into()),
+            "author".into(),
+            vec![Chapter::new(
+                "chapter 1".into(),
+                "https://example.com/1".into(),
+                "".into(),
+                released,
+            )],
+        )
+        .unwrap();
+        insert_manga(key.0.clone(), key.1.clone(), manga, &db.0)
+            .await
+            .unwrap();
+
+        let alice = register_service("alice".into(), "correct horse".into(), db.0.clone())
+            .await
+            .unwrap();
+        insert_subscriptions(alice.id, [key.clone()], &db.0)
+            .await
+            .unwrap();
+
+        let unread = |subscriber: Option<i64>| MangaQuery {
+            unread_only: true,
+            subscriber,
+            ..Default::default()
+        };
+
+        let series = retrieve_manga_service(1, 10, unread(None), db.0.clone())
+            .await
+            .unwrap();
+        assert_eq!(series.data.len(), 1);
+        assert!(series.data[0].unread);
+
+        mark_read_service(None, vec![key.clone()], db.0.clone())
+            .await
+            .unwrap();
+
+        let series = retrieve_manga_service(1, 10, unread(None), db.0.clone())
+            .await
+            .unwrap();
+        assert!(series.data.is_empty());
+
+        // the anonymous read state isn't shared with users
+        let series = retrieve_manga_service(1, 10, unread(Some(alice.id)), db.0.clone())
+            .await
+            .unwrap();
+        assert_eq!(series.data.len(), 1);
+
+        mark_read_service(Some(alice.id), vec![key], db.0.clone())
+            .await
+            .unwrap();
+        let series = retrieve_manga_service(
+            1,
+            10,
+            MangaQuery {
+                subscriber: Some(alice.id),
+                ..Default::default()
+            },
+            db.0,
+        )
+        .await
+        .unwrap();
+        assert!(!series.data[0].unread);
+        assert_eq!(
+            series.data[0].last_read.as_ref().unwrap().chapter_title,
+            "chapter 1"
+        );
+    }
+
+    #[tokio::test]
+    async fn mark_read_released_chapters() {
+        use crate::{core::types::Chapter, db::insert::insert_manga};
+
+        let db = get_test_db("mark_read_released").await.unwrap();
+        let now = chrono::Utc::now().fixed_offset();
+        for (id, release_date) in [
+            ("released", now - chrono::Duration::days(1)),
+            ("upcoming", now + chrono::Duration::days(1)),
+        ] {
+            let manga = Manga::from_chapters(
+                "title".into(),
+                Some("cover".into()),
+                "author".into(),
+                vec![Chapter::new(
+                    "chapter 1".into(),
+                    format!("https://example.com/{id}/1"),
+                    "".into(),
+                    release_date,
+                )],
+            )
+            .unwrap();
+            insert_manga(MangaSource::ShounenJumpPlus, id.into(), manga, &db.0)
+                .await
+                .unwrap();
+        }
+        let series = |id: &str| (MangaSource::ShounenJumpPlus, id.to_string());
+
+        assert_eq!(
+            mark_read_service(None, vec![series("upcoming")], db.0.clone()).await,
+            Err("no manga marked read".into())
+        );
+        mark_read_service(None, vec![series("released")], db.0.clone())
+            .await
+            .unwrap();
+
+        // the chapter is still read once the site moves it to another url
+        sqlx::query("update series set latest_chapter_url = 'https://example.com/moved'")
+            .execute(&db.0)
+            .await
+            .unwrap();
+        let unread = retrieve_manga_service(
+            1,
+            10,
+            MangaQuery {
+                unread_only: true,
+                ..Default::default()
+            },
+            db.0,
+        )
+        .await
+        .unwrap();
+        assert!(unread.data.is_empty());
+    }
+
+    #[tokio::test]
+    async fn api_token_lifecycle() {
+        let db = get_test_db("api_token").await.unwrap();
+        let alice = register_service("alice".into(), "correct horse".into(), db.0.clone())
+            .await
+            .unwrap();
+
+        let token = create_api_token_service(alice.id, "ci".into(), db.0.clone())
+            .await
+            .unwrap();
+        match create_api_token_service(alice.id, " ci ".into(), db.0.clone()).await {
+            Ok(_) => panic!("token name should be taken"),
+            Err(err) => assert_eq!(err, "token ci already exists"),
+        }
+
+        let user = api_token_user_service(token.clone(), db.0.clone())
+            .await
+            .unwrap();
+        assert_eq!(user, Some(alice.clone()));
+        let unknown = api_token_user_service("unknown".into(), db.0.clone())
+            .await
+            .unwrap();
+        assert_eq!(unknown, None);
+
+        let tokens = retrieve_api_tokens_service(alice.id, db.0.clone())
+            .await
+            .unwrap();
+        assert_eq!(tokens.len(), 1);
+        assert!(tokens[0].last_used_at.is_some());
+
+        revoke_api_token_service(alice.id, tokens[0].id, db.0.clone())
             .await
             .unwrap();
+        let user = api_token_user_service(token, db.0).await.unwrap();
+        assert_eq!(user, None);
     }
 }
@@ -2,9 +2,14 @@ use axum::extract::FromRef;
 use leptos::config::LeptosOptions;
 use sqlx::PgPool;
 
+use crate::{notify::SinkConfig, server::auth::AuthConfig};
+
 #[derive(FromRef, Debug, Clone)]
 pub struct AppState {
     pub leptos_options: LeptosOptions,
     pub pool: PgPool,
     pub webdriver_url: String,
+    /// where release notifications of manual refreshes go
+    pub sinks: Vec<SinkConfig>,
+    pub auth: AuthConfig,
 }